@@ -9,12 +9,13 @@
 //! - Multi-signature guardian verification
 //! - Commitment tracking for replay protection
 
-use near_sdk::store::{LookupSet, IterableMap, IterableSet};
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::store::{LookupMap, LookupSet, IterableMap, IterableSet, Vector};
 use near_sdk::json_types::U128;
-use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde::Serialize;
 use near_sdk::{
-    env, log, near, require, AccountId, NearToken,
-    PanicOnDefault, Promise, BorshStorageKey
+    env, is_promise_success, log, near, require, AccountId, CurveType, Gas, NearToken,
+    PanicOnDefault, Promise, PromiseOrValue, PublicKey, BorshStorageKey
 };
 
 type Balance = u128;
@@ -23,6 +24,34 @@ type Balance = u128;
 
 const MIN_DEPOSIT: Balance = 10_000_000_000_000_000_000_000;      // 0.01 NEAR
 const MAX_DEPOSIT: Balance = 100_000_000_000_000_000_000_000_000; // 100 NEAR
+const METRIC_BUCKETS: u64 = 24;                                    // Hours of rolling metrics
+const NANOS_PER_HOUR: u64 = 3_600_000_000_000;
+const CALLBACK_GAS: Gas = Gas::from_tgas(10);
+const TRANSFER_CALLBACK_GAS: Gas = Gas::from_tgas(25);             // Room to reroute and attach its own callback
+const FT_TRANSFER_GAS: Gas = Gas::from_tgas(10);
+const ADMIN_CALL_GAS: Gas = Gas::from_tgas(50);
+const RECIPIENT_NOTIFY_GAS: Gas = Gas::from_tgas(30);
+const DEPOSIT_TREE_DEPTH: usize = 20;                              // Up to ~1M deposit leaves
+const MAX_NONCE_SCAN: u64 = 5_000;                                 // Deposits summed per cumulative view
+const MAX_COMMITMENT_QUERY: usize = 100;                           // Commitments per batch status view
+const MAX_WITHDRAWAL_QUERY: usize = 100;                           // Withdrawal hashes per batch status view
+const MAX_DEPOSIT_PAGE: u64 = 100;                                 // Deposits per pagination view
+const MAX_CONFIRMATION_BATCH: usize = 100;                         // Deposits per relay confirmation batch
+const MAX_DEPOSIT_BATCH: usize = 20;                               // Ceiling on the batch deposit cap
+const DEFAULT_POOL: u32 = 0;                                       // Anonymity pool of batch deposits
+const DEPOSIT_SCHEMA_VERSION: u8 = 2;                              // Layout of newly stored Deposit records
+const LEGACY_DEPOSIT_SCHEMA_VERSION: u8 = 1;                       // Layout of Deposit records from before pools
+const MAX_FEE_TIERS: usize = 10;                                   // Buckets in the deposit fee curve
+const MAX_DEPOSIT_FEE_BPS: u16 = 500;                              // 5% ceiling on any fee tier
+const BPS_DENOMINATOR: u16 = 10_000;
+const MIN_RELAYER_STAKE: Balance = 1_000_000_000_000_000_000_000_000; // 1 NEAR
+const RELAYER_UNBONDING: u64 = 7 * 24 * NANOS_PER_HOUR;            // Stake stays slashable after deregistering
+const RENOUNCE_DELAY: u64 = 7 * 24 * NANOS_PER_HOUR;               // Window to cancel an ownership renounce
+const ADMIN_PROPOSAL_TTL: u64 = 7 * 24 * NANOS_PER_HOUR;           // Admin proposals lapse unexecuted after this
+const MAX_PAUSE_HISTORY: u32 = 50;                                  // Pause state changes kept on chain
+const STORAGE_RECORD_OVERHEAD: u64 = 40;                            // Bytes NEAR charges per storage record
+const DEPOSIT_GAS: Gas = Gas::from_tgas(20);                        // Measured deposit cost plus headroom
+const BURN_ACCOUNT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
 
 // ============ Storage Keys ============
 
@@ -33,6 +62,30 @@ pub enum StorageKey {
     ProcessedDeposits,
     ProcessedWithdrawals,
     Deposits,
+    GuardianKeys,
+    WithdrawalBatches,
+    SuspendedGuardians,
+    CommitmentNullifiers,
+    Proposals,
+    AccountDeposits,
+    SupportedTokens,
+    RecipientWithdrawals,
+    Relayers,
+    SigningWindows,
+    DepositorWhitelist,
+    PauseHistory,
+    DepositReceipts,
+    GuardianWeights,
+    DepositTreeNodes,
+    AssetTvl,
+    AssetTvlCaps,
+    Admins,
+    AdminProposals,
+    PoolStats,
+    FailedWithdrawals,
+    GuardianSetHashes,
+    GuardianNonces,
+    SignatureFirstSeen,
 }
 
 // ============ Events ============
@@ -42,8 +95,44 @@ pub enum StorageKey {
 pub struct DepositEvent {
     pub depositor: AccountId,
     pub commitment: String,
+    pub pool_id: u32,
     pub amount: U128,
     pub nonce: u64,
+    pub receipt_id: String,
+    pub leaf_index: u64,
+    pub merkle_root: String,
+    /// Part of the attached deposit kept to pay for the deposit's storage
+    pub storage_reserved: U128,
+    /// Layout version of the stored `Deposit` record
+    pub schema_version: u8,
+    pub timestamp: u64,
+}
+
+/// `DepositEvent` reduced to what identifies the deposit, logged while
+/// verbose events are off; the rest is available from `get_deposit`
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CompactDepositEvent {
+    pub nonce: u64,
+    pub commitment: String,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DepositRejectedEvent {
+    pub account: AccountId,
+    pub reason: String,
+    pub amount: U128,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenDepositEvent {
+    pub token: AccountId,
+    pub depositor: AccountId,
+    pub commitment: String,
+    pub amount: U128,
     pub timestamp: u64,
 }
 
@@ -56,6 +145,62 @@ pub struct WithdrawalEvent {
     pub timestamp: u64,
 }
 
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawalForwardedEvent {
+    pub withdrawal_hash: String,
+    pub chain_id: String,
+    pub recipient: String,
+    pub amount: U128,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawalReroutedEvent {
+    pub withdrawal_hash: String,
+    pub recipient: AccountId,
+    pub fallback_recipient: AccountId,
+    pub amount: U128,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawalBatchEvent {
+    pub root: String,
+    pub signers: u32,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DepositsRelayedEvent {
+    pub nonces: Vec<u64>,
+    pub skipped: u32,
+    pub signers: u32,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalEvent {
+    pub proposal_id: u64,
+    pub action: String,
+    pub by: AccountId,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AdminProposalEvent {
+    pub proposal_id: u64,
+    pub method_name: String,
+    pub action: String,
+    pub by: AccountId,
+    pub timestamp: u64,
+}
+
 #[derive(Serialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct GuardianEvent {
@@ -65,6 +210,28 @@ pub struct GuardianEvent {
     pub timestamp: u64,
 }
 
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct IncidentSnapshotEvent {
+    pub total_deposited: U128,
+    pub total_withdrawn: U128,
+    pub contract_balance: U128,
+    pub guardian_count: u32,
+    pub deposit_nonce: u64,
+    pub by: AccountId,
+    pub timestamp: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RelayerEvent {
+    pub relayer: AccountId,
+    pub action: String,
+    pub stake: U128,
+    pub reason: Option<String>,
+    pub timestamp: u64,
+}
+
 // ============ Structs ============
 
 #[near(serializers = [borsh, json])]
@@ -76,6 +243,230 @@ pub struct Deposit {
     pub nonce: u64,
     pub timestamp: u64,
     pub processed: bool,
+    /// Anonymity pool the deposit joined
+    pub pool_id: u32,
+    /// Layout version of this record, for indexers parsing older deposits
+    pub schema_version: u8,
+}
+
+/// `Deposit` as stored by the original contract, schema version 1
+#[near(serializers = [borsh])]
+pub struct LegacyDeposit {
+    pub depositor: AccountId,
+    pub commitment: String,
+    pub amount: U128,
+    pub nonce: u64,
+    pub timestamp: u64,
+    pub processed: bool,
+}
+
+/// Returned by `deposit`; `receipt_id` can be shared to look the deposit up
+/// without revealing anything beyond the public deposit record
+#[near(serializers = [json])]
+pub struct DepositReceipt {
+    pub nonce: u64,
+    pub receipt_id: String,
+}
+
+/// Records written for a deposit ahead of crediting its amount
+struct StoredDeposit {
+    nonce: u64,
+    receipt_id: String,
+    leaf_index: u64,
+    /// Cost of the storage the records added, when the reserve is enabled
+    storage_reserved: Balance,
+}
+
+/// Preimage revealed at withdrawal for a commitment's linked nullifier hash
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct NullifierReveal {
+    pub commitment: String,
+    pub nullifier: String,
+}
+
+/// Where the hub settles a withdrawal: paid out on NEAR, or forwarded to
+/// another spoke chain, with NEAR acting as a relay hop
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Default)]
+pub enum ChainDestination {
+    #[default]
+    Near,
+    Chain { chain_id: String, recipient: String },
+}
+
+/// How a withdrawal settles beyond its hash, recipient and amount. Every
+/// field is covered by the guardian signatures.
+#[near(serializers = [json])]
+#[derive(Clone, Default)]
+pub struct WithdrawalRequest {
+    /// Preimage of the commitment's linked nullifier hash, if it has one
+    pub nullifier: Option<NullifierReveal>,
+    /// Passed to the recipient's `on_bridge_withdrawal` alongside the transfer
+    pub recipient_msg: Option<String>,
+    /// Paid out on NEAR or forwarded to another spoke chain
+    pub destination: ChainDestination,
+    /// Guardian set the signatures were made against, when not the current one
+    pub guardian_set_hash: Option<String>,
+}
+
+/// A guardian's ed25519 signature (hex) over a bridge message and the
+/// time it was produced
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct GuardianSignature {
+    pub guardian_id: AccountId,
+    pub signature: String,
+    /// Signing time in nanoseconds, covered by the signature
+    pub signed_at: u64,
+    /// The guardian's signing nonce, covered by the signature; required
+    /// while `require_guardian_nonces` is on
+    pub nonce: Option<u64>,
+}
+
+/// Bridge parameter guardians can change by proposal
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub enum ParamChange {
+    GuardianThreshold { threshold: u32 },
+    DepositLimits { min: U128, max: U128 },
+    MinSignAge { nanos: u64 },
+    MinGuardianAddInterval { nanos: u64 },
+}
+
+/// A guardian governance proposal
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct Proposal {
+    pub id: u64,
+    pub change: ParamChange,
+    pub proposer: AccountId,
+    pub approvals: Vec<AccountId>,
+    pub rejections: Vec<AccountId>,
+    pub executed: bool,
+    pub created_at: u64,
+}
+
+/// An owner-only call awaiting admin approvals in multisig mode
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct AdminProposal {
+    pub id: u64,
+    pub method_name: String,
+    pub args: String,
+    pub proposer: AccountId,
+    pub approvals: Vec<AccountId>,
+    /// Set once the proposed call has succeeded
+    pub executed: bool,
+    pub created_at: u64,
+    /// Set while the proposed call is in flight
+    pub executing: bool,
+}
+
+/// Staked relayer; stake can be slashed until it is withdrawn
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct Relayer {
+    pub stake: U128,
+    pub registered_at: u64,
+    /// Set by `deregister_relayer`; stake is withdrawable after RELAYER_UNBONDING
+    pub unbonding_since: Option<u64>,
+}
+
+/// When a withdrawal's first valid guardian signature and the one that
+/// reached the threshold were produced
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct SigningWindow {
+    pub first_signed_at: u64,
+    pub quorum_signed_at: u64,
+}
+
+/// Guardian signing latency (first signature to quorum) over processed
+/// withdrawals, in nanoseconds
+#[near(serializers = [json])]
+pub struct SlaStats {
+    pub samples: u64,
+    pub average_latency: u64,
+    pub max_latency: u64,
+    pub target: Option<u64>,
+    /// Withdrawals whose latency exceeded the target in force at the time
+    pub breaches: u64,
+}
+
+/// Guardian-attested snapshot of the bridge configuration
+#[near(serializers = [json])]
+pub struct ConfigAttestation {
+    pub nonce: u64,
+    pub config_hash: String,
+    pub hub_chain_id: String,
+    pub guardians: Vec<AccountId>,
+    pub guardian_threshold: u32,
+    pub total_deposited: U128,
+    pub total_withdrawn: U128,
+    pub signers: u32,
+    pub timestamp: u64,
+}
+
+/// State captured by `emergency_snapshot` in the same call that paused
+/// the bridge
+#[near(serializers = [json])]
+pub struct IncidentSnapshot {
+    pub total_deposited: U128,
+    pub total_withdrawn: U128,
+    pub contract_balance: U128,
+    pub guardian_count: u32,
+    pub deposit_nonce: u64,
+    pub timestamp: u64,
+}
+
+/// Outcome of every check `deposit` applies to an account and amount
+#[near(serializers = [json])]
+pub struct DepositEligibility {
+    pub paused: bool,
+    pub whitelisted: bool,
+    pub within_tx_limits: bool,
+    pub within_daily_limit: bool,
+    pub within_account_cap: bool,
+    pub within_tvl: bool,
+    pub ok: bool,
+}
+
+/// Entry counts per collection, for capacity planning
+#[near(serializers = [json])]
+pub struct StorageStats {
+    pub deposits: u64,
+    pub processed_deposits: u64,
+    pub processed_withdrawals: u64,
+    pub guardians: u64,
+    pub storage_usage: u64,
+}
+
+/// A pause state change and who made it
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct PauseRecord {
+    pub paused: bool,
+    pub actor: AccountId,
+    pub timestamp: u64,
+}
+
+/// Deposit fee charged once TVL reaches `utilization_bps` of the TVL cap
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct FeeTier {
+    pub utilization_bps: u16,
+    pub fee_bps: u16,
+}
+
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Default)]
+pub struct HourlyMetric {
+    pub hour: u64,
+    pub deposit_count: u64,
+    pub deposit_volume: U128,
+    pub withdrawal_count: u64,
+    pub withdrawal_volume: U128,
 }
 
 // ============ Contract ============
@@ -87,11 +478,11 @@ pub struct CashioBridge {
     owner_id: AccountId,
     /// Hub chain ID
     hub_chain_id: String,
-    /// Required guardian signatures for withdrawals
+    /// Summed guardian weight required to authorize withdrawals
     guardian_threshold: u32,
     /// Active guardians
     guardians: IterableSet<AccountId>,
-    /// Processed deposit commitments
+    /// Processed deposit commitments, keyed by `commitment_key`
     processed_deposits: LookupSet<String>,
     /// Processed withdrawal hashes
     processed_withdrawals: LookupSet<String>,
@@ -105,28 +496,284 @@ pub struct CashioBridge {
     total_withdrawn: Balance,
     /// Pause state
     is_paused: bool,
+    /// Whether deposits must be co-signed by the commitment service
+    require_commitment_signature: bool,
+    /// Commitment service signing key
+    commitment_signer: Option<PublicKey>,
+    /// Rolling hourly activity buckets, indexed by hour modulo METRIC_BUCKETS
+    hourly_metrics: Vec<HourlyMetric>,
+    /// Whether withdrawals probe the recipient account before paying out
+    verify_recipient_exists: bool,
+    /// Guardian signing keys
+    guardian_keys: LookupMap<AccountId, PublicKey>,
+    /// Guardian-approved withdrawal batch merkle roots
+    withdrawal_batches: LookupSet<String>,
+    /// Guardians whose signatures are temporarily ignored
+    suspended_guardians: LookupSet<AccountId>,
+    /// Nullifier hashes bound to deposit commitments, removed once revealed
+    commitment_nullifiers: LookupMap<String, String>,
+    /// Minimum time (ns) since a guardian signature was first submitted
+    /// before it counts
+    min_sign_age: u64,
+    /// Minimum native deposit
+    min_deposit: Balance,
+    /// Maximum native deposit
+    max_deposit: Balance,
+    /// Guardian parameter change proposals by id
+    proposals: LookupMap<u64, Proposal>,
+    /// Next proposal id
+    next_proposal_id: u64,
+    /// Cap on native deposits over the rolling metric window
+    daily_deposit_limit: Option<Balance>,
+    /// Cap on an account's cumulative native deposits
+    account_deposit_cap: Option<Balance>,
+    /// Cap on value locked (deposited minus withdrawn)
+    tvl_cap: Option<Balance>,
+    /// Cumulative native deposits per account
+    account_deposits: LookupMap<AccountId, Balance>,
+    /// NEP-141 tokens the bridge accepts; their balances back deposits
+    supported_tokens: IterableSet<AccountId>,
+    /// Smallest withdrawal guardians may process
+    min_withdrawal: Balance,
+    /// Rightmost filled node per level of the deposit merkle tree
+    tree_filled_subtrees: Vec<Vec<u8>>,
+    /// Next deposit tree leaf index
+    tree_next_index: u64,
+    /// Current deposit tree root
+    deposit_root: Vec<u8>,
+    /// Nonce the next config attestation must be signed for
+    attestation_nonce: u64,
+    /// Entries in `processed_deposits`, which cannot count itself
+    processed_deposit_count: u64,
+    /// Entries in `processed_withdrawals`, which cannot count itself
+    processed_withdrawal_count: u64,
+    /// When ownership renounce was scheduled, if pending
+    renounce_scheduled_at: Option<u64>,
+    /// Blocks a commitment's embedded height may trail the current height
+    /// (`None` disables the check)
+    commitment_window: Option<u64>,
+    /// Nanoseconds a recipient must wait between withdrawals (0 disables)
+    withdrawal_cooldown: u64,
+    /// Last withdrawal time per recipient
+    last_withdrawal_ns: LookupMap<AccountId, u64>,
+    /// Deposits accepted, independent of nonce allocation and pruning
+    deposit_count: u64,
+    /// Withdrawals paid out
+    withdrawal_count: u64,
+    /// Bumped whenever guardians are added or removed; bound into
+    /// withdrawal messages so approvals don't outlive the set that gave them
+    guardian_set_epoch: u64,
+    /// Whether guardian accounts are barred from receiving withdrawals
+    exclude_guardian_recipients: bool,
+    /// Registered relayers and their stakes
+    relayers: LookupMap<AccountId, Relayer>,
+    /// Receives slashed relayer stakes
+    treasury_id: AccountId,
+    /// Guardian signing window per processed withdrawal hash
+    signing_windows: LookupMap<String, SigningWindow>,
+    /// Withdrawals contributing to the signing latency stats
+    sla_samples: u64,
+    /// Sum of signing latencies, for the average
+    sla_total_latency: u128,
+    /// Largest signing latency seen
+    sla_max_latency: u64,
+    /// Signing latency guardians are expected to stay within (`None` disables)
+    sla_target: Option<u64>,
+    /// Withdrawals that exceeded `sla_target`
+    sla_breaches: u64,
+    /// Whether only whitelisted accounts may deposit
+    deposit_whitelist_enabled: bool,
+    /// Accounts allowed to deposit while the whitelist is enabled
+    depositor_whitelist: LookupSet<AccountId>,
+    /// Ring buffer of the last MAX_PAUSE_HISTORY pause state changes
+    pause_history: Vector<PauseRecord>,
+    /// Pause state changes ever recorded; the next slot is this modulo
+    /// MAX_PAUSE_HISTORY
+    pause_record_count: u64,
+    /// Receives withdrawals whose transfer to the recipient failed
+    fallback_recipient: Option<AccountId>,
+    /// Minimum nanoseconds between successive `add_guardian` calls (0 disables)
+    min_guardian_add_interval: u64,
+    /// When `add_guardian` last succeeded, if ever
+    last_guardian_change_ns: Option<u64>,
+    /// Deposit nonce by receipt id
+    deposit_receipts: LookupMap<String, u64>,
+    /// Voting weight of guardians whose weight isn't the default of 1
+    guardian_weights: LookupMap<AccountId, u32>,
+    /// Total routed on to other spoke chains instead of paid out on NEAR
+    total_forwarded: Balance,
+    /// Deposit fee tiers by ascending utilization (empty charges nothing)
+    deposit_fee_curve: Vec<FeeTier>,
+    /// Deposit fees held for the treasury, outside of liabilities
+    collected_deposit_fees: Balance,
+    /// Nanoseconds a deposit must age before it can be confirmed relayed
+    /// (0 disables)
+    min_deposit_confirmations_ns: u64,
+    /// Current deposit tree nodes by (level, index), level 0 being leaves;
+    /// absent nodes are empty subtrees
+    deposit_tree_nodes: LookupMap<(u32, u64), Vec<u8>>,
+    /// When a timed pause lapses on its own (`None` for an indefinite pause)
+    auto_unpause_at: Option<u64>,
+    /// Value locked per NEP-141 token
+    asset_tvl: IterableMap<AccountId, Balance>,
+    /// Cap on value locked per NEP-141 token; uncapped tokens are absent
+    asset_tvl_caps: IterableMap<AccountId, Balance>,
+    /// Accounts that approve owner-only calls in admin multisig mode
+    admins: IterableSet<AccountId>,
+    /// Admin approvals an owner-only call needs (0 keeps single-owner mode)
+    admin_threshold: u32,
+    /// Owner-only calls awaiting admin approval, by id
+    admin_proposals: LookupMap<u64, AdminProposal>,
+    /// Next admin proposal id
+    next_admin_proposal_id: u64,
+    /// Native deposit volume and count per anonymity pool
+    pool_stats: LookupMap<u32, (Balance, u64)>,
+    /// Nanoseconds a deposit commitment stays in the replay set before it
+    /// may be pruned (`None` disables pruning)
+    commitment_retention_ns: Option<u64>,
+    /// Next deposit nonce `prune_old_commitments` will inspect
+    commitment_prune_cursor: u64,
+    /// Amounts of withdrawals whose transfer failed, awaiting reissue
+    failed_withdrawals: LookupMap<String, U128>,
+    /// Epoch at which each guardian set (by hex hash) was last current
+    guardian_set_hashes: LookupMap<String, u64>,
+    /// Epochs a guardian set stays valid for withdrawals signed against its
+    /// hash once replaced (0 accepts only the current set)
+    guardian_set_window: u64,
+    /// Commitments accepted per batch deposit
+    max_batch_commitments: u32,
+    /// Whether `deposit` keeps its records' storage cost out of the bridged amount
+    reserve_deposit_storage: bool,
+    /// Withdrawals below this amount need only `small_withdrawal_threshold`
+    /// (0 disables the tier)
+    small_withdrawal_amount: Balance,
+    /// Signature weight required for withdrawals below `small_withdrawal_amount`
+    small_withdrawal_threshold: u32,
+    /// Whether deposits log the full `DepositEvent` or a `CompactDepositEvent`
+    verbose_events: bool,
+    /// Whether guardian signatures must carry, and consume, the guardian's
+    /// current signing nonce
+    require_guardian_nonces: bool,
+    /// Next signing nonce per guardian
+    guardian_nonces: LookupMap<AccountId, u64>,
+    /// When each guardian signature was first submitted, keyed by
+    /// `signature_key`; `min_sign_age` is measured from here
+    signature_first_seen: LookupMap<String, u64>,
+}
+
+/// Contract state as stored by the original contract, read by `migrate`
+#[near(serializers = [borsh])]
+pub struct LegacyCashioBridge {
+    owner_id: AccountId,
+    hub_chain_id: String,
+    guardian_threshold: u32,
+    guardians: IterableSet<AccountId>,
+    processed_deposits: LookupSet<String>,
+    processed_withdrawals: LookupSet<String>,
+    deposits: IterableMap<u64, LegacyDeposit>,
+    deposit_nonce: u64,
+    total_deposited: Balance,
+    total_withdrawn: Balance,
+    is_paused: bool,
 }
 
 #[near]
 impl CashioBridge {
     /// Initialize the bridge contract
+    /// With `initial_guardians` the threshold must be reachable by that set.
+    /// Without, withdrawals stay disabled until enough guardians are added
+    /// (see `withdrawals_ready`).
     #[init]
     pub fn new(
         owner_id: AccountId,
         hub_chain_id: String,
         guardian_threshold: u32,
+        initial_guardians: Option<Vec<AccountId>>,
     ) -> Self {
         require!(!env::state_exists(), "Already initialized");
+        if let Some(guardians) = &initial_guardians {
+            require!(
+                guardian_threshold >= 1 && guardian_threshold as usize <= guardians.len(),
+                "Guardian threshold must be between 1 and the initial guardian count"
+            );
+        }
         
         log!("Initializing Cash.io Bridge");
         log!("Owner: {}", owner_id);
         log!("Hub Chain ID: {}", hub_chain_id);
         log!("Guardian Threshold: {}", guardian_threshold);
         
-        Self {
+        let mut bridge = Self::initial_state(owner_id, hub_chain_id, guardian_threshold);
+        for guardian_id in initial_guardians.unwrap_or_default() {
+            require!(bridge.guardians.insert(guardian_id), "Duplicate initial guardian");
+        }
+        bridge.record_guardian_set();
+        bridge
+    }
+
+    /// Upgrade state written by the original contract to the current
+    /// layout. Guardians, replay markers, totals and the pause flag carry
+    /// over; every other setting starts at its `new` default. Stored
+    /// deposits are rewritten in place as schema version 1 records in the
+    /// default pool, and their commitments marked used under the
+    /// per-depositor keys. Deposits made before the deposit tree existed
+    /// have no leaves in it.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let legacy: LegacyCashioBridge = env::state_read()
+            .unwrap_or_else(|| env::panic_str("No state to migrate"));
+        let LegacyCashioBridge {
             owner_id,
             hub_chain_id,
             guardian_threshold,
+            guardians,
+            processed_deposits,
+            processed_withdrawals,
+            deposits: mut legacy_deposits,
+            deposit_nonce,
+            total_deposited,
+            total_withdrawn,
+            is_paused,
+        } = legacy;
+        // Records are rewritten under the same prefix, so clear the old
+        // entries before the new map writes any
+        let records: Vec<(u64, LegacyDeposit)> = legacy_deposits.drain().collect();
+        drop(legacy_deposits);
+
+        let mut bridge = Self::initial_state(owner_id, hub_chain_id, guardian_threshold);
+        bridge.guardians = guardians;
+        bridge.processed_deposits = processed_deposits;
+        bridge.processed_withdrawals = processed_withdrawals;
+        bridge.deposit_nonce = deposit_nonce;
+        bridge.total_deposited = total_deposited;
+        bridge.total_withdrawn = total_withdrawn;
+        bridge.is_paused = is_paused;
+        for (nonce, legacy) in records {
+            bridge.mark_commitment_used(None, &legacy.depositor, &legacy.commitment);
+            bridge.deposit_count += 1;
+            bridge.deposits.insert(nonce, Deposit {
+                depositor: legacy.depositor,
+                commitment: legacy.commitment,
+                amount: legacy.amount,
+                nonce,
+                timestamp: legacy.timestamp,
+                processed: legacy.processed,
+                pool_id: DEFAULT_POOL,
+                schema_version: LEGACY_DEPOSIT_SCHEMA_VERSION,
+            });
+        }
+        bridge.record_guardian_set();
+        log!("Migrated {} deposits from the original layout", bridge.deposit_count);
+        bridge
+    }
+
+    /// State of a freshly initialized bridge
+    fn initial_state(owner_id: AccountId, hub_chain_id: String, guardian_threshold: u32) -> Self {
+        Self {
+            owner_id: owner_id.clone(),
+            hub_chain_id,
+            guardian_threshold,
             guardians: IterableSet::new(StorageKey::Guardians),
             processed_deposits: LookupSet::new(StorageKey::ProcessedDeposits),
             processed_withdrawals: LookupSet::new(StorageKey::ProcessedWithdrawals),
@@ -135,6 +782,82 @@ impl CashioBridge {
             total_deposited: 0,
             total_withdrawn: 0,
             is_paused: false,
+            require_commitment_signature: false,
+            commitment_signer: None,
+            hourly_metrics: Vec::new(),
+            verify_recipient_exists: false,
+            guardian_keys: LookupMap::new(StorageKey::GuardianKeys),
+            withdrawal_batches: LookupSet::new(StorageKey::WithdrawalBatches),
+            suspended_guardians: LookupSet::new(StorageKey::SuspendedGuardians),
+            commitment_nullifiers: LookupMap::new(StorageKey::CommitmentNullifiers),
+            min_sign_age: 0,
+            min_deposit: MIN_DEPOSIT,
+            max_deposit: MAX_DEPOSIT,
+            proposals: LookupMap::new(StorageKey::Proposals),
+            next_proposal_id: 0,
+            daily_deposit_limit: None,
+            account_deposit_cap: None,
+            tvl_cap: None,
+            account_deposits: LookupMap::new(StorageKey::AccountDeposits),
+            supported_tokens: IterableSet::new(StorageKey::SupportedTokens),
+            min_withdrawal: 0,
+            tree_filled_subtrees: zero_hashes()[..DEPOSIT_TREE_DEPTH].to_vec(),
+            tree_next_index: 0,
+            deposit_root: zero_hashes()[DEPOSIT_TREE_DEPTH].clone(),
+            attestation_nonce: 0,
+            processed_deposit_count: 0,
+            processed_withdrawal_count: 0,
+            renounce_scheduled_at: None,
+            commitment_window: None,
+            withdrawal_cooldown: 0,
+            last_withdrawal_ns: LookupMap::new(StorageKey::RecipientWithdrawals),
+            deposit_count: 0,
+            withdrawal_count: 0,
+            guardian_set_epoch: 0,
+            exclude_guardian_recipients: false,
+            relayers: LookupMap::new(StorageKey::Relayers),
+            treasury_id: owner_id,
+            signing_windows: LookupMap::new(StorageKey::SigningWindows),
+            sla_samples: 0,
+            sla_total_latency: 0,
+            sla_max_latency: 0,
+            sla_target: None,
+            sla_breaches: 0,
+            deposit_whitelist_enabled: false,
+            depositor_whitelist: LookupSet::new(StorageKey::DepositorWhitelist),
+            pause_history: Vector::new(StorageKey::PauseHistory),
+            pause_record_count: 0,
+            fallback_recipient: None,
+            min_guardian_add_interval: 0,
+            last_guardian_change_ns: None,
+            deposit_receipts: LookupMap::new(StorageKey::DepositReceipts),
+            guardian_weights: LookupMap::new(StorageKey::GuardianWeights),
+            total_forwarded: 0,
+            deposit_fee_curve: Vec::new(),
+            collected_deposit_fees: 0,
+            min_deposit_confirmations_ns: 0,
+            deposit_tree_nodes: LookupMap::new(StorageKey::DepositTreeNodes),
+            auto_unpause_at: None,
+            asset_tvl: IterableMap::new(StorageKey::AssetTvl),
+            asset_tvl_caps: IterableMap::new(StorageKey::AssetTvlCaps),
+            admins: IterableSet::new(StorageKey::Admins),
+            admin_threshold: 0,
+            admin_proposals: LookupMap::new(StorageKey::AdminProposals),
+            next_admin_proposal_id: 0,
+            pool_stats: LookupMap::new(StorageKey::PoolStats),
+            commitment_retention_ns: None,
+            commitment_prune_cursor: 0,
+            failed_withdrawals: LookupMap::new(StorageKey::FailedWithdrawals),
+            guardian_set_hashes: LookupMap::new(StorageKey::GuardianSetHashes),
+            guardian_set_window: 0,
+            max_batch_commitments: MAX_DEPOSIT_BATCH as u32,
+            reserve_deposit_storage: false,
+            small_withdrawal_amount: 0,
+            small_withdrawal_threshold: 0,
+            verbose_events: true,
+            require_guardian_nonces: false,
+            guardian_nonces: LookupMap::new(StorageKey::GuardianNonces),
+            signature_first_seen: LookupMap::new(StorageKey::SignatureFirstSeen),
         }
     }
 
@@ -144,8 +867,19 @@ impl CashioBridge {
     pub fn add_guardian(&mut self, guardian_id: AccountId) {
         self.assert_owner();
         require!(!self.guardians.contains(&guardian_id), "Guardian already exists");
+        // Slow down packing the set with new keys from a compromised owner
+        let now = env::block_timestamp();
+        if let Some(last) = self.last_guardian_change_ns {
+            require!(
+                now >= last.saturating_add(self.min_guardian_add_interval),
+                "Guardian added too recently"
+            );
+        }
         
         self.guardians.insert(guardian_id.clone());
+        self.guardian_set_epoch += 1;
+        self.record_guardian_set();
+        self.last_guardian_change_ns = Some(now);
         
         let event = GuardianEvent {
             guardian: guardian_id,
@@ -162,11 +896,16 @@ impl CashioBridge {
         self.assert_owner();
         require!(self.guardians.contains(&guardian_id), "Guardian not found");
         require!(
-            self.guardians.len() > self.guardian_threshold as u32,
+            self.total_guardian_weight() - self.guardian_weight(&guardian_id) >= self.guardian_threshold,
             "Cannot remove: would go below threshold"
         );
         
         self.guardians.remove(&guardian_id);
+        self.guardian_keys.remove(&guardian_id);
+        self.guardian_weights.remove(&guardian_id);
+        self.suspended_guardians.remove(&guardian_id);
+        self.guardian_set_epoch += 1;
+        self.record_guardian_set();
         
         let event = GuardianEvent {
             guardian: guardian_id,
@@ -178,325 +917,6572 @@ impl CashioBridge {
         log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
     }
 
-    /// Update guardian threshold
-    pub fn update_threshold(&mut self, new_threshold: u32) {
+    /// Suspend a guardian's signing without removing it from the set
+    pub fn suspend_guardian(&mut self, guardian_id: AccountId) {
         self.assert_owner();
-        require!(
-            new_threshold <= self.guardians.len(),
-            "Threshold cannot exceed guardian count"
-        );
+        require!(self.guardians.contains(&guardian_id), "Guardian not found");
+        require!(self.suspended_guardians.insert(guardian_id.clone()), "Guardian already suspended");
+        
+        let event = GuardianEvent {
+            guardian: guardian_id,
+            action: "suspended".to_string(),
+            by: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        };
+        
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+    }
+
+    /// Restore a suspended guardian's signing
+    pub fn unsuspend_guardian(&mut self, guardian_id: AccountId) {
+        self.assert_owner();
+        require!(self.suspended_guardians.remove(&guardian_id), "Guardian not suspended");
+        
+        let event = GuardianEvent {
+            guardian: guardian_id,
+            action: "unsuspended".to_string(),
+            by: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        };
+        
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+    }
+
+    /// Set a guardian's voting weight (at least 1)
+    /// The remaining guardian weight must still reach the threshold.
+    pub fn set_guardian_weight(&mut self, guardian_id: AccountId, weight: u32) {
+        self.assert_owner();
+        require!(self.guardians.contains(&guardian_id), "Guardian not found");
+        require!(weight >= 1, "Guardian weight must be at least 1");
+        let total = self.total_guardian_weight() - self.guardian_weight(&guardian_id) + weight;
+        require!(total >= self.guardian_threshold, "Cannot reweight: would go below threshold");
+        
+        if weight == 1 {
+            self.guardian_weights.remove(&guardian_id);
+        } else {
+            self.guardian_weights.insert(guardian_id.clone(), weight);
+        }
+        
+        let event = GuardianEvent {
+            guardian: guardian_id,
+            action: format!("weight set to {}", weight),
+            by: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        };
         
-        self.guardian_threshold = new_threshold;
-        log!("Guardian threshold updated to: {}", new_threshold);
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+    }
+
+    /// Update guardian threshold, as a summed guardian weight
+    pub fn update_threshold(&mut self, new_threshold: u32) {
+        self.assert_owner();
+        self.apply_param_change(ParamChange::GuardianThreshold { threshold: new_threshold });
     }
 
     /// Pause the bridge
     pub fn pause(&mut self) {
         self.assert_owner();
         self.is_paused = true;
+        self.auto_unpause_at = None;
+        self.record_pause_change(true);
         log!("Bridge paused by {}", env::predecessor_account_id());
     }
 
+    /// Pause the bridge for `duration_ns`, after which deposits and
+    /// withdrawals resume without a manual unpause
+    pub fn pause_for(&mut self, duration_ns: u64) {
+        self.assert_owner();
+        require!(duration_ns > 0, "Pause duration must be positive");
+        let resume_at = env::block_timestamp().saturating_add(duration_ns);
+        self.is_paused = true;
+        self.auto_unpause_at = Some(resume_at);
+        self.record_pause_change(true);
+        log!("Bridge paused by {} until {}", env::predecessor_account_id(), resume_at);
+    }
+
+    /// Pause the bridge and return a snapshot of its state taken in the
+    /// same call, for incident response. Called by the owner without
+    /// `signatures`, or by anyone with threshold guardian signatures over
+    /// the incident snapshot message.
+    pub fn emergency_snapshot(&mut self, signatures: Option<Vec<GuardianSignature>>) -> IncidentSnapshot {
+        match signatures {
+            Some(signatures) => {
+                self.assert_guardian_quorum(&self.incident_snapshot_message(), &signatures);
+            }
+            None => self.assert_owner(),
+        }
+        self.is_paused = true;
+        self.auto_unpause_at = None;
+        self.record_pause_change(true);
+
+        let snapshot = IncidentSnapshot {
+            total_deposited: U128(self.total_deposited),
+            total_withdrawn: U128(self.total_withdrawn),
+            contract_balance: U128(env::account_balance().as_yoctonear()),
+            guardian_count: self.guardian_count(),
+            deposit_nonce: self.deposit_nonce,
+            timestamp: env::block_timestamp(),
+        };
+        let event = IncidentSnapshotEvent {
+            total_deposited: snapshot.total_deposited,
+            total_withdrawn: snapshot.total_withdrawn,
+            contract_balance: snapshot.contract_balance,
+            guardian_count: snapshot.guardian_count,
+            deposit_nonce: snapshot.deposit_nonce,
+            by: env::predecessor_account_id(),
+            timestamp: snapshot.timestamp,
+        };
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+        log!("Bridge paused for incident by {}", env::predecessor_account_id());
+        snapshot
+    }
+
     /// Unpause the bridge
     pub fn unpause(&mut self) {
         self.assert_owner();
         self.is_paused = false;
+        self.auto_unpause_at = None;
+        self.record_pause_change(false);
         log!("Bridge unpaused by {}", env::predecessor_account_id());
     }
 
-    /// Transfer ownership
-    pub fn transfer_ownership(&mut self, new_owner: AccountId) {
+    /// Configure commitment co-signing for deposits
+    pub fn set_commitment_signature_mode(&mut self, enabled: bool, signer: Option<PublicKey>) {
         self.assert_owner();
-        log!("Ownership transferred from {} to {}", self.owner_id, new_owner);
-        self.owner_id = new_owner;
+        if let Some(signer) = signer {
+            require!(signer.curve_type() == CurveType::ED25519, "Commitment signer must be ed25519");
+            self.commitment_signer = Some(signer);
+        }
+        require!(
+            !enabled || self.commitment_signer.is_some(),
+            "Commitment signer not set"
+        );
+        
+        self.require_commitment_signature = enabled;
+        log!("Commitment signature requirement set to: {}", enabled);
     }
 
-    // ============ User Functions ============
+    /// Toggle the recipient existence check on withdrawals
+    pub fn set_verify_recipient_exists(&mut self, enabled: bool) {
+        self.assert_owner();
+        self.verify_recipient_exists = enabled;
+        log!("Recipient existence check set to: {}", enabled);
+    }
 
-    /// Deposit NEAR with a privacy commitment
-    #[payable]
-    pub fn deposit(&mut self, commitment: String) -> u64 {
-        require!(!self.is_paused, "Bridge is paused");
-        
-        let amount = env::attached_deposit().as_yoctonear();
-        require!(amount >= MIN_DEPOSIT, "Deposit amount too small");
-        require!(amount <= MAX_DEPOSIT, "Deposit amount too large");
-        require!(!self.processed_deposits.contains(&commitment), "Commitment already used");
-        
-        // Record commitment
-        self.processed_deposits.insert(commitment.clone());
-        
-        let nonce = self.deposit_nonce;
-        self.deposit_nonce += 1;
-        self.total_deposited += amount;
-        
-        let deposit = Deposit {
-            depositor: env::predecessor_account_id(),
-            commitment: commitment.clone(),
-            amount: U128(amount),
-            nonce,
-            timestamp: env::block_timestamp(),
-            processed: false,
-        };
-        
-        self.deposits.insert(nonce, deposit);
-        
-        // Emit event for relayers
-        let event = DepositEvent {
-            depositor: env::predecessor_account_id(),
-            commitment,
-            amount: U128(amount),
-            nonce,
-            timestamp: env::block_timestamp(),
-        };
+    /// Toggle barring guardians from receiving withdrawals (self-dealing)
+    pub fn set_exclude_guardian_recipients(&mut self, enabled: bool) {
+        self.assert_owner();
+        self.exclude_guardian_recipients = enabled;
+        log!("Guardian recipient exclusion set to: {}", enabled);
+    }
+
+    /// Set how long (in nanoseconds) after `submit_guardian_signature` a
+    /// guardian signature must wait before it counts
+    pub fn set_min_sign_age(&mut self, min_sign_age: u64) {
+        self.assert_owner();
+        self.apply_param_change(ParamChange::MinSignAge { nanos: min_sign_age });
+    }
+
+    /// Record when a guardian signature over `message` (hex) was first seen
+    /// on chain. While `min_sign_age` is set, signatures only count once
+    /// submitted here and aged, so a guardian cannot backdate `signed_at`
+    /// to skip the wait. Callable by anyone.
+    pub fn submit_guardian_signature(&mut self, message: String, signature: GuardianSignature) {
+        let message = decode_hex(&message).unwrap_or_else(|| env::panic_str("Invalid message"));
+        require!(self.verify_guardian_signature(&message, &signature), "Invalid guardian signature");
+        let key = signature_key(&signature);
+        if !self.signature_first_seen.contains_key(&key) {
+            self.signature_first_seen.insert(key, env::block_timestamp());
+            log!("Signature from {} submitted", signature.guardian_id);
+        }
+    }
+
+    /// Let withdrawals below `amount` pass with `threshold` signature weight
+    /// instead of the full guardian threshold (`amount` 0 disables)
+    pub fn set_small_withdrawal_tier(&mut self, amount: U128, threshold: u32) {
+        self.assert_owner();
+        require!(threshold >= 1, "Small withdrawal threshold must be at least 1");
+        require!(threshold <= self.guardian_threshold, "Small withdrawal threshold above full threshold");
+        self.small_withdrawal_amount = amount.0;
+        self.small_withdrawal_threshold = threshold;
+        log!("Withdrawals below {} need {} signature weight", amount.0, threshold);
+    }
+
+    /// Require every guardian signature to cover the guardian's current
+    /// signing nonce, which advances each time the signature is used, so no
+    /// single signature can be replayed in another context
+    pub fn set_require_guardian_nonces(&mut self, enabled: bool) {
+        self.assert_owner();
+        self.require_guardian_nonces = enabled;
+        log!("Guardian signing nonces {}", if enabled { "required" } else { "optional" });
+    }
+
+    /// Set whether deposits log the full `DepositEvent` or only its nonce and
+    /// commitment, to cut log gas on high-volume deployments
+    pub fn set_verbose_events(&mut self, verbose: bool) {
+        self.assert_owner();
+        self.verbose_events = verbose;
+        log!("Verbose deposit events {}", if verbose { "enabled" } else { "disabled" });
+    }
+
+    /// Set whether deposits pay for their own storage out of the attached
+    /// deposit, bridging only the remainder
+    pub fn set_deposit_storage_reserve(&mut self, enabled: bool) {
+        self.assert_owner();
+        self.reserve_deposit_storage = enabled;
+        log!("Deposit storage reserve {}", if enabled { "enabled" } else { "disabled" });
+    }
+
+    /// Set how many commitments a batch deposit may carry, up to MAX_DEPOSIT_BATCH
+    pub fn set_max_batch_commitments(&mut self, max: u32) {
+        self.assert_owner();
+        require!(max > 0 && max as usize <= MAX_DEPOSIT_BATCH, "Invalid batch commitment cap");
+        self.max_batch_commitments = max;
+        log!("Batch deposit cap set to {} commitments", max);
+    }
+
+    /// Set how many epochs a replaced guardian set's hash stays accepted
+    /// for withdrawals signed against it
+    pub fn set_guardian_set_window(&mut self, epochs: u64) {
+        self.assert_owner();
+        self.guardian_set_window = epochs;
+        log!("Guardian set window set to {} epochs", epochs);
+    }
+
+    /// Raise the minimum time between guardian additions
+    /// Lowering it takes a guardian proposal, so a compromised owner can't
+    /// lift the limit before packing the set.
+    pub fn set_min_guardian_add_interval(&mut self, interval_ns: u64) {
+        self.assert_owner();
+        require!(
+            interval_ns >= self.min_guardian_add_interval,
+            "Guardian add interval can only be lowered by proposal"
+        );
+        self.apply_param_change(ParamChange::MinGuardianAddInterval { nanos: interval_ns });
+    }
+
+    /// Set optional daily, per-account and TVL deposit caps (`None` disables)
+    pub fn set_deposit_caps(
+        &mut self,
+        daily_limit: Option<U128>,
+        account_cap: Option<U128>,
+        tvl_cap: Option<U128>,
+    ) {
+        self.assert_owner();
+        self.daily_deposit_limit = daily_limit.map(|limit| limit.0);
+        self.account_deposit_cap = account_cap.map(|cap| cap.0);
+        self.tvl_cap = tvl_cap.map(|cap| cap.0);
+        log!("Deposit caps updated");
+    }
+
+    /// Set the deposit fee curve. Each tier's fee applies from its
+    /// utilization of the TVL cap up to the next tier; below the first
+    /// tier, or without a TVL cap, deposits are free.
+    pub fn set_deposit_fee_curve(&mut self, tiers: Vec<FeeTier>) {
+        self.assert_owner();
+        require!(tiers.len() <= MAX_FEE_TIERS, "Too many fee tiers");
+        require!(
+            tiers.windows(2).all(|pair| pair[0].utilization_bps < pair[1].utilization_bps),
+            "Fee tiers must have ascending utilization"
+        );
+        require!(
+            tiers.iter().all(|tier| tier.utilization_bps <= BPS_DENOMINATOR && tier.fee_bps <= MAX_DEPOSIT_FEE_BPS),
+            "Fee tier out of range"
+        );
+        self.deposit_fee_curve = tiers;
+        log!("Deposit fee curve updated");
+    }
+
+    /// Send collected deposit fees to the treasury
+    pub fn claim_deposit_fees(&mut self) -> Promise {
+        self.assert_owner();
+        let fees = std::mem::take(&mut self.collected_deposit_fees);
+        require!(fees > 0, "No deposit fees to claim");
+        log!("Claimed {} in deposit fees", fees);
+        Promise::new(self.treasury_id.clone()).transfer(NearToken::from_yoctonear(fees))
+    }
+
+    /// Set the smallest withdrawal amount to stop dust griefing
+    pub fn set_min_withdrawal(&mut self, min_withdrawal: U128) {
+        self.assert_owner();
+        self.min_withdrawal = min_withdrawal.0;
+        log!("Minimum withdrawal set to {}", min_withdrawal.0);
+    }
+
+    /// Require deposits to embed a recent block height as
+    /// `<commitment>@<height>`, within `window` blocks (`None` to disable)
+    pub fn set_commitment_window(&mut self, window: Option<u64>) {
+        self.assert_owner();
+        self.commitment_window = window;
+        log!("Commitment window set to {:?} blocks", window);
+    }
+
+    /// Set the minimum time between withdrawals to the same recipient
+    pub fn set_withdrawal_cooldown(&mut self, cooldown_ns: u64) {
+        self.assert_owner();
+        self.withdrawal_cooldown = cooldown_ns;
+        log!("Recipient withdrawal cooldown set to {} ns", cooldown_ns);
+    }
+
+    /// Set how long a deposit must age before relayers may confirm it
+    pub fn set_min_deposit_confirmations(&mut self, min_ns: u64) {
+        self.assert_owner();
+        self.min_deposit_confirmations_ns = min_ns;
+        log!("Deposit finality window set to {} ns", min_ns);
+    }
+
+    /// Set how long deposit commitments are kept for replay protection
+    /// before they may be pruned (`None` to disable pruning). It must cover
+    /// the hub's deposit root validity window, which rejects older replays.
+    pub fn set_commitment_retention(&mut self, retention_ns: Option<u64>) {
+        self.assert_owner();
+        self.commitment_retention_ns = retention_ns;
+        log!("Commitment retention set to {:?} ns", retention_ns);
+    }
+
+    /// Set the guardian signing latency target in nanoseconds (`None` to disable)
+    pub fn set_guardian_sla_target(&mut self, target_ns: Option<u64>) {
+        self.assert_owner();
+        self.sla_target = target_ns;
+        log!("Guardian SLA target set to {:?} ns", target_ns);
+    }
+
+    /// Register a NEP-141 token as a bridge asset
+    pub fn add_supported_token(&mut self, token_id: AccountId) {
+        self.assert_owner();
+        require!(self.supported_tokens.insert(token_id.clone()), "Token already supported");
+        log!("Token {} supported", token_id);
+    }
+
+    /// Stop treating a NEP-141 token as a bridge asset
+    pub fn remove_supported_token(&mut self, token_id: AccountId) {
+        self.assert_owner();
+        require!(self.supported_tokens.remove(&token_id), "Token not supported");
+        log!("Token {} no longer supported", token_id);
+    }
+
+    /// Cap the value locked in a NEP-141 token (`None` removes the cap)
+    /// Native NEAR stays governed by the TVL cap in `set_deposit_caps`.
+    pub fn set_asset_tvl_cap(&mut self, token_id: AccountId, cap: Option<U128>) {
+        self.assert_owner();
+        match cap {
+            Some(cap) => {
+                self.asset_tvl_caps.insert(token_id.clone(), cap.0);
+            }
+            None => {
+                self.asset_tvl_caps.remove(&token_id);
+            }
+        }
+        log!("TVL cap for {} set to {:?}", token_id, cap.map(|cap| cap.0));
+    }
+
+    /// Restrict deposits to whitelisted accounts, e.g. during a guarded launch
+    pub fn set_deposit_whitelist_enabled(&mut self, enabled: bool) {
+        self.assert_owner();
+        self.deposit_whitelist_enabled = enabled;
+        log!("Deposit whitelist {}", if enabled { "enabled" } else { "disabled" });
+    }
+
+    /// Allow an account to deposit while the whitelist is enabled
+    pub fn add_whitelisted_depositor(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        require!(self.depositor_whitelist.insert(account_id.clone()), "Depositor already whitelisted");
+        log!("Depositor {} whitelisted", account_id);
+    }
+
+    /// Remove an account from the depositor whitelist
+    pub fn remove_whitelisted_depositor(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        require!(self.depositor_whitelist.remove(&account_id), "Depositor not whitelisted");
+        log!("Depositor {} removed from whitelist", account_id);
+    }
+
+    /// Delete a relayed deposit's record to reclaim storage
+    /// Its commitment stays marked used and the deposit stays counted.
+    pub fn prune_deposit(&mut self, nonce: u64) {
+        self.assert_owner();
+        let deposit = self.deposits.get(&nonce)
+            .unwrap_or_else(|| env::panic_str("Deposit not found"));
+        require!(deposit.processed, "Deposit not yet relayed");
+        let receipt_id = deposit_receipt_id(nonce, &deposit.depositor, &deposit.commitment);
+        self.deposit_receipts.remove(&receipt_id);
+        self.deposits.remove(&nonce);
+        log!("Deposit {} pruned", nonce);
+    }
+
+    /// Drop the replay records of native deposit commitments made before
+    /// `before_ns`, which must predate the retention window. Scans at most
+    /// MAX_NONCE_SCAN deposits from where the last call stopped; deposits
+    /// already pruned keep their commitment. Returns how many were dropped.
+    pub fn prune_old_commitments(&mut self, before_ns: u64) -> u64 {
+        self.assert_owner();
+        let retention = self.commitment_retention_ns
+            .unwrap_or_else(|| env::panic_str("Commitment pruning disabled"));
+        require!(
+            before_ns.saturating_add(retention) <= env::block_timestamp(),
+            "Cutoff within the protected window"
+        );
+        
+        let end = self.deposit_nonce.min(self.commitment_prune_cursor + MAX_NONCE_SCAN);
+        let mut cursor = self.commitment_prune_cursor;
+        let mut pruned = 0;
+        while cursor < end {
+            if let Some(deposit) = self.deposits.get(&cursor) {
+                if deposit.timestamp >= before_ns {
+                    break;
+                }
+                let pooled = pool_commitment(deposit.pool_id, &deposit.commitment);
+                if self.processed_deposits.remove(&commitment_key(None, &deposit.depositor, &pooled)) {
+                    pruned += 1;
+                }
+            }
+            cursor += 1;
+        }
+        self.commitment_prune_cursor = cursor;
+        log!("Pruned {} deposit commitments", pruned);
+        pruned
+    }
+
+    /// Return tokens sent to the bridge by mistake
+    /// Supported tokens, and any token deposits are still locked in, back
+    /// deposits and can never be rescued, even after support is removed.
+    pub fn rescue_tokens(&mut self, token_id: AccountId, recipient: AccountId, amount: U128) -> Promise {
+        self.assert_owner();
+        require!(!self.supported_tokens.contains(&token_id), "Cannot rescue a bridge token");
+        require!(
+            self.asset_tvl.get(&token_id).copied().unwrap_or(0) == 0,
+            "Cannot rescue a token backing deposits"
+        );
+        
+        log!("Rescuing {} of {} to {}", amount.0, token_id, recipient);
+        Promise::new(token_id).function_call(
+            "ft_transfer".to_string(),
+            near_sdk::serde_json::json!({
+                "receiver_id": recipient,
+                "amount": amount,
+                "memo": "cashio rescue",
+            })
+            .to_string()
+            .into_bytes(),
+            NearToken::from_yoctonear(1),
+            FT_TRANSFER_GAS,
+        )
+    }
+
+    /// Set the account that receives slashed relayer stakes
+    pub fn set_treasury(&mut self, treasury_id: AccountId) {
+        self.assert_owner();
+        log!("Treasury set to {}", treasury_id);
+        self.treasury_id = treasury_id;
+    }
+
+    /// Set the account failed withdrawal transfers are rerouted to (`None` to disable)
+    pub fn set_fallback_recipient(&mut self, fallback_recipient: Option<AccountId>) {
+        self.assert_owner();
+        if let Some(fallback) = &fallback_recipient {
+            self.assert_valid_recipient(fallback);
+        }
+        log!("Fallback recipient set to {:?}", fallback_recipient);
+        self.fallback_recipient = fallback_recipient;
+    }
+
+    /// Transfer ownership
+    pub fn transfer_ownership(&mut self, new_owner: AccountId) {
+        self.assert_owner();
+        log!("Ownership transferred from {} to {}", self.owner_id, new_owner);
+        self.owner_id = new_owner;
+    }
+
+    /// Start the delay before ownership can be renounced for good
+    pub fn schedule_renounce(&mut self) {
+        self.assert_owner();
+        require!(self.renounce_scheduled_at.is_none(), "Renounce already scheduled");
+        self.renounce_scheduled_at = Some(env::block_timestamp());
+        log!("Ownership renounce scheduled, final after {}", env::block_timestamp() + RENOUNCE_DELAY);
+    }
+
+    /// Abort a scheduled renounce
+    pub fn cancel_renounce(&mut self) {
+        self.assert_owner();
+        require!(self.renounce_scheduled_at.take().is_some(), "No renounce scheduled");
+        log!("Ownership renounce cancelled");
+    }
+
+    /// Hand ownership to an unusable account once the delay has passed.
+    /// Owner-only methods can never be called again afterwards.
+    pub fn finalize_renounce(&mut self) {
+        self.assert_owner();
+        let scheduled_at = self.renounce_scheduled_at
+            .unwrap_or_else(|| env::panic_str("No renounce scheduled"));
+        require!(
+            env::block_timestamp() >= scheduled_at + RENOUNCE_DELAY,
+            "Renounce delay has not elapsed"
+        );
+        self.renounce_scheduled_at = None;
+        log!("Ownership renounced by {}", self.owner_id);
+        self.owner_id = BURN_ACCOUNT.parse().unwrap();
+        self.admins.clear();
+        self.admin_threshold = 0;
+    }
+
+    /// Require `threshold` of `admins` to approve every owner-only call,
+    /// which then runs as a call from the contract itself. A threshold of 0
+    /// returns to single-owner mode. Once enabled, changing this also takes
+    /// an admin proposal.
+    pub fn set_admin_multisig(&mut self, admins: Vec<AccountId>, threshold: u32) {
+        self.assert_owner();
+        require!(threshold as usize <= admins.len(), "Admin threshold exceeds admin count");
+        self.admins.clear();
+        if threshold > 0 {
+            for admin in admins {
+                require!(self.admins.insert(admin), "Duplicate admin");
+            }
+        }
+        self.admin_threshold = threshold;
+        log!("Admin multisig threshold set to {} of {}", threshold, self.admins.len());
+    }
+
+    /// Propose an owner-only call, e.g. `pause` with args `{}`; the
+    /// proposer's approval counts
+    pub fn propose_admin_action(&mut self, method_name: String, args: String) -> u64 {
+        self.assert_admin();
+        require!(
+            near_sdk::serde_json::from_str::<near_sdk::serde_json::Value>(&args).is_ok(),
+            "Admin action args must be JSON"
+        );
+        let proposer = env::predecessor_account_id();
+        let id = self.next_admin_proposal_id;
+        self.next_admin_proposal_id += 1;
+        self.admin_proposals.insert(id, AdminProposal {
+            id,
+            method_name,
+            args,
+            proposer: proposer.clone(),
+            approvals: vec![proposer],
+            executed: false,
+            created_at: env::block_timestamp(),
+            executing: false,
+        });
+        self.log_admin_proposal_event(id, "created");
+        
+        self.try_execute_admin_proposal(id);
+        id
+    }
+
+    /// Approve an admin proposal, executing it once approvals reach the
+    /// admin threshold. Proposals older than ADMIN_PROPOSAL_TTL can't be
+    /// approved.
+    pub fn approve_admin_action(&mut self, proposal_id: u64) {
+        self.assert_admin();
+        let admin = env::predecessor_account_id();
+        let proposal = self.admin_proposals.get_mut(&proposal_id)
+            .unwrap_or_else(|| env::panic_str("Proposal not found"));
+        require!(!proposal.executed, "Proposal already executed");
+        require!(!proposal.executing, "Proposal execution in flight");
+        require!(
+            env::block_timestamp() <= proposal.created_at.saturating_add(ADMIN_PROPOSAL_TTL),
+            "Proposal expired"
+        );
+        require!(!proposal.approvals.contains(&admin), "Already approved");
+        proposal.approvals.push(admin);
+        self.log_admin_proposal_event(proposal_id, "approved");
+        
+        self.try_execute_admin_proposal(proposal_id);
+    }
+
+    /// Retry an approved admin proposal whose call failed
+    pub fn execute_admin_proposal(&mut self, proposal_id: u64) {
+        self.assert_admin();
+        let proposal = self.admin_proposals.get(&proposal_id)
+            .unwrap_or_else(|| env::panic_str("Proposal not found"));
+        require!(!proposal.executed, "Proposal already executed");
+        require!(!proposal.executing, "Proposal execution in flight");
+        require!(
+            env::block_timestamp() <= proposal.created_at.saturating_add(ADMIN_PROPOSAL_TTL),
+            "Proposal expired"
+        );
+        require!(self.try_execute_admin_proposal(proposal_id), "Not enough admin approvals");
+    }
+
+    /// Callback after an admin proposal's call. The proposal is only marked
+    /// executed if the call succeeded; a failed one can be retried.
+    #[private]
+    pub fn on_admin_proposal_executed(&mut self, proposal_id: u64) -> bool {
+        let success = is_promise_success();
+        if let Some(proposal) = self.admin_proposals.get_mut(&proposal_id) {
+            proposal.executing = false;
+            proposal.executed = success;
+        }
+        self.log_admin_proposal_event(proposal_id, if success { "executed" } else { "failed" });
+        success
+    }
+
+    // ============ Guardian Functions ============
+
+    /// Register the caller's ed25519 signing key
+    pub fn register_guardian_key(&mut self, public_key: PublicKey) {
+        self.assert_guardian();
+        require!(public_key.curve_type() == CurveType::ED25519, "Guardian key must be ed25519");
+        
+        let guardian_id = env::predecessor_account_id();
+        self.guardian_keys.insert(guardian_id.clone(), public_key);
+        log!("Signing key registered for guardian {}", guardian_id);
+    }
+
+    /// Mark a deposit as relayed to the hub chain once it is past the
+    /// finality window
+    pub fn confirm_deposit_relayed(&mut self, nonce: u64) {
+        self.assert_guardian();
+        let min_age = self.min_deposit_confirmations_ns;
+        let deposit = self.deposits.get_mut(&nonce)
+            .unwrap_or_else(|| env::panic_str("Deposit not found"));
+        require!(!deposit.processed, "Deposit already relayed");
+        require!(deposit_is_final(deposit, min_age), "Deposit not yet final");
+        deposit.processed = true;
+        log!("Deposit {} relayed", nonce);
+    }
+
+    /// Mark many deposits relayed with threshold guardian signatures over
+    /// the batch. Missing, already relayed or not yet final nonces are
+    /// skipped.
+    pub fn confirm_deposits_relayed(&mut self, nonces: Vec<u64>, signatures: Vec<GuardianSignature>) {
+        require!(!nonces.is_empty(), "Empty confirmation batch");
+        require!(nonces.len() <= MAX_CONFIRMATION_BATCH, "Too many deposits in batch");
+        let signers = self.assert_guardian_quorum(&self.relay_confirmation_message(&nonces), &signatures);
+        
+        let requested = nonces.len();
+        let min_age = self.min_deposit_confirmations_ns;
+        let mut confirmed = Vec::with_capacity(requested);
+        for nonce in nonces {
+            match self.deposits.get_mut(&nonce) {
+                Some(deposit) if !deposit.processed && deposit_is_final(deposit, min_age) => {
+                    deposit.processed = true;
+                    confirmed.push(nonce);
+                }
+                _ => {}
+            }
+        }
+        
+        let event = DepositsRelayedEvent {
+            skipped: (requested - confirmed.len()) as u32,
+            nonces: confirmed,
+            signers,
+            timestamp: env::block_timestamp(),
+        };
         
         log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
-        log!("Deposit #{}: {} yoctoNEAR from {}", 
-            nonce, 
-            amount, 
-            env::predecessor_account_id()
+    }
+
+    /// Approve a merkle root covering many withdrawals
+    /// Requires threshold guardian signatures over the batch message
+    pub fn commit_withdrawal_batch(&mut self, root: String, signatures: Vec<GuardianSignature>) {
+        require!(!self.paused(), "Bridge is paused");
+        let root_bytes = decode_hex(&root)
+            .filter(|bytes| bytes.len() == 32)
+            .unwrap_or_else(|| env::panic_str("Invalid batch root"));
+        let root = hex::encode(&root_bytes);
+        require!(!self.withdrawal_batches.contains(&root), "Batch already committed");
+        
+        let signers = self.assert_guardian_quorum(&self.batch_message(&root_bytes), &signatures);
+        self.withdrawal_batches.insert(root.clone());
+        
+        let event = WithdrawalBatchEvent {
+            root,
+            signers,
+            timestamp: env::block_timestamp(),
+        };
+        
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+    }
+
+    /// Propose a parameter change; the proposer's approval is counted
+    pub fn propose_param_change(&mut self, change: ParamChange) -> u64 {
+        self.assert_guardian();
+        self.validate_param_change(&change);
+        
+        let proposer = env::predecessor_account_id();
+        let id = self.next_proposal_id;
+        self.next_proposal_id += 1;
+        self.proposals.insert(id, Proposal {
+            id,
+            change,
+            proposer: proposer.clone(),
+            approvals: vec![proposer.clone()],
+            rejections: Vec::new(),
+            executed: false,
+            created_at: env::block_timestamp(),
+        });
+        self.log_proposal_event(id, "created");
+        
+        self.try_execute_proposal(id);
+        id
+    }
+
+    /// Vote on an open proposal, executing it once approvals reach threshold
+    pub fn vote_on_proposal(&mut self, proposal_id: u64, approve: bool) {
+        self.assert_guardian();
+        let voter = env::predecessor_account_id();
+        let proposal = self.proposals.get_mut(&proposal_id)
+            .unwrap_or_else(|| env::panic_str("Proposal not found"));
+        require!(!proposal.executed, "Proposal already executed");
+        require!(
+            !proposal.approvals.contains(&voter) && !proposal.rejections.contains(&voter),
+            "Already voted"
+        );
+        
+        if approve {
+            proposal.approvals.push(voter);
+        } else {
+            proposal.rejections.push(voter);
+        }
+        self.log_proposal_event(proposal_id, if approve { "approved" } else { "rejected" });
+        
+        self.try_execute_proposal(proposal_id);
+    }
+
+    /// Produce a config attestation from threshold guardian signatures over
+    /// `config_hash`. The hash covers the attestation nonce, which advances
+    /// on every attestation so signatures cannot be replayed.
+    pub fn attest_config(&mut self, signatures: Vec<GuardianSignature>) -> ConfigAttestation {
+        let config_hash = self.config_hash();
+        let signers = self.assert_guardian_quorum(&config_hash, &signatures);
+        
+        let attestation = ConfigAttestation {
+            nonce: self.attestation_nonce,
+            config_hash: hex::encode(&config_hash),
+            hub_chain_id: self.hub_chain_id.clone(),
+            guardians: self.get_guardians(),
+            guardian_threshold: self.guardian_threshold,
+            total_deposited: U128(self.total_deposited),
+            total_withdrawn: U128(self.total_withdrawn),
+            signers,
+            timestamp: env::block_timestamp(),
+        };
+        self.attestation_nonce += 1;
+        
+        log!("Config attestation #{} issued", attestation.nonce);
+        attestation
+    }
+
+    // ============ Relayer Functions ============
+
+    /// Register the caller as a relayer, staking the attached NEAR.
+    /// Calling again while registered tops up the stake.
+    #[payable]
+    pub fn register_relayer(&mut self) {
+        let relayer_id = env::predecessor_account_id();
+        let attached = env::attached_deposit().as_yoctonear();
+        let mut relayer = self.relayers.get(&relayer_id).cloned().unwrap_or(Relayer {
+            stake: U128(0),
+            registered_at: env::block_timestamp(),
+            unbonding_since: None,
+        });
+        require!(relayer.unbonding_since.is_none(), "Relayer is unbonding");
+        relayer.stake = U128(relayer.stake.0 + attached);
+        require!(relayer.stake.0 >= MIN_RELAYER_STAKE, "Relayer stake too small");
+        
+        self.log_relayer_event(&relayer_id, "registered", relayer.stake, None);
+        self.relayers.insert(relayer_id, relayer);
+    }
+
+    /// Stop relaying; the stake can be withdrawn after RELAYER_UNBONDING
+    pub fn deregister_relayer(&mut self) {
+        let relayer_id = env::predecessor_account_id();
+        let relayer = self.relayers.get_mut(&relayer_id)
+            .unwrap_or_else(|| env::panic_str("Relayer not registered"));
+        require!(relayer.unbonding_since.is_none(), "Relayer is unbonding");
+        relayer.unbonding_since = Some(env::block_timestamp());
+        let stake = relayer.stake;
+        
+        self.log_relayer_event(&relayer_id, "deregistered", stake, None);
+    }
+
+    /// Return a deregistered relayer's stake once unbonding has finished
+    pub fn withdraw_relayer_stake(&mut self) -> Promise {
+        let relayer_id = env::predecessor_account_id();
+        let relayer = self.relayers.get(&relayer_id)
+            .unwrap_or_else(|| env::panic_str("Relayer not registered"));
+        let since = relayer.unbonding_since
+            .unwrap_or_else(|| env::panic_str("Relayer is not unbonding"));
+        require!(
+            env::block_timestamp() >= since + RELAYER_UNBONDING,
+            "Relayer stake is still unbonding"
+        );
+        let stake = relayer.stake;
+        self.relayers.remove(&relayer_id);
+        
+        self.log_relayer_event(&relayer_id, "withdrawn", stake, None);
+        Promise::new(relayer_id).transfer(NearToken::from_yoctonear(stake.0))
+    }
+
+    /// Move a relayer's whole stake to the treasury, with threshold
+    /// guardian signatures over `slash_message(relayer, reason)`
+    pub fn slash_relayer(
+        &mut self,
+        relayer_id: AccountId,
+        reason: String,
+        signatures: Vec<GuardianSignature>,
+    ) -> Promise {
+        let message = self.slash_message(&relayer_id, &reason);
+        self.assert_guardian_quorum(&message, &signatures);
+        let relayer = self.relayers.remove(&relayer_id)
+            .unwrap_or_else(|| env::panic_str("Relayer not registered"));
+        
+        self.log_relayer_event(&relayer_id, "slashed", relayer.stake, Some(reason));
+        Promise::new(self.treasury_id.clone()).transfer(NearToken::from_yoctonear(relayer.stake.0))
+    }
+
+    // ============ User Functions ============
+
+    /// Deposit NEAR with a privacy commitment into anonymity pool `pool_id`
+    /// Outside the default pool the commitment is namespaced as
+    /// `<pool_id>/<commitment>` for reuse checks, nullifier reveals and the
+    /// deposit tree leaf, so pools never share commitments. With the storage
+    /// reserve enabled, the cost of the storage the deposit's records add
+    /// (at most `deposit_cost_estimate`) is kept back and the remainder is
+    /// bridged.
+    #[payable]
+    pub fn deposit(
+        &mut self,
+        commitment: String,
+        commitment_sig: Option<String>,
+        nullifier_hash: Option<String>,
+        pool_id: u32,
+    ) -> DepositReceipt {
+        let depositor = env::predecessor_account_id();
+        let attached = env::attached_deposit().as_yoctonear();
+        let commitment = self.accept_commitment_height(commitment);
+        let commitment_unused = !self.processed_deposits.contains(&commitment_key(
+            None,
+            &depositor,
+            &pool_commitment(pool_id, &commitment),
+        ));
+        // Records are written ahead of the checks so the reserve is their
+        // measured storage; a rejected deposit reverts them
+        let stored = commitment_unused
+            .then(|| self.store_deposit(&depositor, &commitment, nullifier_hash, pool_id));
+        let storage_reserved = stored.as_ref().map_or(0, |stored| stored.storage_reserved);
+        let amount = attached
+            .checked_sub(storage_reserved)
+            .unwrap_or_else(|| self.reject_deposit(&depositor, "Deposit does not cover storage", attached));
+        let eligibility = self.deposit_eligibility(&depositor, amount);
+        let checks = [
+            (!eligibility.paused, "Bridge is paused"),
+            (eligibility.whitelisted, "Depositor not whitelisted"),
+            (amount >= self.min_deposit, "Deposit amount too small"),
+            (amount <= self.max_deposit, "Deposit amount too large"),
+            (eligibility.within_daily_limit, "Daily deposit limit exceeded"),
+            (eligibility.within_account_cap, "Account deposit cap exceeded"),
+            (eligibility.within_tvl, "TVL cap exceeded"),
+            (commitment_unused, "Commitment already used by depositor"),
+        ];
+        if let Some((_, reason)) = checks.iter().find(|(ok, _)| !ok) {
+            self.reject_deposit(&depositor, reason, amount);
+        }
+        self.assert_commitment_signature(&commitment, commitment_sig);
+        let stored = stored.expect("Unused commitment was stored");
+        self.record_deposit(&depositor, commitment, amount, pool_id, stored)
+    }
+
+    /// Deposit several commitments in one call, each with its own amount.
+    /// `amounts` must sum to the attached deposit; every commitment gets its
+    /// own record, nonce and event. Per-deposit limits apply to each amount
+    /// and the caps to the total. With the storage reserve enabled, each
+    /// amount is bridged net of its own records' storage. Unavailable while
+    /// commitment co-signing is required.
+    #[payable]
+    pub fn deposit_batch(&mut self, commitments: Vec<String>, amounts: Vec<U128>) -> Vec<DepositReceipt> {
+        require!(!commitments.is_empty(), "Empty deposit batch");
+        require!(commitments.len() <= self.max_batch_commitments as usize, "Too many commitments in batch");
+        require!(commitments.len() == amounts.len(), "Commitments and amounts length mismatch");
+        require!(!self.require_commitment_signature, "Batch deposits cannot carry commitment signatures");
+        let depositor = env::predecessor_account_id();
+        let total = env::attached_deposit().as_yoctonear();
+        require!(
+            amounts.iter().try_fold(0u128, |sum, amount| sum.checked_add(amount.0)) == Some(total),
+            "Amounts do not sum to attached deposit"
+        );
+        
+        let commitments: Vec<String> = commitments
+            .into_iter()
+            .map(|commitment| self.accept_commitment_height(commitment))
+            .collect();
+        require!(
+            commitments.iter().enumerate().all(|(i, c)| !commitments[..i].contains(c)),
+            "Duplicate commitment in batch"
+        );
+        let commitments_unused = commitments
+            .iter()
+            .all(|c| !self.processed_deposits.contains(&commitment_key(None, &depositor, c)));
+        // As in `deposit`, records are written first to measure the reserve
+        let stored: Vec<StoredDeposit> = if commitments_unused {
+            commitments
+                .iter()
+                .map(|commitment| self.store_deposit(&depositor, commitment, None, DEFAULT_POOL))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let amounts: Vec<Balance> = amounts
+            .iter()
+            .zip(&stored)
+            .map(|(amount, stored)| amount.0.checked_sub(stored.storage_reserved))
+            .collect::<Option<_>>()
+            .unwrap_or_else(|| self.reject_deposit(&depositor, "Deposit does not cover storage", total));
+        let bridged = amounts.iter().sum();
+        let eligibility = self.deposit_eligibility(&depositor, bridged);
+        let checks = [
+            (!eligibility.paused, "Bridge is paused"),
+            (eligibility.whitelisted, "Depositor not whitelisted"),
+            (amounts.iter().all(|a| *a >= self.min_deposit), "Deposit amount too small"),
+            (amounts.iter().all(|a| *a <= self.max_deposit), "Deposit amount too large"),
+            (eligibility.within_daily_limit, "Daily deposit limit exceeded"),
+            (eligibility.within_account_cap, "Account deposit cap exceeded"),
+            (eligibility.within_tvl, "TVL cap exceeded"),
+            (commitments_unused, "Commitment already used by depositor"),
+        ];
+        if let Some((_, reason)) = checks.iter().find(|(ok, _)| !ok) {
+            self.reject_deposit(&depositor, reason, bridged);
+        }
+        
+        commitments
+            .into_iter()
+            .zip(amounts)
+            .zip(stored)
+            .map(|((commitment, amount), stored)| {
+                self.record_deposit(&depositor, commitment, amount, DEFAULT_POOL, stored)
+            })
+            .collect()
+    }
+
+    /// Process a verified withdrawal from hub chain
+    /// Callable by anyone holding threshold guardian signatures over the
+    /// withdrawal and its `request`, so relayers can submit for guardians
+    /// without gas. When the commitment was deposited with a nullifier hash,
+    /// guardians sign the matching nullifier reveal along with the
+    /// withdrawal, so it cannot be left out or swapped. With a
+    /// `recipient_msg`, the recipient contract's `on_bridge_withdrawal` is
+    /// called alongside the transfer. A destination other than NEAR records
+    /// a forward instead of paying out.
+    /// With a `guardian_set_hash`, guardians sign against that guardian set
+    /// rather than the current epoch, so approvals survive set changes made
+    /// while they were collected, within `guardian_set_window` epochs.
+    /// Withdrawals below `small_withdrawal_amount` need only the reduced
+    /// signature threshold.
+    pub fn process_withdrawal(
+        &mut self,
+        withdrawal_hash: String,
+        recipient: AccountId,
+        amount: U128,
+        signatures: Vec<GuardianSignature>,
+        request: WithdrawalRequest,
+    ) -> PromiseOrValue<()> {
+        let WithdrawalRequest { nullifier, recipient_msg, destination, guardian_set_hash } = request;
+        require!(!self.paused(), "Bridge is paused");
+        require!(amount.0 > 0, "Withdrawal amount must be positive");
+        require!(amount.0 >= self.min_withdrawal, "Withdrawal amount too small");
+        require!(
+            amount.0 <= env::account_balance().as_yoctonear(),
+            "Withdrawal exceeds contract balance"
+        );
+        require!(
+            !self.processed_withdrawals.contains(&withdrawal_hash),
+            "Withdrawal already processed"
+        );
+        let message = match &guardian_set_hash {
+            Some(set_hash) => self.withdrawal_set_message(&withdrawal_hash, &recipient, amount.0, set_hash),
+            None => self.withdrawal_message(&withdrawal_hash, &recipient, amount.0),
+        };
+        let threshold = self.withdrawal_threshold(amount.0);
+        let signing_times = self.assert_guardian_quorum_at(
+            &with_destination(
+                with_recipient_msg(with_nullifier(message, nullifier.as_ref()), recipient_msg.as_deref()),
+                &destination,
+            ),
+            &signatures,
+            threshold,
+        );
+        self.record_signing_window(&withdrawal_hash, signing_times, threshold);
+        if let Some(reveal) = nullifier {
+            self.consume_nullifier(&reveal);
+        }
+        
+        match destination {
+            ChainDestination::Near => {
+                self.execute_withdrawal(withdrawal_hash, recipient, amount, recipient_msg).into()
+            }
+            ChainDestination::Chain { chain_id, recipient } => {
+                require!(recipient_msg.is_none(), "Forwarded withdrawals cannot notify a recipient");
+                self.forward_withdrawal(withdrawal_hash, chain_id, recipient, amount);
+                PromiseOrValue::Value(())
+            }
+        }
+    }
+
+    /// Claim a withdrawal included in a guardian-approved batch
+    /// Callable by anyone holding a valid inclusion proof
+    pub fn claim_from_batch(
+        &mut self,
+        root: String,
+        withdrawal_hash: String,
+        recipient: AccountId,
+        amount: U128,
+        proof: Vec<String>,
+    ) -> Promise {
+        require!(!self.paused(), "Bridge is paused");
+        let root_bytes = decode_hex(&root).unwrap_or_else(|| env::panic_str("Invalid batch root"));
+        require!(self.withdrawal_batches.contains(&hex::encode(&root_bytes)), "Unknown batch root");
+        require!(
+            !self.processed_withdrawals.contains(&withdrawal_hash),
+            "Withdrawal already processed"
+        );
+        
+        let mut node = batch_leaf(&withdrawal_hash, &recipient, amount.0);
+        for sibling in &proof {
+            let sibling = decode_hex(sibling).unwrap_or_else(|| env::panic_str("Invalid merkle proof"));
+            node = hash_pair(&node, &sibling);
+        }
+        require!(node == root_bytes, "Invalid merkle proof");
+        
+        self.execute_withdrawal(withdrawal_hash, recipient, amount, None)
+    }
+
+    /// Re-execute a withdrawal whose transfer failed, paying `new_recipient`
+    /// Requires threshold guardian signatures over the reissue. The hash
+    /// stays processed and the original nullifier stays consumed, so the
+    /// withdrawal still pays out at most once.
+    pub fn reissue_withdrawal(
+        &mut self,
+        withdrawal_hash: String,
+        new_recipient: AccountId,
+        signatures: Vec<GuardianSignature>,
+    ) -> Promise {
+        require!(!self.paused(), "Bridge is paused");
+        let amount = self.failed_withdrawals.get(&withdrawal_hash)
+            .copied()
+            .unwrap_or_else(|| env::panic_str("Withdrawal not failed"));
+        let message = self.reissue_message(&withdrawal_hash, &new_recipient, amount.0);
+        self.assert_guardian_quorum(&message, &signatures);
+        
+        self.failed_withdrawals.remove(&withdrawal_hash);
+        // Marked processed again by execute_withdrawal
+        self.processed_withdrawals.remove(&withdrawal_hash);
+        self.processed_withdrawal_count -= 1;
+        log!("Withdrawal {} reissued to {}", withdrawal_hash, new_recipient);
+        self.execute_withdrawal(withdrawal_hash, new_recipient, amount, None)
+    }
+
+    // ============ Callbacks ============
+
+    /// Callback for the recipient existence probe
+    #[private]
+    pub fn on_recipient_checked(
+        &mut self,
+        withdrawal_hash: String,
+        recipient: AccountId,
+        amount: U128,
+        recipient_msg: Option<String>,
+    ) -> PromiseOrValue<bool> {
+        if !is_promise_success() {
+            // Release the hash so the withdrawal can be resubmitted with a valid recipient
+            self.processed_withdrawals.remove(&withdrawal_hash);
+            self.processed_withdrawal_count -= 1;
+            log!("Withdrawal {} rejected: recipient {} does not exist", withdrawal_hash, recipient);
+            return PromiseOrValue::Value(false);
+        }
+        
+        PromiseOrValue::Promise(self.complete_withdrawal(withdrawal_hash, recipient, amount, recipient_msg))
+    }
+
+    /// Callback for a transfer batched with the recipient's
+    /// `on_bridge_withdrawal`. If the recipient call failed the transfer was
+    /// refunded too, so the withdrawal is unwound and can be resubmitted.
+    #[private]
+    pub fn on_withdrawal_notified(
+        &mut self,
+        withdrawal_hash: String,
+        recipient: AccountId,
+        amount: U128,
+    ) -> bool {
+        if is_promise_success() {
+            return true;
+        }
+        
+        self.total_withdrawn -= amount.0;
+        self.withdrawal_count -= 1;
+        self.processed_withdrawals.remove(&withdrawal_hash);
+        self.processed_withdrawal_count -= 1;
+        log!("Withdrawal {} reverted: recipient {} rejected the notification", withdrawal_hash, recipient);
+        false
+    }
+
+    /// Callback after a plain withdrawal transfer. A failed transfer is
+    /// refunded to the bridge, so it is re-sent to the fallback recipient if
+    /// one is configured, else recorded as failed for guardians to reissue.
+    #[private]
+    pub fn on_withdrawal_transferred(
+        &mut self,
+        withdrawal_hash: String,
+        recipient: AccountId,
+        amount: U128,
+    ) -> PromiseOrValue<bool> {
+        if is_promise_success() {
+            return PromiseOrValue::Value(true);
+        }
+        let Some(fallback_recipient) = self.fallback_recipient.clone() else {
+            self.record_failed_withdrawal(&withdrawal_hash, &recipient, amount);
+            return PromiseOrValue::Value(false);
+        };
+        
+        let event = WithdrawalReroutedEvent {
+            withdrawal_hash: withdrawal_hash.clone(),
+            recipient,
+            fallback_recipient: fallback_recipient.clone(),
+            amount,
+            timestamp: env::block_timestamp(),
+        };
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+        
+        PromiseOrValue::Promise(
+            Promise::new(fallback_recipient.clone())
+                .transfer(NearToken::from_yoctonear(amount.0))
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(CALLBACK_GAS)
+                        .on_withdrawal_rerouted(withdrawal_hash, fallback_recipient, amount),
+                ),
+        )
+    }
+
+    /// Callback after re-sending a failed withdrawal to the fallback
+    /// recipient. If that transfer failed too, the withdrawal is recorded as
+    /// failed for guardians to reissue.
+    #[private]
+    pub fn on_withdrawal_rerouted(
+        &mut self,
+        withdrawal_hash: String,
+        fallback_recipient: AccountId,
+        amount: U128,
+    ) -> bool {
+        if is_promise_success() {
+            return true;
+        }
+        self.record_failed_withdrawal(&withdrawal_hash, &fallback_recipient, amount);
+        false
+    }
+
+    // ============ View Functions ============
+
+    /// Get when a timed pause lapses, if one is in effect
+    pub fn get_auto_unpause_at(&self) -> Option<u64> {
+        self.auto_unpause_at.filter(|_| self.is_paused)
+    }
+
+    /// Get bridge statistics
+    pub fn get_stats(&self) -> (U128, U128, U128, u64, bool) {
+        (
+            U128(env::account_balance().as_yoctonear()),
+            U128(self.total_deposited),
+            U128(self.total_withdrawn),
+            self.deposit_nonce,
+            self.paused(),
+        )
+    }
+
+    /// Get the number of deposits accepted
+    pub fn get_deposit_count(&self) -> u64 {
+        self.deposit_count
+    }
+
+    /// Get the number of withdrawals paid out
+    pub fn get_withdrawal_count(&self) -> u64 {
+        self.withdrawal_count
+    }
+
+    /// Get activity for the last METRIC_BUCKETS hours, oldest first
+    pub fn get_hourly_metrics(&self) -> Vec<HourlyMetric> {
+        let current_hour = env::block_timestamp() / NANOS_PER_HOUR;
+        let mut metrics: Vec<HourlyMetric> = self
+            .hourly_metrics
+            .iter()
+            .filter(|m| m.hour + METRIC_BUCKETS > current_hour)
+            .filter(|m| m.deposit_count > 0 || m.withdrawal_count > 0)
+            .cloned()
+            .collect();
+        metrics.sort_by_key(|m| m.hour);
+        metrics
+    }
+
+    /// Check whether the depositor whitelist is enforced
+    pub fn is_deposit_whitelist_enabled(&self) -> bool {
+        self.deposit_whitelist_enabled
+    }
+
+    /// Check whether `account_id` is on the depositor whitelist
+    pub fn is_depositor_whitelisted(&self, account_id: AccountId) -> bool {
+        self.depositor_whitelist.contains(&account_id)
+    }
+
+    /// Get up to `limit` of the most recent pause state changes, newest first
+    pub fn get_pause_history(&self, limit: u32) -> Vec<PauseRecord> {
+        let len = self.pause_history.len();
+        (0..limit.min(len))
+            .map(|i| {
+                let slot = (self.pause_record_count - 1 - i as u64) % MAX_PAUSE_HISTORY as u64;
+                self.pause_history[slot as u32].clone()
+            })
+            .collect()
+    }
+
+    /// Check whether `account_id` could deposit `amount` right now
+    pub fn can_deposit(&self, account_id: AccountId, amount: U128) -> DepositEligibility {
+        self.deposit_eligibility(&account_id, amount.0)
+    }
+
+    /// Get the value currently locked (deposited minus withdrawn)
+    pub fn get_tvl(&self) -> U128 {
+        U128(self.tvl())
+    }
+
+    /// Get the value locked in a NEP-141 token
+    pub fn get_asset_tvl(&self, token_id: AccountId) -> U128 {
+        U128(self.asset_tvl.get(&token_id).copied().unwrap_or(0))
+    }
+
+    /// Get a NEP-141 token's TVL cap, if any
+    pub fn get_asset_tvl_cap(&self, token_id: AccountId) -> Option<U128> {
+        self.asset_tvl_caps.get(&token_id).copied().map(U128)
+    }
+
+    /// Get the fee, in basis points, the next deposit would pay
+    pub fn get_current_deposit_fee_bps(&self) -> u16 {
+        self.current_deposit_fee_bps()
+    }
+
+    /// Get the deposit fee curve
+    pub fn get_deposit_fee_curve(&self) -> Vec<FeeTier> {
+        self.deposit_fee_curve.clone()
+    }
+
+    /// Get deposit fees collected and not yet claimed
+    pub fn get_collected_deposit_fees(&self) -> U128 {
+        U128(self.collected_deposit_fees)
+    }
+
+    /// Get daily, per-account and TVL deposit caps
+    pub fn get_deposit_caps(&self) -> (Option<U128>, Option<U128>, Option<U128>) {
+        (
+            self.daily_deposit_limit.map(U128),
+            self.account_deposit_cap.map(U128),
+            self.tvl_cap.map(U128),
+        )
+    }
+
+    /// Get the minimum withdrawal amount
+    pub fn get_min_withdrawal(&self) -> U128 {
+        U128(self.min_withdrawal)
+    }
+
+    /// Get the per-recipient withdrawal cooldown in nanoseconds
+    pub fn get_withdrawal_cooldown(&self) -> u64 {
+        self.withdrawal_cooldown
+    }
+
+    /// Get how long commitments are kept before pruning, if enabled
+    pub fn get_commitment_retention(&self) -> Option<u64> {
+        self.commitment_retention_ns
+    }
+
+    /// Get the deposit finality window in nanoseconds
+    pub fn get_min_deposit_confirmations(&self) -> u64 {
+        self.min_deposit_confirmations_ns
+    }
+
+    /// Get the commitment acceptance window in blocks, if enabled
+    pub fn get_commitment_window(&self) -> Option<u64> {
+        self.commitment_window
+    }
+
+    /// Get the deposit merkle tree root (hex) and leaf count
+    pub fn get_deposit_root(&self) -> (String, u64) {
+        (hex::encode(&self.deposit_root), self.tree_next_index)
+    }
+
+    /// Get the native volume deposited into a pool and its deposit count
+    pub fn get_pool_stats(&self, pool_id: u32) -> (U128, u64) {
+        let (volume, count) = self.pool_stats.get(&pool_id).copied().unwrap_or_default();
+        (U128(volume), count)
+    }
+
+    /// Get the sibling hashes (hex) from a deposit leaf up to the root.
+    /// Hashing the leaf with each sibling in turn, on the side given by the
+    /// index bit at that level, yields the current deposit root.
+    pub fn get_merkle_path(&self, leaf_index: u64) -> Vec<String> {
+        require!(leaf_index < self.tree_next_index, "Leaf index out of range");
+        let zeros = zero_hashes();
+        let mut index = leaf_index;
+        let mut path = Vec::with_capacity(DEPOSIT_TREE_DEPTH);
+        for (level, zero) in zeros.iter().take(DEPOSIT_TREE_DEPTH).enumerate() {
+            let sibling = self.deposit_tree_nodes.get(&(level as u32, index ^ 1)).unwrap_or(zero);
+            path.push(hex::encode(sibling));
+            index /= 2;
+        }
+        path
+    }
+
+    /// Get entry counts per collection and total contract storage in bytes
+    pub fn get_storage_stats(&self) -> StorageStats {
+        StorageStats {
+            deposits: self.deposits.len() as u64,
+            processed_deposits: self.processed_deposit_count,
+            processed_withdrawals: self.processed_withdrawal_count,
+            guardians: self.guardians.len() as u64,
+            storage_usage: env::storage_usage(),
+        }
+    }
+
+    /// Estimate what a wallet should attach to `deposit`: the storage cost
+    /// in yoctoNEAR and the gas in TGas. Storage is sized for a first
+    /// deposit from a maximum-length account id with a linked nullifier,
+    /// so typical deposits use less.
+    pub fn deposit_cost_estimate(&self) -> (U128, u64) {
+        (U128(deposit_storage_cost()), DEPOSIT_GAS.as_tgas())
+    }
+
+    /// Get the small withdrawal amount bound and its reduced threshold
+    pub fn get_small_withdrawal_tier(&self) -> (U128, u32) {
+        (U128(self.small_withdrawal_amount), self.small_withdrawal_threshold)
+    }
+
+    /// Get the nonce `guardian_id`'s next signature must carry
+    pub fn get_guardian_nonce(&self, guardian_id: AccountId) -> u64 {
+        self.guardian_nonces.get(&guardian_id).copied().unwrap_or(0)
+    }
+
+    /// Check whether guardian signatures must carry a signing nonce
+    pub fn get_require_guardian_nonces(&self) -> bool {
+        self.require_guardian_nonces
+    }
+
+    /// Check whether deposits log the full `DepositEvent`
+    pub fn get_verbose_events(&self) -> bool {
+        self.verbose_events
+    }
+
+    /// Check whether `deposit` keeps its storage cost out of the bridged amount
+    pub fn get_deposit_storage_reserve(&self) -> bool {
+        self.reserve_deposit_storage
+    }
+
+    /// Describe the public methods and event shapes as JSON, for codegen
+    /// tooling and explorers
+    pub fn get_abi_schema(&self) -> String {
+        near_sdk::serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "methods": ABI_METHODS,
+            "events": ABI_EVENTS,
+        })
+        .to_string()
+    }
+
+    /// Get guardian signing latency stats for processed withdrawals
+    pub fn get_guardian_sla_stats(&self) -> SlaStats {
+        SlaStats {
+            samples: self.sla_samples,
+            average_latency: self.sla_total_latency.checked_div(self.sla_samples as u128).unwrap_or(0) as u64,
+            max_latency: self.sla_max_latency,
+            target: self.sla_target,
+            breaches: self.sla_breaches,
+        }
+    }
+
+    /// Get when a withdrawal's first and quorum-reaching signatures were produced
+    pub fn get_signing_window(&self, withdrawal_hash: String) -> Option<SigningWindow> {
+        self.signing_windows.get(&withdrawal_hash).cloned()
+    }
+
+    /// Get a relayer's registration, if any
+    pub fn get_relayer(&self, relayer_id: AccountId) -> Option<Relayer> {
+        self.relayers.get(&relayer_id).cloned()
+    }
+
+    /// Check whether an account is a registered relayer that is not unbonding
+    pub fn is_staked_relayer(&self, relayer_id: AccountId) -> bool {
+        self.relayers
+            .get(&relayer_id)
+            .is_some_and(|relayer| relayer.unbonding_since.is_none())
+    }
+
+    /// Get the current guardian set epoch
+    pub fn get_guardian_set_epoch(&self) -> u64 {
+        self.guardian_set_epoch
+    }
+
+    /// Get how many commitments a batch deposit may carry
+    pub fn get_max_batch_commitments(&self) -> u32 {
+        self.max_batch_commitments
+    }
+
+    /// Get the current guardian set hash (hex) withdrawals may sign against
+    pub fn get_guardian_set_hash(&self) -> String {
+        hex::encode(self.guardian_set_hash())
+    }
+
+    /// Get how many epochs a replaced guardian set stays accepted
+    pub fn get_guardian_set_window(&self) -> u64 {
+        self.guardian_set_window
+    }
+
+    /// Get the withdrawal message (hex) `process_withdrawal` verifies
+    /// against at `epoch`. Guardians sign it wrapped with their `signed_at`.
+    pub fn preview_withdrawal_digest(
+        &self,
+        withdrawal_hash: String,
+        recipient: AccountId,
+        amount: U128,
+        epoch: u64,
+        recipient_msg: Option<String>,
+        destination: ChainDestination,
+    ) -> String {
+        hex::encode(with_destination(
+            with_recipient_msg(
+                self.withdrawal_digest(&withdrawal_hash, &recipient, amount.0, epoch),
+                recipient_msg.as_deref(),
+            ),
+            &destination,
+        ))
+    }
+
+    /// Get the config hash (hex) guardians sign for the next attestation
+    pub fn get_config_hash(&self) -> (String, u64) {
+        (hex::encode(self.config_hash()), self.attestation_nonce)
+    }
+
+    /// Get supported NEP-141 tokens
+    pub fn get_supported_tokens(&self) -> Vec<AccountId> {
+        self.supported_tokens.iter().cloned().collect()
+    }
+
+    /// Check whether guardians are barred from receiving withdrawals
+    pub fn get_exclude_guardian_recipients(&self) -> bool {
+        self.exclude_guardian_recipients
+    }
+
+    /// Check whether withdrawals probe the recipient account first
+    pub fn get_verify_recipient_exists(&self) -> bool {
+        self.verify_recipient_exists
+    }
+
+    /// Get deposit details by nonce
+    pub fn get_deposit(&self, nonce: u64) -> Option<Deposit> {
+        self.deposits.get(&nonce).cloned()
+    }
+
+    /// Get up to `limit` deposits not yet relayed, starting at nonce `from`.
+    /// Scans at most `MAX_NONCE_SCAN` nonces; resume from the last returned
+    /// nonce + 1.
+    pub fn get_unprocessed_deposits(&self, from: u64, limit: u64) -> Vec<Deposit> {
+        let limit = limit.min(MAX_DEPOSIT_PAGE) as usize;
+        let end = from.saturating_add(MAX_NONCE_SCAN).min(self.deposit_nonce);
+        (from..end)
+            .filter_map(|n| self.deposits.get(&n))
+            .filter(|deposit| !deposit.processed)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Get issued nonces in `from..to` with no deposit record, e.g. pruned
+    /// deposits. Nonces not yet issued are not gaps. The range may span at
+    /// most `MAX_NONCE_SCAN` nonces.
+    pub fn get_nonce_gaps(&self, from: u64, to: u64) -> Vec<u64> {
+        require!(from <= to, "Invalid nonce range");
+        require!(to - from <= MAX_NONCE_SCAN, "Nonce range exceeds scan limit");
+        (from..to.min(self.deposit_nonce))
+            .filter(|n| !self.deposits.contains_key(n))
+            .collect()
+    }
+
+    /// Get cumulative deposited amount and count for nonces `0..=nonce`
+    /// O(nonce): reads every deposit record, so `nonce` is capped at
+    /// `MAX_NONCE_SCAN - 1`
+    pub fn get_cumulative_at_nonce(&self, nonce: u64) -> (U128, u64) {
+        require!(nonce < MAX_NONCE_SCAN, "Nonce exceeds scan limit");
+        let end = (nonce + 1).min(self.deposit_nonce);
+        let mut deposited: Balance = 0;
+        let mut count = 0;
+        for n in 0..end {
+            if let Some(deposit) = self.deposits.get(&n) {
+                deposited += deposit.amount.0;
+                count += 1;
+            }
+        }
+        (U128(deposited), count)
+    }
+
+    /// Check if `depositor` has used a native NEAR deposit commitment
+    pub fn is_commitment_used(&self, depositor: AccountId, commitment: String) -> bool {
+        self.processed_deposits.contains(&commitment_key(None, &depositor, &commitment))
+    }
+
+    /// Check if `depositor` has used a commitment for deposits of `token`
+    pub fn is_token_commitment_used(&self, token: AccountId, depositor: AccountId, commitment: String) -> bool {
+        self.processed_deposits.contains(&commitment_key(Some(&token), &depositor, &commitment))
+    }
+
+    /// Get the minimum guardian signature age in nanoseconds
+    pub fn get_min_sign_age(&self) -> u64 {
+        self.min_sign_age
+    }
+
+    /// Get the nullifier hash linked to a commitment, if still unrevealed
+    pub fn get_commitment_nullifier(&self, commitment: String) -> Option<String> {
+        self.commitment_nullifiers.get(&commitment).cloned()
+    }
+
+    /// Check many of `depositor`'s commitments at once, returning a parallel vector
+    pub fn are_commitments_used(&self, depositor: AccountId, commitments: Vec<String>) -> Vec<bool> {
+        require!(commitments.len() <= MAX_COMMITMENT_QUERY, "Too many commitments");
+        commitments
+            .iter()
+            .map(|commitment| self.processed_deposits.contains(&commitment_key(None, &depositor, commitment)))
+            .collect()
+    }
+
+    /// Get the admins and approval threshold (0 in single-owner mode)
+    pub fn get_admin_multisig(&self) -> (Vec<AccountId>, u32) {
+        (self.admins.iter().cloned().collect(), self.admin_threshold)
+    }
+
+    /// Get an admin proposal
+    pub fn get_admin_proposal(&self, proposal_id: u64) -> Option<AdminProposal> {
+        self.admin_proposals.get(&proposal_id).cloned()
+    }
+
+    /// Get a governance proposal
+    pub fn get_proposal(&self, proposal_id: u64) -> Option<Proposal> {
+        self.proposals.get(&proposal_id).cloned()
+    }
+
+    /// Get native deposit limits (min, max)
+    pub fn get_deposit_limits(&self) -> (U128, U128) {
+        (U128(self.min_deposit), U128(self.max_deposit))
+    }
+
+    /// Check if withdrawal is processed
+    pub fn is_withdrawal_processed(&self, withdrawal_hash: String) -> bool {
+        self.processed_withdrawals.contains(&withdrawal_hash)
+    }
+
+    /// Get the amount of a withdrawal whose transfer failed, if awaiting reissue
+    pub fn get_failed_withdrawal(&self, withdrawal_hash: String) -> Option<U128> {
+        self.failed_withdrawals.get(&withdrawal_hash).copied()
+    }
+
+    /// Check many withdrawal hashes at once, returning a parallel vector
+    pub fn are_withdrawals_processed(&self, hashes: Vec<String>) -> Vec<bool> {
+        require!(hashes.len() <= MAX_WITHDRAWAL_QUERY, "Too many withdrawal hashes");
+        hashes
+            .iter()
+            .map(|hash| self.processed_withdrawals.contains(hash))
+            .collect()
+    }
+
+    /// Get guardian list
+    pub fn get_guardians(&self) -> Vec<AccountId> {
+        self.guardians.iter().cloned().collect()
+    }
+
+    /// Get guardian count
+    pub fn guardian_count(&self) -> u32 {
+        self.guardians.len()
+    }
+
+    /// Check if account is guardian
+    pub fn is_guardian(&self, account_id: AccountId) -> bool {
+        self.guardians.contains(&account_id)
+    }
+
+    /// Check whether unsuspended guardians with registered keys carry
+    /// enough weight to reach the threshold, i.e. withdrawals can be signed
+    pub fn withdrawals_ready(&self) -> bool {
+        self.effective_guardian_capacity().2
+    }
+
+    /// Get the signing weight of unsuspended guardians with registered keys,
+    /// the threshold it must reach, and whether it does
+    pub fn effective_guardian_capacity(&self) -> (u32, u32, bool) {
+        let available: u32 = self.guardians
+            .iter()
+            .filter(|g| !self.suspended_guardians.contains(*g) && self.guardian_keys.contains_key(*g))
+            .map(|g| self.guardian_weight(g))
+            .sum();
+        let threshold = self.guardian_threshold.max(1);
+        (available, threshold, available >= threshold)
+    }
+
+    /// Get a guardian's voting weight
+    pub fn get_guardian_weight(&self, account_id: AccountId) -> u32 {
+        require!(self.guardians.contains(&account_id), "Guardian not found");
+        self.guardian_weight(&account_id)
+    }
+
+    /// Get the summed weight of all guardians
+    pub fn get_total_guardian_weight(&self) -> u32 {
+        self.total_guardian_weight()
+    }
+
+    /// Check if a guardian is suspended
+    pub fn is_guardian_suspended(&self, account_id: AccountId) -> bool {
+        self.suspended_guardians.contains(&account_id)
+    }
+
+    /// Resolve a deposit receipt id returned by `deposit`
+    pub fn get_deposit_by_receipt(&self, receipt_id: String) -> Option<Deposit> {
+        let nonce = self.deposit_receipts.get(&receipt_id)?;
+        self.deposits.get(nonce).cloned()
+    }
+
+    /// Get the total forwarded to other spoke chains
+    pub fn get_total_forwarded(&self) -> U128 {
+        U128(self.total_forwarded)
+    }
+
+    /// Get the account failed withdrawal transfers are rerouted to
+    pub fn get_fallback_recipient(&self) -> Option<AccountId> {
+        self.fallback_recipient.clone()
+    }
+
+    /// Get the minimum time between guardian additions in nanoseconds
+    pub fn get_min_guardian_add_interval(&self) -> u64 {
+        self.min_guardian_add_interval
+    }
+
+    /// Get owner
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    /// Get hub chain ID
+    pub fn get_hub_chain_id(&self) -> String {
+        self.hub_chain_id.clone()
+    }
+
+    /// Get guardian threshold, as a summed guardian weight
+    pub fn get_guardian_threshold(&self) -> u32 {
+        self.guardian_threshold
+    }
+
+    /// Get commitment co-signing mode and signer
+    pub fn get_commitment_signature_mode(&self) -> (bool, Option<PublicKey>) {
+        (self.require_commitment_signature, self.commitment_signer.clone())
+    }
+
+    // ============ Internal Functions ============
+
+    /// Mark a validated withdrawal processed and pay it out, probing the
+    /// recipient first if configured
+    fn execute_withdrawal(
+        &mut self,
+        withdrawal_hash: String,
+        recipient: AccountId,
+        amount: U128,
+        recipient_msg: Option<String>,
+    ) -> Promise {
+        self.assert_within_liabilities(amount.0);
+        self.assert_valid_recipient(&recipient);
+        
+        // Rate-limit how fast any single address can receive funds
+        let now = env::block_timestamp();
+        if self.withdrawal_cooldown > 0 {
+            if let Some(last) = self.last_withdrawal_ns.get(&recipient) {
+                require!(
+                    now >= last + self.withdrawal_cooldown,
+                    "Recipient withdrawal cooldown active"
+                );
+            }
+            self.last_withdrawal_ns.insert(recipient.clone(), now);
+        }
+        
+        // Mark as processed
+        self.processed_withdrawals.insert(withdrawal_hash.clone());
+        self.processed_withdrawal_count += 1;
+        
+        if self.verify_recipient_exists {
+            // A zero-value transfer fails if the account does not exist
+            return Promise::new(recipient.clone())
+                .transfer(NearToken::from_yoctonear(0))
+                .then(
+                    Self::ext(env::current_account_id())
+                        .with_static_gas(CALLBACK_GAS)
+                        .on_recipient_checked(withdrawal_hash, recipient, amount, recipient_msg),
+                );
+        }
+        
+        self.complete_withdrawal(withdrawal_hash, recipient, amount, recipient_msg)
+    }
+
+    /// Mark a validated withdrawal processed and record it as forwarded to
+    /// another spoke chain; the funds stay in the contract
+    fn forward_withdrawal(&mut self, withdrawal_hash: String, chain_id: String, recipient: String, amount: U128) {
+        require!(!chain_id.is_empty() && !recipient.is_empty(), "Invalid forward destination");
+        self.assert_within_liabilities(amount.0);
+        self.processed_withdrawals.insert(withdrawal_hash.clone());
+        self.processed_withdrawal_count += 1;
+        self.total_forwarded += amount.0;
+        
+        let event = WithdrawalForwardedEvent {
+            withdrawal_hash,
+            chain_id,
+            recipient,
+            amount,
+            timestamp: env::block_timestamp(),
+        };
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+    }
+
+    /// Unwind the accounting of a withdrawal whose transfer bounced back to
+    /// the bridge. The hash stays processed until the withdrawal is reissued.
+    fn record_failed_withdrawal(&mut self, withdrawal_hash: &str, recipient: &AccountId, amount: U128) {
+        self.total_withdrawn -= amount.0;
+        self.withdrawal_count -= 1;
+        self.failed_withdrawals.insert(withdrawal_hash.to_string(), amount);
+        log!("Withdrawal {} transfer to {} failed; funds held by bridge", withdrawal_hash, recipient);
+    }
+
+    /// Never release more than has been deposited, even with valid approvals
+    fn assert_within_liabilities(&self, amount: Balance) {
+        let released = self.total_withdrawn
+            .checked_add(self.total_forwarded)
+            .and_then(|total| total.checked_add(amount))
+            .unwrap_or_else(|| env::panic_str("Withdrawal amount overflow"));
+        require!(released <= self.total_deposited, "Withdrawal exceeds bridge liabilities");
+    }
+
+    /// Account for and pay out a withdrawal whose hash is already marked processed
+    fn complete_withdrawal(
+        &mut self,
+        withdrawal_hash: String,
+        recipient: AccountId,
+        amount: U128,
+        recipient_msg: Option<String>,
+    ) -> Promise {
+        self.total_withdrawn += amount.0;
+        self.withdrawal_count += 1;
+        self.record_metric(amount.0, false);
+        
+        // Emit event
+        let event = WithdrawalEvent {
+            withdrawal_hash: withdrawal_hash.clone(),
+            recipient: recipient.clone(),
+            amount,
+            timestamp: env::block_timestamp(),
+        };
+        
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+        log!("Withdrawal processed: {} yoctoNEAR to {}", amount.0, recipient);
+        
+        // Transfer NEAR to recipient
+        let transfer = Promise::new(recipient.clone()).transfer(NearToken::from_yoctonear(amount.0));
+        let Some(msg) = recipient_msg else {
+            return transfer.then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(TRANSFER_CALLBACK_GAS)
+                    .on_withdrawal_transferred(withdrawal_hash, recipient, amount),
+            );
+        };
+        
+        // Batched with the transfer, so a failing recipient call refunds it
+        let args = near_sdk::serde_json::json!({
+            "withdrawal_hash": withdrawal_hash,
+            "amount": amount,
+            "msg": msg,
+        });
+        transfer
+            .function_call(
+                "on_bridge_withdrawal".to_string(),
+                args.to_string().into_bytes(),
+                NearToken::from_yoctonear(0),
+                RECIPIENT_NOTIFY_GAS,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(CALLBACK_GAS)
+                    .on_withdrawal_notified(withdrawal_hash, recipient, amount),
+            )
+    }
+
+    /// Reject recipients that should never receive bridge funds
+    fn assert_valid_recipient(&self, recipient: &AccountId) {
+        require!(recipient != &env::current_account_id(), "Invalid recipient: bridge contract");
+        require!(
+            recipient.as_str() != BURN_ACCOUNT && recipient.as_str() != "system",
+            "Invalid recipient: system account"
+        );
+        require!(
+            !self.exclude_guardian_recipients || !self.guardians.contains(recipient),
+            "Invalid recipient: guardian"
+        );
+    }
+
+    /// In admin multisig mode owner-only calls arrive from the contract
+    /// itself, once an admin proposal has executed
+    fn assert_owner(&self) {
+        let caller = env::predecessor_account_id();
+        if self.admin_threshold > 0 {
+            require!(caller == env::current_account_id(), "Owner calls require admin approval");
+        } else {
+            require!(caller == self.owner_id, "Only owner can call this method");
+        }
+    }
+
+    fn assert_admin(&self) {
+        require!(self.admin_threshold > 0, "Admin multisig not enabled");
+        require!(
+            self.admins.contains(&env::predecessor_account_id()),
+            "Only admins can call this method"
+        );
+    }
+
+    /// Call the proposed method on this contract once enough current
+    /// admins have approved, returning whether the call was dispatched.
+    /// `on_admin_proposal_executed` records the outcome.
+    fn try_execute_admin_proposal(&mut self, proposal_id: u64) -> bool {
+        let proposal = self.admin_proposals.get(&proposal_id)
+            .unwrap_or_else(|| env::panic_str("Proposal not found"));
+        let approvals = proposal.approvals.iter().filter(|a| self.admins.contains(*a)).count();
+        if approvals < self.admin_threshold as usize {
+            return false;
+        }
+        let (method_name, args) = (proposal.method_name.clone(), proposal.args.clone());
+        if let Some(proposal) = self.admin_proposals.get_mut(&proposal_id) {
+            proposal.executing = true;
+        }
+        self.log_admin_proposal_event(proposal_id, "dispatched");
+        
+        Promise::new(env::current_account_id())
+            .function_call(
+                method_name,
+                args.into_bytes(),
+                NearToken::from_yoctonear(0),
+                ADMIN_CALL_GAS,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(CALLBACK_GAS)
+                    .on_admin_proposal_executed(proposal_id),
+            )
+            .detach();
+        true
+    }
+
+    fn log_admin_proposal_event(&self, proposal_id: u64, action: &str) {
+        let method_name = self.admin_proposals.get(&proposal_id)
+            .map(|proposal| proposal.method_name.clone())
+            .unwrap_or_default();
+        let event = AdminProposalEvent {
+            proposal_id,
+            method_name,
+            action: action.to_string(),
+            by: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        };
+        
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+    }
+
+    fn assert_guardian(&self) {
+        let caller = env::predecessor_account_id();
+        require!(
+            self.guardians.contains(&caller),
+            "Only guardians can call this method"
+        );
+        require!(!self.suspended_guardians.contains(&caller), "Guardian is suspended");
+    }
+
+    /// Add an operation to the current hour's bucket, resetting stale buckets
+    fn record_metric(&mut self, amount: Balance, is_deposit: bool) {
+        let hour = env::block_timestamp() / NANOS_PER_HOUR;
+        if self.hourly_metrics.is_empty() {
+            self.hourly_metrics = vec![HourlyMetric::default(); METRIC_BUCKETS as usize];
+        }
+        
+        let bucket = &mut self.hourly_metrics[(hour % METRIC_BUCKETS) as usize];
+        if bucket.hour != hour {
+            *bucket = HourlyMetric { hour, ..Default::default() };
+        }
+        
+        if is_deposit {
+            bucket.deposit_count += 1;
+            bucket.deposit_volume = U128(bucket.deposit_volume.0 + amount);
+        } else {
+            bucket.withdrawal_count += 1;
+            bucket.withdrawal_volume = U128(bucket.withdrawal_volume.0 + amount);
+        }
+    }
+
+    /// Panic unless `change` would leave the bridge in a valid configuration
+    fn validate_param_change(&self, change: &ParamChange) {
+        match change {
+            ParamChange::GuardianThreshold { threshold } => require!(
+                *threshold <= self.total_guardian_weight(),
+                "Threshold cannot exceed total guardian weight"
+            ),
+            ParamChange::DepositLimits { min, max } => require!(
+                min.0 > 0 && min.0 <= max.0,
+                "Invalid deposit limits"
+            ),
+            ParamChange::MinSignAge { .. } | ParamChange::MinGuardianAddInterval { .. } => {}
+        }
+    }
+
+    fn apply_param_change(&mut self, change: ParamChange) {
+        self.validate_param_change(&change);
+        match change {
+            ParamChange::GuardianThreshold { threshold } => {
+                self.guardian_threshold = threshold;
+                log!("Guardian threshold updated to: {}", threshold);
+            }
+            ParamChange::DepositLimits { min, max } => {
+                self.min_deposit = min.0;
+                self.max_deposit = max.0;
+                log!("Deposit limits updated to: {} - {}", min.0, max.0);
+            }
+            ParamChange::MinSignAge { nanos } => {
+                self.min_sign_age = nanos;
+                log!("Minimum signature age set to {} ns", nanos);
+            }
+            ParamChange::MinGuardianAddInterval { nanos } => {
+                self.min_guardian_add_interval = nanos;
+                log!("Minimum guardian add interval set to {} ns", nanos);
+            }
+        }
+    }
+
+    /// Execute a proposal once approvals from current guardians reach threshold
+    fn try_execute_proposal(&mut self, proposal_id: u64) {
+        let proposal = self.proposals.get(&proposal_id).unwrap();
+        let approvals: u32 = proposal.approvals.iter()
+            .filter(|guardian| self.guardians.contains(*guardian))
+            .map(|guardian| self.guardian_weight(guardian))
+            .sum();
+        if approvals < self.guardian_threshold.max(1) {
+            return;
+        }
+        
+        let change = proposal.change.clone();
+        self.proposals.get_mut(&proposal_id).unwrap().executed = true;
+        self.apply_param_change(change);
+        self.log_proposal_event(proposal_id, "executed");
+    }
+
+    fn log_proposal_event(&self, proposal_id: u64, action: &str) {
+        let event = ProposalEvent {
+            proposal_id,
+            action: action.to_string(),
+            by: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        };
+        
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+    }
+
+    /// Record a pause state change, overwriting the oldest entry once the
+    /// history is full
+    fn record_pause_change(&mut self, paused: bool) {
+        let record = PauseRecord {
+            paused,
+            actor: env::predecessor_account_id(),
+            timestamp: env::block_timestamp(),
+        };
+        let slot = (self.pause_record_count % MAX_PAUSE_HISTORY as u64) as u32;
+        if slot < self.pause_history.len() {
+            self.pause_history.replace(slot, record);
+        } else {
+            self.pause_history.push(record);
+        }
+        self.pause_record_count += 1;
+    }
+
+    /// Append a leaf to the incremental deposit tree, returning its index
+    fn insert_deposit_leaf(&mut self, leaf: Vec<u8>) -> u64 {
+        let leaf_index = self.tree_next_index;
+        require!(leaf_index < 1 << DEPOSIT_TREE_DEPTH, "Deposit tree is full");
+        
+        let zeros = zero_hashes();
+        let mut node = leaf;
+        let mut index = leaf_index;
+        for (level, zero) in zeros.iter().take(DEPOSIT_TREE_DEPTH).enumerate() {
+            self.deposit_tree_nodes.insert((level as u32, index), node.clone());
+            node = if index & 1 == 0 {
+                self.tree_filled_subtrees[level] = node.clone();
+                hash_children(&node, zero)
+            } else {
+                hash_children(&self.tree_filled_subtrees[level], &node)
+            };
+            index /= 2;
+        }
+        
+        self.deposit_root = node;
+        self.tree_next_index += 1;
+        leaf_index
+    }
+
+    /// Record a deposit commitment for an asset (`None` for native NEAR),
+    /// rejecting reuse by the same depositor within that asset
+    fn mark_commitment_used(&mut self, asset: Option<&AccountId>, depositor: &AccountId, commitment: &str) {
+        require!(
+            self.processed_deposits.insert(commitment_key(asset, depositor, commitment)),
+            "Commitment already used by depositor"
+        );
+        self.processed_deposit_count += 1;
+    }
+
+    /// Check a revealed nullifier against its commitment's linked hash and
+    /// unlink it so the commitment cannot be withdrawn again
+    fn consume_nullifier(&mut self, reveal: &NullifierReveal) {
+        let expected = self.commitment_nullifiers.get(&reveal.commitment)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("No nullifier linked to commitment"));
+        let nullifier = decode_hex(&reveal.nullifier)
+            .unwrap_or_else(|| env::panic_str("Invalid nullifier"));
+        require!(hex::encode(env::sha256(&nullifier)) == expected, "Nullifier mismatch");
+        self.commitment_nullifiers.remove(&reveal.commitment);
+    }
+
+    /// Evaluate every limit `deposit` enforces, without side effects
+    fn deposit_eligibility(&self, account_id: &AccountId, amount: Balance) -> DepositEligibility {
+        let within_cap = |used: Balance, cap: Option<Balance>| {
+            cap.is_none_or(|cap| used.checked_add(amount).is_some_and(|total| total <= cap))
+        };
+        let account_total = self.account_deposits.get(account_id).copied().unwrap_or(0);
+        let locked = self.tvl();
+        
+        let paused = self.paused();
+        let whitelisted = !self.deposit_whitelist_enabled || self.depositor_whitelist.contains(account_id);
+        let within_tx_limits = amount >= self.min_deposit && amount <= self.max_deposit;
+        let within_daily_limit = within_cap(self.deposited_last_day(), self.daily_deposit_limit);
+        let within_account_cap = within_cap(account_total, self.account_deposit_cap);
+        let within_tvl = within_cap(locked, self.tvl_cap);
+        DepositEligibility {
+            paused,
+            whitelisted,
+            within_tx_limits,
+            within_daily_limit,
+            within_account_cap,
+            within_tvl,
+            ok: !paused && whitelisted && within_tx_limits && within_daily_limit && within_account_cap && within_tvl,
+        }
+    }
+
+    /// Whether the bridge is paused, treating a lapsed timed pause as over
+    fn paused(&self) -> bool {
+        self.is_paused && self.auto_unpause_at.is_none_or(|at| env::block_timestamp() < at)
+    }
+
+    fn tvl(&self) -> Balance {
+        self.total_deposited - self.total_withdrawn
+    }
+
+    /// Fee tier matching TVL as a share of the TVL cap
+    fn current_deposit_fee_bps(&self) -> u16 {
+        let Some(cap) = self.tvl_cap.filter(|cap| *cap > 0) else {
+            return 0;
+        };
+        let utilization = (self.tvl().saturating_mul(BPS_DENOMINATOR as u128) / cap)
+            .min(BPS_DENOMINATOR as u128) as u16;
+        self.deposit_fee_curve
+            .iter()
+            .rev()
+            .find(|tier| tier.utilization_bps <= utilization)
+            .map_or(0, |tier| tier.fee_bps)
+    }
+
+    /// Native deposit volume across the rolling metric window
+    fn deposited_last_day(&self) -> Balance {
+        let current_hour = env::block_timestamp() / NANOS_PER_HOUR;
+        self.hourly_metrics
+            .iter()
+            .filter(|m| m.hour + METRIC_BUCKETS > current_hour)
+            .map(|m| m.deposit_volume.0)
+            .sum()
+    }
+
+    /// Write every storage record of a new native deposit: mark its
+    /// pool-namespaced commitment used, link the optional nullifier hash,
+    /// and store the deposit, its receipt and tree leaf with no amount
+    /// credited yet. With the storage reserve enabled, the records are
+    /// flushed so the storage they add can be priced.
+    fn store_deposit(
+        &mut self,
+        depositor: &AccountId,
+        commitment: &str,
+        nullifier_hash: Option<String>,
+        pool_id: u32,
+    ) -> StoredDeposit {
+        if self.reserve_deposit_storage {
+            self.flush_deposit_records();
+        }
+        let usage_before = env::storage_usage();
+        let pooled = pool_commitment(pool_id, commitment);
+
+        // Record commitment
+        self.mark_commitment_used(None, depositor, &pooled);
+        if let Some(nullifier_hash) = nullifier_hash {
+            let hash = decode_hex(&nullifier_hash)
+                .filter(|bytes| bytes.len() == 32)
+                .unwrap_or_else(|| env::panic_str("Invalid nullifier hash"));
+            // Another depositor's unrevealed nullifier must not be replaced
+            require!(
+                !self.commitment_nullifiers.contains_key(&pooled),
+                "Nullifier already linked to commitment"
+            );
+            self.commitment_nullifiers.insert(pooled.clone(), hex::encode(hash));
+        }
+        
+        let nonce = self.deposit_nonce;
+        self.deposit_nonce += 1;
+        self.deposit_count += 1;
+        
+        let deposit = Deposit {
+            depositor: depositor.clone(),
+            commitment: commitment.to_string(),
+            amount: U128(0),
+            nonce,
+            timestamp: env::block_timestamp(),
+            processed: false,
+            pool_id,
+            schema_version: DEPOSIT_SCHEMA_VERSION,
+        };
+        
+        self.deposits.insert(nonce, deposit);
+        let receipt_id = deposit_receipt_id(nonce, depositor, commitment);
+        self.deposit_receipts.insert(receipt_id.clone(), nonce);
+        let account_total = self.account_deposits.get(depositor).copied().unwrap_or(0);
+        self.account_deposits.insert(depositor.clone(), account_total);
+        let (pool_volume, pool_count) = self.pool_stats.get(&pool_id).copied().unwrap_or_default();
+        self.pool_stats.insert(pool_id, (pool_volume, pool_count + 1));
+        
+        let leaf_index = self.insert_deposit_leaf(deposit_leaf(&pooled));
+        
+        let storage_reserved = if self.reserve_deposit_storage {
+            self.flush_deposit_records();
+            (env::storage_usage() - usage_before) as Balance * env::storage_byte_cost().as_yoctonear()
+        } else {
+            0
+        };
+        StoredDeposit { nonce, receipt_id, leaf_index, storage_reserved }
+    }
+
+    /// Write out the cached collections `store_deposit` touches
+    fn flush_deposit_records(&mut self) {
+        self.commitment_nullifiers.flush();
+        self.deposits.flush();
+        self.deposit_receipts.flush();
+        self.account_deposits.flush();
+        self.pool_stats.flush();
+        self.deposit_tree_nodes.flush();
+    }
+
+    /// Credit an accepted native deposit whose records `store_deposit`
+    /// wrote: take the utilization fee and emit its event. The deposit is
+    /// credited net of the fee; `storage_reserved` was already kept back
+    /// and is only reported.
+    fn record_deposit(
+        &mut self,
+        depositor: &AccountId,
+        commitment: String,
+        amount: Balance,
+        pool_id: u32,
+        stored: StoredDeposit,
+    ) -> DepositReceipt {
+        let fee = amount * self.current_deposit_fee_bps() as u128 / BPS_DENOMINATOR as u128;
+        self.collected_deposit_fees += fee;
+        let amount = amount - fee;
+        let StoredDeposit { nonce, receipt_id, leaf_index, storage_reserved } = stored;
+        
+        self.total_deposited += amount;
+        if let Some(deposit) = self.deposits.get_mut(&nonce) {
+            deposit.amount = U128(amount);
+        }
+        self.record_metric(amount, true);
+        let account_total = self.account_deposits.get(depositor).copied().unwrap_or(0);
+        self.account_deposits.insert(depositor.clone(), account_total + amount);
+        let (pool_volume, pool_count) = self.pool_stats.get(&pool_id).copied().unwrap_or_default();
+        self.pool_stats.insert(pool_id, (pool_volume + amount, pool_count));
+        
+        // Emit event for relayers
+        if self.verbose_events {
+            let event = DepositEvent {
+                depositor: depositor.clone(),
+                commitment,
+                pool_id,
+                amount: U128(amount),
+                nonce,
+                receipt_id: receipt_id.clone(),
+                leaf_index,
+                merkle_root: hex::encode(&self.deposit_root),
+                storage_reserved: U128(storage_reserved),
+                schema_version: DEPOSIT_SCHEMA_VERSION,
+                timestamp: env::block_timestamp(),
+            };
+            log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+        } else {
+            let event = CompactDepositEvent { nonce, commitment };
+            log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+        }
+        log!("Deposit #{}: {} yoctoNEAR from {}", 
+            nonce, 
+            amount, 
+            depositor
+        );
+        
+        DepositReceipt { nonce, receipt_id }
+    }
+
+    /// Log a `DepositRejectedEvent` for abuse monitoring, then abort
+    fn reject_deposit(&self, account: &AccountId, reason: &str, amount: Balance) -> ! {
+        let event = DepositRejectedEvent {
+            account: account.clone(),
+            reason: reason.to_string(),
+            amount: U128(amount),
+            timestamp: env::block_timestamp(),
+        };
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+        env::panic_str(reason)
+    }
+
+    /// When the commitment window is enabled, split the embedded height off
+    /// `<commitment>@<height>` and check it is recent. Returns the bare
+    /// commitment.
+    fn accept_commitment_height(&self, commitment: String) -> String {
+        let Some(window) = self.commitment_window else {
+            return commitment;
+        };
+        
+        let (bare, height) = commitment
+            .split_once('@')
+            .and_then(|(bare, height)| Some((bare, height.parse::<u64>().ok()?)))
+            .unwrap_or_else(|| env::panic_str("Commitment height missing"));
+        let current = env::block_height();
+        require!(height <= current, "Commitment height is in the future");
+        require!(current - height <= window, "Commitment is stale");
+        bare.to_string()
+    }
+
+    /// Verify the commitment service signature over the raw commitment bytes
+    fn assert_commitment_signature(&self, commitment: &str, commitment_sig: Option<String>) {
+        if !self.require_commitment_signature {
+            return;
+        }
+        
+        let signer = self.commitment_signer.as_ref().expect("Commitment signer not set");
+        let message = decode_hex(commitment).unwrap_or_else(|| env::panic_str("Invalid commitment encoding"));
+        let signature = commitment_sig
+            .as_deref()
+            .and_then(decode_hex)
+            .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+            .unwrap_or_else(|| env::panic_str("Missing or malformed commitment signature"));
+        
+        require!(
+            env::ed25519_verify(&signature, &message, &ed25519_key_bytes(signer)),
+            "Invalid commitment signature"
+        );
+    }
+
+    /// Message guardians sign to slash a relayer. Bound to the relayer's
+    /// registration time so it cannot be replayed after re-registering.
+    fn slash_message(&self, relayer_id: &AccountId, reason: &str) -> Vec<u8> {
+        let registered_at = self.relayers.get(relayer_id)
+            .map(|relayer| relayer.registered_at)
+            .unwrap_or_else(|| env::panic_str("Relayer not registered"));
+        let mut message = b"cashio-slash-relayer:".to_vec();
+        message.extend_from_slice(self.hub_chain_id.as_bytes());
+        message.push(b':');
+        message.extend_from_slice(&near_sdk::borsh::to_vec(&(relayer_id, reason, registered_at)).unwrap());
+        env::sha256(&message)
+    }
+
+    fn log_relayer_event(&self, relayer: &AccountId, action: &str, stake: U128, reason: Option<String>) {
+        let event = RelayerEvent {
+            relayer: relayer.clone(),
+            action: action.to_string(),
+            stake,
+            reason,
+            timestamp: env::block_timestamp(),
+        };
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+    }
+
+    /// Message guardians sign to reissue a failed withdrawal to a new recipient
+    fn reissue_message(&self, withdrawal_hash: &str, new_recipient: &AccountId, amount: Balance) -> Vec<u8> {
+        let mut message = b"cashio-reissue-withdrawal:".to_vec();
+        message.extend_from_slice(self.hub_chain_id.as_bytes());
+        message.push(b':');
+        message.extend_from_slice(&self.guardian_set_epoch.to_le_bytes());
+        message.extend_from_slice(&batch_leaf(withdrawal_hash, new_recipient, amount));
+        env::sha256(&message)
+    }
+
+    /// Message guardians sign to pause and snapshot the bridge. Bound to the
+    /// pause history length so it cannot be replayed after an unpause.
+    fn incident_snapshot_message(&self) -> Vec<u8> {
+        let mut message = b"cashio-incident-snapshot:".to_vec();
+        message.extend_from_slice(self.hub_chain_id.as_bytes());
+        message.push(b':');
+        message.extend_from_slice(&self.pause_record_count.to_le_bytes());
+        env::sha256(&message)
+    }
+
+    /// Message guardians sign to confirm a batch of deposits relayed
+    fn relay_confirmation_message(&self, nonces: &[u64]) -> Vec<u8> {
+        let mut message = b"cashio-deposits-relayed:".to_vec();
+        message.extend_from_slice(self.hub_chain_id.as_bytes());
+        message.push(b':');
+        for nonce in nonces {
+            message.extend_from_slice(&nonce.to_le_bytes());
+        }
+        env::sha256(&message)
+    }
+
+    /// Voting weight of a guardian, 1 unless set otherwise
+    fn guardian_weight(&self, guardian_id: &AccountId) -> u32 {
+        self.guardian_weights.get(guardian_id).copied().unwrap_or(1)
+    }
+
+    fn total_guardian_weight(&self) -> u32 {
+        self.guardians.iter().map(|g| self.guardian_weight(g)).sum()
+    }
+
+    /// Message guardians sign to approve a withdrawal batch root
+    fn batch_message(&self, root: &[u8]) -> Vec<u8> {
+        let mut message = b"cashio-withdrawal-batch:".to_vec();
+        message.extend_from_slice(self.hub_chain_id.as_bytes());
+        message.push(b':');
+        message.extend_from_slice(root);
+        env::sha256(&message)
+    }
+
+    /// Digest of the attested configuration at the current attestation nonce
+    fn config_hash(&self) -> Vec<u8> {
+        let mut message = b"cashio-config:".to_vec();
+        message.extend_from_slice(self.hub_chain_id.as_bytes());
+        message.push(b':');
+        message.extend_from_slice(&near_sdk::borsh::to_vec(&(
+            self.get_guardians(),
+            self.guardian_threshold,
+            self.total_deposited,
+            self.total_withdrawn,
+            self.attestation_nonce,
+        )).unwrap());
+        env::sha256(&message)
+    }
+
+    /// Message guardians sign to approve a single withdrawal under the
+    /// current guardian set
+    fn withdrawal_message(&self, withdrawal_hash: &str, recipient: &AccountId, amount: Balance) -> Vec<u8> {
+        self.withdrawal_digest(withdrawal_hash, recipient, amount, self.guardian_set_epoch)
+    }
+
+    /// Message guardians sign to approve a single withdrawal under the
+    /// guardian set hashing to `set_hash`, which must have been current
+    /// within the last `guardian_set_window` epochs
+    fn withdrawal_set_message(&self, withdrawal_hash: &str, recipient: &AccountId, amount: Balance, set_hash: &str) -> Vec<u8> {
+        let epoch = self.guardian_set_hashes.get(set_hash)
+            .copied()
+            .unwrap_or_else(|| env::panic_str("Unknown guardian set"));
+        require!(
+            self.guardian_set_epoch - epoch <= self.guardian_set_window,
+            "Guardian set outside epoch window"
+        );
+        let mut message = b"cashio-withdrawal-set:".to_vec();
+        message.extend_from_slice(self.hub_chain_id.as_bytes());
+        message.push(b':');
+        message.extend_from_slice(&decode_hex(set_hash).unwrap_or_else(|| env::panic_str("Invalid guardian set hash")));
+        message.extend_from_slice(&batch_leaf(withdrawal_hash, recipient, amount));
+        env::sha256(&message)
+    }
+
+    /// Hash of the guardian accounts, sorted
+    fn guardian_set_hash(&self) -> Vec<u8> {
+        let mut guardians = self.get_guardians();
+        guardians.sort();
+        env::sha256(near_sdk::borsh::to_vec(&guardians).unwrap())
+    }
+
+    /// Remember the current guardian set's hash as current at this epoch
+    fn record_guardian_set(&mut self) {
+        self.guardian_set_hashes.insert(hex::encode(self.guardian_set_hash()), self.guardian_set_epoch);
+    }
+
+    /// Withdrawal message for a given guardian set epoch
+    fn withdrawal_digest(&self, withdrawal_hash: &str, recipient: &AccountId, amount: Balance, epoch: u64) -> Vec<u8> {
+        let mut message = b"cashio-withdrawal:".to_vec();
+        message.extend_from_slice(self.hub_chain_id.as_bytes());
+        message.push(b':');
+        message.extend_from_slice(&epoch.to_le_bytes());
+        message.extend_from_slice(&batch_leaf(withdrawal_hash, recipient, amount));
+        env::sha256(&message)
+    }
+
+    /// `(guardian, signed_at, weight)` of each distinct guardian with a valid
+    /// signature over `message`. Unknown or suspended guardians, unregistered
+    /// keys, bad signatures and signatures submitted less than `min_sign_age`
+    /// ago (or dated in the future) are ignored. While
+    /// `require_guardian_nonces` is on, a signature only counts if it covers
+    /// the guardian's current nonce.
+    fn valid_signers<'a>(&self, message: &[u8], signatures: &'a [GuardianSignature]) -> Vec<(&'a AccountId, u64, u32)> {
+        let now = env::block_timestamp();
+        let mut seen: Vec<&AccountId> = Vec::new();
+        let mut signers = Vec::new();
+        for sig in signatures {
+            if seen.contains(&&sig.guardian_id) || sig.signed_at > now || !self.signature_aged(sig, now) {
+                continue;
+            }
+            if self.verify_guardian_signature(message, sig) {
+                seen.push(&sig.guardian_id);
+                signers.push((&sig.guardian_id, sig.signed_at, self.guardian_weight(&sig.guardian_id)));
+            }
+        }
+        signers
+    }
+
+    /// Whether `sig` is a valid signature over `message` by an active,
+    /// unsuspended guardian, covering its current nonce while
+    /// `require_guardian_nonces` is on
+    fn verify_guardian_signature(&self, message: &[u8], sig: &GuardianSignature) -> bool {
+        if !self.guardians.contains(&sig.guardian_id) || self.suspended_guardians.contains(&sig.guardian_id) {
+            return false;
+        }
+        let Some(public_key) = self.guardian_keys.get(&sig.guardian_id) else {
+            return false;
+        };
+        let Some(signature) = decode_hex(&sig.signature).and_then(|b| <[u8; 64]>::try_from(b).ok()) else {
+            return false;
+        };
+        let signed = if self.require_guardian_nonces {
+            if sig.nonce != Some(self.get_guardian_nonce(sig.guardian_id.clone())) {
+                return false;
+            }
+            signed_message(&nonced_message(message, sig.nonce.unwrap_or_default()), sig.signed_at)
+        } else {
+            signed_message(message, sig.signed_at)
+        };
+        env::ed25519_verify(&signature, &signed, &ed25519_key_bytes(public_key))
+    }
+
+    /// Whether `sig` was submitted at least `min_sign_age` before `now`
+    fn signature_aged(&self, sig: &GuardianSignature, now: u64) -> bool {
+        self.min_sign_age == 0
+            || self.signature_first_seen
+                .get(&signature_key(sig))
+                .is_some_and(|&first_seen| now - first_seen >= self.min_sign_age)
+    }
+
+    /// Require valid guardian signatures whose summed weight reaches
+    /// `guardian_threshold` (and never zero), returning how many signed
+    fn assert_guardian_quorum(&mut self, message: &[u8], signatures: &[GuardianSignature]) -> u32 {
+        self.assert_guardian_quorum_times(message, signatures).len() as u32
+    }
+
+    /// `assert_guardian_quorum`, returning the valid signatures' signing
+    /// times and weights
+    fn assert_guardian_quorum_times(&mut self, message: &[u8], signatures: &[GuardianSignature]) -> Vec<(u64, u32)> {
+        self.assert_guardian_quorum_at(message, signatures, self.guardian_threshold)
+    }
+
+    /// `assert_guardian_quorum_times` against `threshold` signature weight.
+    /// Advances the signing nonce of every guardian whose signature counted.
+    fn assert_guardian_quorum_at(
+        &mut self,
+        message: &[u8],
+        signatures: &[GuardianSignature],
+        threshold: u32,
+    ) -> Vec<(u64, u32)> {
+        let signers = self.valid_signers(message, signatures);
+        let weight: u32 = signers.iter().map(|(_, _, weight)| weight).sum();
+        require!(
+            weight >= threshold.max(1),
+            "Insufficient guardian signatures"
+        );
+        if self.require_guardian_nonces {
+            for (guardian_id, _, _) in &signers {
+                let nonce = self.get_guardian_nonce((*guardian_id).clone());
+                self.guardian_nonces.insert((*guardian_id).clone(), nonce + 1);
+            }
+        }
+        signers
+            .into_iter()
+            .map(|(_, signed_at, weight)| (signed_at, weight))
+            .collect()
+    }
+
+    /// Signature weight a withdrawal of `amount` needs
+    fn withdrawal_threshold(&self, amount: Balance) -> u32 {
+        if amount < self.small_withdrawal_amount {
+            self.small_withdrawal_threshold.min(self.guardian_threshold)
+        } else {
+            self.guardian_threshold
+        }
+    }
+
+    /// Record when a withdrawal's first signature and the one bringing the
+    /// signed weight to the threshold were produced, and fold the gap into
+    /// the SLA stats
+    fn record_signing_window(&mut self, withdrawal_hash: &str, mut signing_times: Vec<(u64, u32)>, threshold: u32) {
+        signing_times.sort_unstable();
+        let threshold = threshold.max(1);
+        let mut signed_weight = 0;
+        let Some(&(quorum_signed_at, _)) = signing_times.iter().find(|(_, weight)| {
+            signed_weight += weight;
+            signed_weight >= threshold
+        }) else {
+            return;
+        };
+        let window = SigningWindow {
+            first_signed_at: signing_times[0].0,
+            quorum_signed_at,
+        };
+        let latency = window.quorum_signed_at - window.first_signed_at;
+        
+        self.sla_samples += 1;
+        self.sla_total_latency += latency as u128;
+        self.sla_max_latency = self.sla_max_latency.max(latency);
+        if self.sla_target.is_some_and(|target| latency > target) {
+            self.sla_breaches += 1;
+        }
+        self.signing_windows.insert(withdrawal_hash.to_string(), window);
+    }
+}
+
+// ============ Helpers ============
+
+/// Decode a hex string with an optional 0x prefix
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok()
+}
+
+/// Storage key for a deposit commitment: `"<asset>:<depositor>:<commitment>"`
+/// Native NEAR uses the empty asset id and tokens use their contract id.
+/// Account ids never contain ':', so keys from different assets or
+/// depositors cannot collide even when the commitment strings are identical.
+fn commitment_key(asset: Option<&AccountId>, depositor: &AccountId, commitment: &str) -> String {
+    format!("{}:{}:{}", asset.map_or("", |token| token.as_str()), depositor, commitment)
+}
+
+/// Key under which a guardian signature's first submission time is kept
+fn signature_key(sig: &GuardianSignature) -> String {
+    let signature = decode_hex(&sig.signature).unwrap_or_default();
+    hex::encode(env::sha256([sig.guardian_id.as_bytes(), &signature].concat()))
+}
+
+/// Digest a guardian actually signs: the message bound to its signing time
+pub fn signed_message(message: &[u8], signed_at: u64) -> Vec<u8> {
+    let mut signed = message.to_vec();
+    signed.extend_from_slice(&signed_at.to_le_bytes());
+    env::sha256(&signed)
+}
+
+/// Message bound to a guardian's signing nonce, which `signed_message`
+/// then binds to the signing time
+pub fn nonced_message(message: &[u8], nonce: u64) -> Vec<u8> {
+    let mut nonced = message.to_vec();
+    nonced.extend_from_slice(&nonce.to_le_bytes());
+    env::sha256(&nonced)
+}
+
+/// Leaf of a withdrawal batch tree: sha256 of the borsh-encoded
+/// (withdrawal_hash, recipient, amount) tuple
+pub fn batch_leaf(withdrawal_hash: &str, recipient: &AccountId, amount: Balance) -> Vec<u8> {
+    let encoded = near_sdk::borsh::to_vec(&(withdrawal_hash, recipient, amount))
+        .unwrap_or_else(|_| env::panic_str("Failed to encode batch leaf"));
+    env::sha256(&encoded)
+}
+
+/// Bind a withdrawal's destination into its message; NEAR-settled
+/// messages are unchanged
+pub fn with_destination(message: Vec<u8>, destination: &ChainDestination) -> Vec<u8> {
+    match destination {
+        ChainDestination::Near => message,
+        ChainDestination::Chain { .. } => {
+            let encoded = near_sdk::borsh::to_vec(destination)
+                .unwrap_or_else(|_| env::panic_str("Failed to encode destination"));
+            env::sha256([message, encoded].concat())
+        }
+    }
+}
+
+/// Bind an optional nullifier reveal into a withdrawal message; messages
+/// without one are unchanged
+pub fn with_nullifier(message: Vec<u8>, nullifier: Option<&NullifierReveal>) -> Vec<u8> {
+    match nullifier {
+        Some(reveal) => {
+            let encoded = near_sdk::borsh::to_vec(reveal)
+                .unwrap_or_else(|_| env::panic_str("Failed to encode nullifier"));
+            env::sha256([message, encoded].concat())
+        }
+        None => message,
+    }
+}
+
+/// Bind an optional recipient notification message into a withdrawal
+/// message; messages without one are unchanged
+pub fn with_recipient_msg(message: Vec<u8>, recipient_msg: Option<&str>) -> Vec<u8> {
+    match recipient_msg {
+        Some(msg) => env::sha256([message, msg.as_bytes().to_vec()].concat()),
+        None => message,
+    }
+}
+
+/// Hash two tree nodes in sorted order, so proofs need no direction bits
+pub fn hash_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    env::sha256([left, right].concat())
+}
+
+/// Receipt id for a deposit: hex sha256 of the borsh-encoded
+/// (nonce, depositor, commitment) tuple
+pub fn deposit_receipt_id(nonce: u64, depositor: &AccountId, commitment: &str) -> String {
+    let encoded = near_sdk::borsh::to_vec(&(nonce, depositor, commitment))
+        .unwrap_or_else(|_| env::panic_str("Failed to encode deposit receipt"));
+    hex::encode(env::sha256(&encoded))
+}
+
+/// Whether `deposit` has aged past the finality window
+fn deposit_is_final(deposit: &Deposit, min_age: u64) -> bool {
+    env::block_timestamp() >= deposit.timestamp.saturating_add(min_age)
+}
+
+/// Bytes of storage records a deposit writes, for a maximum-length
+/// account id, a 0x-prefixed 32-byte hex commitment and a nullifier hash
+fn deposit_storage_bytes() -> u64 {
+    let depositor: AccountId = "a".repeat(64).parse().unwrap();
+    let commitment = format!("0x{}", "0".repeat(64));
+    let hash_hex = "0".repeat(64);
+    // Keys are the one-byte StorageKey prefix plus the borsh-encoded key
+    let string = |value: &str| 4 + value.len() as u64;
+    let record = |key: u64, value: u64| 1 + key + value + STORAGE_RECORD_OVERHEAD;
+    let deposit = string(depositor.as_str()) + string(&commitment) + 16 + 8 + 8 + 1 + 4 + 1;
+    [
+        record(string(&commitment_key(None, &depositor, &commitment)), 0),
+        // IterableMap key slot, and its value tagged with the key index
+        // under a sha256 of the key
+        record(1 + 4, 8),
+        record(1 + 32, deposit + 4),
+        record(string(&hash_hex), 8),
+        record(string(depositor.as_str()), 16),
+        record(string(&commitment), string(&hash_hex)),
+        // The first deposit creates a tree node at every level
+        DEPOSIT_TREE_DEPTH as u64 * record(4 + 8, 4 + 32),
+        // and the first deposit into a pool its stats
+        record(4, 16 + 8),
+    ]
+    .iter()
+    .sum()
+}
+
+/// Storage cost in yoctoNEAR of `deposit_storage_bytes`
+fn deposit_storage_cost() -> Balance {
+    deposit_storage_bytes() as u128 * env::storage_byte_cost().as_yoctonear()
+}
+
+/// Commitment as namespaced within anonymity pool `pool_id`; the default
+/// pool keeps bare commitments
+pub fn pool_commitment(pool_id: u32, commitment: &str) -> String {
+    if pool_id == DEFAULT_POOL {
+        commitment.to_string()
+    } else {
+        format!("{}/{}", pool_id, commitment)
+    }
+}
+
+/// Deposit tree leaf for a commitment: sha256 of the commitment string
+pub fn deposit_leaf(commitment: &str) -> Vec<u8> {
+    env::sha256(commitment.as_bytes())
+}
+
+/// Hash two deposit tree nodes in positional order
+pub fn hash_children(left: &[u8], right: &[u8]) -> Vec<u8> {
+    env::sha256([left, right].concat())
+}
+
+/// Root of an empty subtree at each level, from the zero leaf up to the root
+fn zero_hashes() -> Vec<Vec<u8>> {
+    let mut zeros = vec![vec![0u8; 32]];
+    for level in 0..DEPOSIT_TREE_DEPTH {
+        zeros.push(hash_children(&zeros[level], &zeros[level]));
+    }
+    zeros
+}
+
+/// Raw 32-byte key material of an ed25519 public key
+fn ed25519_key_bytes(public_key: &PublicKey) -> [u8; 32] {
+    public_key.as_bytes()[1..]
+        .try_into()
+        .unwrap_or_else(|_| env::panic_str("Invalid ed25519 public key"))
+}
+
+// ============ ABI Registry ============
+
+/// A public contract method as described by `get_abi_schema`
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AbiMethod {
+    pub name: &'static str,
+    pub args: &'static [&'static str],
+    /// "init", "view", "call", "payable" or "private" (callbacks)
+    pub kind: &'static str,
+}
+
+/// An `EVENT_JSON` log shape as described by `get_abi_schema`
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AbiEvent {
+    pub name: &'static str,
+    pub fields: &'static [&'static str],
+}
+
+const fn method(name: &'static str, args: &'static [&'static str], kind: &'static str) -> AbiMethod {
+    AbiMethod { name, args, kind }
+}
+
+const fn init(name: &'static str, args: &'static [&'static str]) -> AbiMethod {
+    method(name, args, "init")
+}
+const fn view(name: &'static str, args: &'static [&'static str]) -> AbiMethod {
+    method(name, args, "view")
+}
+const fn call(name: &'static str, args: &'static [&'static str]) -> AbiMethod {
+    method(name, args, "call")
+}
+const fn payable(name: &'static str, args: &'static [&'static str]) -> AbiMethod {
+    method(name, args, "payable")
+}
+const fn private(name: &'static str, args: &'static [&'static str]) -> AbiMethod {
+    method(name, args, "private")
+}
+
+/// Every public method, in declaration order. Add an entry alongside any
+/// new or changed method.
+const ABI_METHODS: &[AbiMethod] = &[
+    init("new", &["owner_id", "hub_chain_id", "guardian_threshold", "initial_guardians"]),
+    init("migrate", &[]),
+    call("add_guardian", &["guardian_id"]),
+    call("remove_guardian", &["guardian_id"]),
+    call("suspend_guardian", &["guardian_id"]),
+    call("unsuspend_guardian", &["guardian_id"]),
+    call("set_guardian_weight", &["guardian_id", "weight"]),
+    call("update_threshold", &["new_threshold"]),
+    call("pause", &[]),
+    call("pause_for", &["duration_ns"]),
+    call("unpause", &[]),
+    call("set_commitment_signature_mode", &["enabled", "signer"]),
+    call("set_verify_recipient_exists", &["enabled"]),
+    call("set_exclude_guardian_recipients", &["enabled"]),
+    call("set_min_sign_age", &["min_sign_age"]),
+    call("submit_guardian_signature", &["message", "signature"]),
+    call("set_min_guardian_add_interval", &["interval_ns"]),
+    call("set_guardian_set_window", &["epochs"]),
+    call("set_max_batch_commitments", &["max"]),
+    call("set_deposit_storage_reserve", &["enabled"]),
+    call("set_small_withdrawal_tier", &["amount", "threshold"]),
+    call("set_verbose_events", &["verbose"]),
+    call("set_require_guardian_nonces", &["enabled"]),
+    call("set_deposit_caps", &["daily_limit", "account_cap", "tvl_cap"]),
+    call("set_asset_tvl_cap", &["token_id", "cap"]),
+    call("set_deposit_fee_curve", &["tiers"]),
+    call("claim_deposit_fees", &[]),
+    call("set_min_withdrawal", &["min_withdrawal"]),
+    call("set_commitment_window", &["window"]),
+    call("set_withdrawal_cooldown", &["cooldown_ns"]),
+    call("set_min_deposit_confirmations", &["min_ns"]),
+    call("set_guardian_sla_target", &["target_ns"]),
+    call("add_supported_token", &["token_id"]),
+    call("remove_supported_token", &["token_id"]),
+    call("set_deposit_whitelist_enabled", &["enabled"]),
+    call("add_whitelisted_depositor", &["account_id"]),
+    call("remove_whitelisted_depositor", &["account_id"]),
+    call("prune_deposit", &["nonce"]),
+    call("set_commitment_retention", &["retention_ns"]),
+    call("prune_old_commitments", &["before_ns"]),
+    call("rescue_tokens", &["token_id", "recipient", "amount"]),
+    call("set_treasury", &["treasury_id"]),
+    call("set_fallback_recipient", &["fallback_recipient"]),
+    call("transfer_ownership", &["new_owner"]),
+    call("schedule_renounce", &[]),
+    call("cancel_renounce", &[]),
+    call("finalize_renounce", &[]),
+    call("set_admin_multisig", &["admins", "threshold"]),
+    call("propose_admin_action", &["method_name", "args"]),
+    call("approve_admin_action", &["proposal_id"]),
+    call("execute_admin_proposal", &["proposal_id"]),
+    call("register_guardian_key", &["public_key"]),
+    call("confirm_deposit_relayed", &["nonce"]),
+    call("confirm_deposits_relayed", &["nonces", "signatures"]),
+    call("commit_withdrawal_batch", &["root", "signatures"]),
+    call("propose_param_change", &["change"]),
+    call("vote_on_proposal", &["proposal_id", "approve"]),
+    call("attest_config", &["signatures"]),
+    call("emergency_snapshot", &["signatures"]),
+    payable("register_relayer", &[]),
+    call("deregister_relayer", &[]),
+    call("withdraw_relayer_stake", &[]),
+    call("slash_relayer", &["relayer_id", "reason", "signatures"]),
+    call("reissue_withdrawal", &["withdrawal_hash", "new_recipient", "signatures"]),
+    payable("deposit", &["commitment", "commitment_sig", "nullifier_hash", "pool_id"]),
+    payable("deposit_batch", &["commitments", "amounts"]),
+    call("process_withdrawal", &["withdrawal_hash", "recipient", "amount", "signatures", "request"]),
+    call("claim_from_batch", &["root", "withdrawal_hash", "recipient", "amount", "proof"]),
+    private("on_recipient_checked", &["withdrawal_hash", "recipient", "amount", "recipient_msg"]),
+    private("on_withdrawal_notified", &["withdrawal_hash", "recipient", "amount"]),
+    private("on_withdrawal_transferred", &["withdrawal_hash", "recipient", "amount"]),
+    private("on_withdrawal_rerouted", &["withdrawal_hash", "fallback_recipient", "amount"]),
+    private("on_admin_proposal_executed", &["proposal_id"]),
+    call("ft_on_transfer", &["sender_id", "amount", "msg"]),
+    view("get_stats", &[]),
+    view("get_deposit_count", &[]),
+    view("get_withdrawal_count", &[]),
+    view("get_hourly_metrics", &[]),
+    view("is_deposit_whitelist_enabled", &[]),
+    view("is_depositor_whitelisted", &["account_id"]),
+    view("get_pause_history", &["limit"]),
+    view("get_auto_unpause_at", &[]),
+    view("can_deposit", &["account_id", "amount"]),
+    view("get_tvl", &[]),
+    view("get_asset_tvl", &["token_id"]),
+    view("get_asset_tvl_cap", &["token_id"]),
+    view("get_current_deposit_fee_bps", &[]),
+    view("get_deposit_fee_curve", &[]),
+    view("get_collected_deposit_fees", &[]),
+    view("get_deposit_caps", &[]),
+    view("get_min_withdrawal", &[]),
+    view("get_withdrawal_cooldown", &[]),
+    view("get_min_deposit_confirmations", &[]),
+    view("get_commitment_retention", &[]),
+    view("get_commitment_window", &[]),
+    view("get_deposit_root", &[]),
+    view("get_merkle_path", &["leaf_index"]),
+    view("get_pool_stats", &["pool_id"]),
+    view("get_storage_stats", &[]),
+    view("deposit_cost_estimate", &[]),
+    view("get_abi_schema", &[]),
+    view("get_guardian_sla_stats", &[]),
+    view("get_signing_window", &["withdrawal_hash"]),
+    view("get_relayer", &["relayer_id"]),
+    view("is_staked_relayer", &["relayer_id"]),
+    view("get_guardian_set_epoch", &[]),
+    view("get_guardian_set_hash", &[]),
+    view("get_guardian_set_window", &[]),
+    view("get_max_batch_commitments", &[]),
+    view("get_deposit_storage_reserve", &[]),
+    view("get_small_withdrawal_tier", &[]),
+    view("get_verbose_events", &[]),
+    view("get_guardian_nonce", &["guardian_id"]),
+    view("get_require_guardian_nonces", &[]),
+    view("preview_withdrawal_digest", &["withdrawal_hash", "recipient", "amount", "epoch", "recipient_msg", "destination"]),
+    view("get_config_hash", &[]),
+    view("get_supported_tokens", &[]),
+    view("get_exclude_guardian_recipients", &[]),
+    view("get_verify_recipient_exists", &[]),
+    view("get_deposit", &["nonce"]),
+    view("get_unprocessed_deposits", &["from", "limit"]),
+    view("get_cumulative_at_nonce", &["nonce"]),
+    view("get_nonce_gaps", &["from", "to"]),
+    view("is_commitment_used", &["depositor", "commitment"]),
+    view("is_token_commitment_used", &["token", "depositor", "commitment"]),
+    view("get_min_sign_age", &[]),
+    view("get_commitment_nullifier", &["commitment"]),
+    view("are_commitments_used", &["depositor", "commitments"]),
+    view("get_admin_multisig", &[]),
+    view("get_admin_proposal", &["proposal_id"]),
+    view("get_proposal", &["proposal_id"]),
+    view("get_deposit_limits", &[]),
+    view("is_withdrawal_processed", &["withdrawal_hash"]),
+    view("get_failed_withdrawal", &["withdrawal_hash"]),
+    view("are_withdrawals_processed", &["hashes"]),
+    view("get_guardians", &[]),
+    view("guardian_count", &[]),
+    view("is_guardian", &["account_id"]),
+    view("withdrawals_ready", &[]),
+    view("effective_guardian_capacity", &[]),
+    view("get_guardian_weight", &["account_id"]),
+    view("get_total_guardian_weight", &[]),
+    view("is_guardian_suspended", &["account_id"]),
+    view("get_deposit_by_receipt", &["receipt_id"]),
+    view("get_total_forwarded", &[]),
+    view("get_fallback_recipient", &[]),
+    view("get_min_guardian_add_interval", &[]),
+    view("get_owner", &[]),
+    view("get_hub_chain_id", &[]),
+    view("get_guardian_threshold", &[]),
+    view("get_commitment_signature_mode", &[]),
+];
+
+/// Every event struct logged as `EVENT_JSON`
+const ABI_EVENTS: &[AbiEvent] = &[
+    AbiEvent { name: "DepositEvent", fields: &["depositor", "commitment", "pool_id", "amount", "nonce", "receipt_id", "leaf_index", "merkle_root", "storage_reserved", "schema_version", "timestamp"] },
+    AbiEvent { name: "CompactDepositEvent", fields: &["nonce", "commitment"] },
+    AbiEvent { name: "DepositRejectedEvent", fields: &["account", "reason", "amount", "timestamp"] },
+    AbiEvent { name: "TokenDepositEvent", fields: &["token", "depositor", "commitment", "amount", "timestamp"] },
+    AbiEvent { name: "WithdrawalEvent", fields: &["withdrawal_hash", "recipient", "amount", "timestamp"] },
+    AbiEvent { name: "WithdrawalForwardedEvent", fields: &["withdrawal_hash", "chain_id", "recipient", "amount", "timestamp"] },
+    AbiEvent { name: "WithdrawalReroutedEvent", fields: &["withdrawal_hash", "recipient", "fallback_recipient", "amount", "timestamp"] },
+    AbiEvent { name: "WithdrawalBatchEvent", fields: &["root", "signers", "timestamp"] },
+    AbiEvent { name: "DepositsRelayedEvent", fields: &["nonces", "skipped", "signers", "timestamp"] },
+    AbiEvent { name: "ProposalEvent", fields: &["proposal_id", "action", "by", "timestamp"] },
+    AbiEvent { name: "AdminProposalEvent", fields: &["proposal_id", "method_name", "action", "by", "timestamp"] },
+    AbiEvent { name: "GuardianEvent", fields: &["guardian", "action", "by", "timestamp"] },
+    AbiEvent { name: "IncidentSnapshotEvent", fields: &["total_deposited", "total_withdrawn", "contract_balance", "guardian_count", "deposit_nonce", "by", "timestamp"] },
+    AbiEvent { name: "RelayerEvent", fields: &["relayer", "action", "stake", "reason", "timestamp"] },
+];
+
+#[near]
+impl FungibleTokenReceiver for CashioBridge {
+    /// Deposit NEP-141 tokens with the commitment passed as `msg`.
+    /// Only whitelisted token contracts may call this; anything else gets
+    /// the full amount back so a spoofing contract cannot fake deposits.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let token = env::predecessor_account_id();
+        if !self.supported_tokens.contains(&token) {
+            log!("Refunding transfer from unsupported token {}", token);
+            return PromiseOrValue::Value(amount);
+        }
+        if self.paused() {
+            log!("Refunding token deposit: bridge is paused");
+            return PromiseOrValue::Value(amount);
+        }
+        require!(amount.0 > 0, "Deposit amount too small");
+        let locked = self.asset_tvl.get(&token).copied().unwrap_or(0);
+        let within_cap = self.asset_tvl_caps.get(&token)
+            .is_none_or(|cap| locked.checked_add(amount.0).is_some_and(|total| total <= *cap));
+        if !within_cap {
+            log!("Refunding token deposit: TVL cap exceeded for {}", token);
+            return PromiseOrValue::Value(amount);
+        }
+        
+        self.mark_commitment_used(Some(&token), &sender_id, &msg);
+        self.asset_tvl.insert(token.clone(), locked + amount.0);
+        
+        let event = TokenDepositEvent {
+            token,
+            depositor: sender_id,
+            commitment: msg,
+            amount,
+            timestamp: env::block_timestamp(),
+        };
+        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
+        
+        PromiseOrValue::Value(U128(0))
+    }
+}
+
+// ============ Tests ============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs, VMContextBuilder};
+    use near_sdk::mock::MockAction;
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMContext};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn get_context(predecessor: AccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .predecessor_account_id(predecessor)
+            .current_account_id(accounts(0));
+        builder
+    }
+
+    /// Bridge owned by accounts(0) with `threshold` signing guardians from
+    /// accounts(2) on, left with the owner as predecessor
+    fn bridge_with_guardian(threshold: u32) -> CashioBridge {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            threshold,
+            None,
+        );
+        for i in 2..2 + threshold as usize {
+            add_signing_guardian(&mut contract, accounts(i), &signing_key(i as u8));
+        }
+        testing_env!(get_context(accounts(0)).build());
+        contract
+    }
+
+    fn testing_env_with_promise_result(context: VMContext, result: PromiseResult) {
+        testing_env!(
+            context,
+            near_sdk::test_vm_config(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    fn signing_key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn near_public_key(key: &SigningKey) -> PublicKey {
+        PublicKey::from_parts(CurveType::ED25519, key.verifying_key().to_bytes().to_vec()).unwrap()
+    }
+
+    fn sign_hex(key: &SigningKey, message: &[u8]) -> String {
+        hex::encode(key.sign(message).to_bytes())
+    }
+
+    /// Add `guardian` as owner accounts(0) and register its signing key
+    fn add_signing_guardian(contract: &mut CashioBridge, guardian: AccountId, key: &SigningKey) {
+        testing_env!(get_context(accounts(0)).build());
+        contract.add_guardian(guardian.clone());
+        testing_env!(get_context(guardian).build());
+        contract.register_guardian_key(near_public_key(key));
+    }
+
+    fn seed_deposit(contract: &mut CashioBridge, commitment: &str, amount: Balance) {
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(amount))
+            .build());
+        contract.deposit(commitment.to_string(), None, None, 0);
+    }
+
+    fn guardian_signature(guardian: AccountId, key: &SigningKey, message: &[u8]) -> GuardianSignature {
+        let signed_at = env::block_timestamp();
+        GuardianSignature {
+            guardian_id: guardian,
+            signature: sign_hex(key, &signed_message(message, signed_at)),
+            signed_at,
+            nonce: None,
+        }
+    }
+
+    /// Withdraw as guardian accounts(2) signing with `signing_key(2)` at the
+    /// current block time
+    fn signed_withdrawal(
+        contract: &mut CashioBridge,
+        withdrawal_hash: &str,
+        recipient: AccountId,
+        amount: Balance,
+        nullifier: Option<NullifierReveal>,
+    ) -> PromiseOrValue<()> {
+        let message = with_nullifier(contract.withdrawal_message(withdrawal_hash, &recipient, amount), nullifier.as_ref());
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        contract.process_withdrawal(withdrawal_hash.to_string(), recipient, U128(amount), vec![signature], WithdrawalRequest { nullifier, ..Default::default() })
+    }
+
+    #[test]
+    fn test_init() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        assert_eq!(contract.get_owner(), accounts(0));
+        assert_eq!(contract.get_hub_chain_id(), "999888777");
+        assert_eq!(contract.get_guardian_threshold(), 1);
+        assert!(!contract.is_paused);
+    }
+
+    #[test]
+    fn test_add_guardian() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        contract.add_guardian(accounts(1));
+        assert!(contract.is_guardian(accounts(1)));
+        assert_eq!(contract.guardian_count(), 1);
+    }
+
+    #[test]
+    fn test_deposit() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        let commitment = "0x0102030405060708091011121314151617181920212223242526272829303132".to_string();
+        let nonce = contract.deposit(commitment.clone(), None, None, 0).nonce;
+        
+        assert_eq!(nonce, 0);
+        assert!(contract.is_commitment_used(accounts(1), commitment));
+        
+        let (_, total_deposited, _, deposit_nonce, _) = contract.get_stats();
+        assert_eq!(total_deposited.0, MIN_DEPOSIT);
+        assert_eq!(deposit_nonce, 1);
+    }
+
+    #[test]
+    fn test_pause_unpause() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        assert!(!contract.is_paused);
+        
+        contract.pause();
+        let (_, _, _, _, is_paused) = contract.get_stats();
+        assert!(is_paused);
+        
+        contract.unpause();
+        let (_, _, _, _, is_paused) = contract.get_stats();
+        assert!(!is_paused);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit amount too small")]
+    fn test_deposit_too_small() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context
+            .attached_deposit(NearToken::from_yoctonear(1000)) // Too small
+            .build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        let commitment = "0x0102030405060708".to_string();
+        contract.deposit(commitment, None, None, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can call this method")]
+    fn test_only_owner() {
+        let context = get_context(accounts(1)); // Not owner
+        testing_env!(context.build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0), // Owner is accounts(0)
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        contract.pause(); // Should fail
+    }
+
+    #[test]
+    fn test_deposit_with_commitment_signature() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        let service = signing_key(7);
+        contract.set_commitment_signature_mode(true, Some(near_public_key(&service)));
+
+        let commitment = "0x0102030405060708".to_string();
+        let sig = sign_hex(&service, &decode_hex(&commitment).unwrap());
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        let nonce = contract.deposit(commitment.clone(), Some(sig), None, 0).nonce;
+
+        assert_eq!(nonce, 0);
+        assert!(contract.is_commitment_used(accounts(1), commitment));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid commitment signature")]
+    fn test_deposit_with_wrong_commitment_signer() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        contract.set_commitment_signature_mode(true, Some(near_public_key(&signing_key(7))));
+
+        let commitment = "0x0102030405060708".to_string();
+        let sig = sign_hex(&signing_key(8), &decode_hex(&commitment).unwrap());
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        contract.deposit(commitment, Some(sig), None, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing or malformed commitment signature")]
+    fn test_deposit_missing_commitment_signature() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        contract.set_commitment_signature_mode(true, Some(near_public_key(&signing_key(7))));
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        contract.deposit("0x0102030405060708".to_string(), None, None, 0);
+    }
+
+    #[test]
+    fn test_hourly_metrics_rollover() {
+        let hour = NANOS_PER_HOUR;
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .block_timestamp(hour / 2)
+            .build());
+        contract.deposit("0x01".to_string(), None, None, 0);
+        contract.deposit("0x02".to_string(), None, None, 0);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(NearToken::from_yoctonear(0))
+            .block_timestamp(hour + 1)
+            .build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(1), MIN_DEPOSIT, None);
+
+        let metrics = contract.get_hourly_metrics();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].hour, 0);
+        assert_eq!(metrics[0].deposit_count, 2);
+        assert_eq!(metrics[0].deposit_volume.0, 2 * MIN_DEPOSIT);
+        assert_eq!(metrics[1].hour, 1);
+        assert_eq!(metrics[1].withdrawal_count, 1);
+        assert_eq!(metrics[1].withdrawal_volume.0, MIN_DEPOSIT);
+
+        // Hour 25 reuses hour 1's slot and pushes hour 0 out of the window
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .block_timestamp(25 * hour)
+            .build());
+        contract.deposit("0x03".to_string(), None, None, 0);
+
+        let metrics = contract.get_hourly_metrics();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].hour, 25);
+        assert_eq!(metrics[0].deposit_count, 1);
+        assert_eq!(metrics[0].withdrawal_count, 0);
+    }
+
+    #[test]
+    fn test_withdrawal_recipient_exists() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_verify_recipient_exists(true);
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(1), MIN_DEPOSIT, None);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn.0, 0);
+
+        testing_env_with_promise_result(
+            context.predecessor_account_id(accounts(0)).build(),
+            PromiseResult::Successful(vec![]),
+        );
+        let result = contract.on_recipient_checked("0xaa".to_string(), accounts(1), U128(MIN_DEPOSIT), None);
+        assert!(matches!(result, PromiseOrValue::Promise(_)));
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn.0, MIN_DEPOSIT);
+    }
+
+    #[test]
+    fn test_withdrawal_recipient_missing() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_verify_recipient_exists(true);
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        let missing: AccountId = "missing.near".parse().unwrap();
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", missing.clone(), MIN_DEPOSIT, None);
+
+        testing_env_with_promise_result(
+            context.predecessor_account_id(accounts(0)).build(),
+            PromiseResult::Failed,
+        );
+        let result = contract.on_recipient_checked("0xaa".to_string(), missing, U128(MIN_DEPOSIT), None);
+        assert!(matches!(result, PromiseOrValue::Value(false)));
+        assert!(!contract.is_withdrawal_processed("0xaa".to_string()));
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn.0, 0);
+    }
+
+    /// Withdraw to a receiver contract with a notification message signed by
+    /// guardian accounts(2)
+    fn notified_withdrawal(contract: &mut CashioBridge, receiver: AccountId, msg: &str) -> PromiseOrValue<()> {
+        let message = with_recipient_msg(
+            contract.withdrawal_message("0xaa", &receiver, MIN_DEPOSIT),
+            Some(msg),
+        );
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        contract.process_withdrawal(
+            "0xaa".to_string(),
+            receiver,
+            U128(MIN_DEPOSIT),
+            vec![signature],
+            WithdrawalRequest {
+                recipient_msg: Some(msg.to_string()),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_withdrawal_notifies_recipient_contract() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let receiver: AccountId = "mock-receiver.near".parse().unwrap();
+        testing_env!(get_context(accounts(2)).build());
+        let _ = notified_withdrawal(&mut contract, receiver.clone(), "credit:alice");
+
+        let receipts = get_created_receipts();
+        let receipt = receipts.iter().find(|r| r.receiver_id == receiver).expect("Expected a receipt");
+        assert!(matches!(
+            receipt.actions[0],
+            MockAction::Transfer { deposit, .. } if deposit == NearToken::from_yoctonear(MIN_DEPOSIT)
+        ));
+        match &receipt.actions[1] {
+            MockAction::FunctionCallWeight { method_name, args, .. } => {
+                assert_eq!(method_name, b"on_bridge_withdrawal");
+                let args: near_sdk::serde_json::Value = near_sdk::serde_json::from_slice(args).unwrap();
+                assert_eq!(args["withdrawal_hash"], "0xaa");
+                assert_eq!(args["amount"], MIN_DEPOSIT.to_string());
+                assert_eq!(args["msg"], "credit:alice");
+            }
+            _ => panic!("Expected on_bridge_withdrawal call"),
+        }
+        assert!(receipts.iter().any(|r| r.receiver_id == accounts(0)));
+
+        // The mock receiver accepted the notification
+        testing_env_with_promise_result(get_context(accounts(0)).build(), PromiseResult::Successful(vec![]));
+        assert!(contract.on_withdrawal_notified("0xaa".to_string(), receiver, U128(MIN_DEPOSIT)));
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn.0, MIN_DEPOSIT);
+    }
+
+    #[test]
+    fn test_withdrawal_recipient_rejects_notification() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let receiver: AccountId = "mock-receiver.near".parse().unwrap();
+        testing_env!(get_context(accounts(2)).build());
+        let _ = notified_withdrawal(&mut contract, receiver.clone(), "credit:alice");
+
+        testing_env_with_promise_result(get_context(accounts(0)).build(), PromiseResult::Failed);
+        assert!(!contract.on_withdrawal_notified("0xaa".to_string(), receiver, U128(MIN_DEPOSIT)));
+        assert!(!contract.is_withdrawal_processed("0xaa".to_string()));
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn.0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_withdrawal_recipient_msg_must_be_signed() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let receiver: AccountId = "mock-receiver.near".parse().unwrap();
+        testing_env!(get_context(accounts(2)).build());
+        let message = contract.withdrawal_message("0xaa", &receiver, MIN_DEPOSIT);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        let _ = contract.process_withdrawal(
+            "0xaa".to_string(),
+            receiver,
+            U128(MIN_DEPOSIT),
+            vec![signature],
+            WithdrawalRequest {
+                recipient_msg: Some("credit:mallory".to_string()),
+                ..Default::default()
+            },
+        );
+    }
+
+    fn batch_fixture() -> (CashioBridge, Vec<Vec<u8>>, Vec<u8>) {
+        batch_fixture_seeded(MAX_DEPOSIT)
+    }
+
+    /// Commit a four-leaf batch against a bridge holding `seeded` in deposits
+    fn batch_fixture_seeded(seeded: Balance) -> (CashioBridge, Vec<Vec<u8>>, Vec<u8>) {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let key = signing_key(11);
+        add_signing_guardian(&mut contract, accounts(2), &key);
+        seed_deposit(&mut contract, "0x01", seeded);
+
+        let leaves: Vec<Vec<u8>> = (0..4u8)
+            .map(|i| batch_leaf(&format!("0x0{}", i), &accounts(3), MIN_DEPOSIT + i as u128))
+            .collect();
+        let root = hash_pair(&hash_pair(&leaves[0], &leaves[1]), &hash_pair(&leaves[2], &leaves[3]));
+
+        let signature = guardian_signature(accounts(2), &key, &contract.batch_message(&root));
+        testing_env!(get_context(accounts(4)).build());
+        contract.commit_withdrawal_batch(hex::encode(&root), vec![signature]);
+
+        (contract, leaves, root)
+    }
+
+    #[test]
+    fn test_claim_from_batch() {
+        let (mut contract, leaves, root) = batch_fixture();
+
+        let proof = vec![hex::encode(&leaves[3]), hex::encode(hash_pair(&leaves[0], &leaves[1]))];
+        let _ = contract.claim_from_batch(
+            hex::encode(&root),
+            "0x02".to_string(),
+            accounts(3),
+            U128(MIN_DEPOSIT + 2),
+            proof,
+        );
+
+        assert!(contract.is_withdrawal_processed("0x02".to_string()));
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn.0, MIN_DEPOSIT + 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid merkle proof")]
+    fn test_claim_from_batch_tampered_leaf() {
+        let (mut contract, leaves, root) = batch_fixture();
+
+        let proof = vec![hex::encode(&leaves[3]), hex::encode(hash_pair(&leaves[0], &leaves[1]))];
+        let _ = contract.claim_from_batch(
+            hex::encode(&root),
+            "0x02".to_string(),
+            accounts(3),
+            U128(MIN_DEPOSIT * 100),
+            proof,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_commit_batch_with_unregistered_signer() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.add_guardian(accounts(2));
+
+        let root = vec![7u8; 32];
+        let signature = guardian_signature(accounts(2), &signing_key(11), &contract.batch_message(&root));
+        contract.commit_withdrawal_batch(hex::encode(&root), vec![signature]);
+    }
+
+    #[test]
+    fn test_suspended_guardian_signature_ignored() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let key = signing_key(12);
+        add_signing_guardian(&mut contract, accounts(2), &key);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.suspend_guardian(accounts(2));
+        assert!(contract.is_guardian_suspended(accounts(2)));
+
+        let root = vec![9u8; 32];
+        let signature = guardian_signature(accounts(2), &key, &contract.batch_message(&root));
+        assert_eq!(contract.valid_signers(&contract.batch_message(&root), std::slice::from_ref(&signature)).len(), 0);
+
+        contract.unsuspend_guardian(accounts(2));
+        assert_eq!(contract.valid_signers(&contract.batch_message(&root), std::slice::from_ref(&signature)).len(), 1);
+        contract.commit_withdrawal_batch(hex::encode(&root), vec![signature]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_suspended_guardian_cannot_meet_threshold() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let key = signing_key(12);
+        add_signing_guardian(&mut contract, accounts(2), &key);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.suspend_guardian(accounts(2));
+
+        let root = vec![9u8; 32];
+        let signature = guardian_signature(accounts(2), &key, &contract.batch_message(&root));
+        contract.commit_withdrawal_batch(hex::encode(&root), vec![signature]);
+    }
+
+    #[test]
+    fn test_withdrawal_within_liabilities() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+        let _ = signed_withdrawal(&mut contract, "0xbb", accounts(3), MIN_DEPOSIT, None);
+
+        let (_, total_deposited, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn, total_deposited);
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal exceeds bridge liabilities")]
+    fn test_withdrawal_exceeding_liabilities() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT + 1, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal exceeds bridge liabilities")]
+    fn test_batch_claim_exceeding_liabilities() {
+        let (mut contract, leaves, root) = batch_fixture_seeded(MIN_DEPOSIT);
+
+        let proof = vec![hex::encode(&leaves[3]), hex::encode(hash_pair(&leaves[0], &leaves[1]))];
+        let _ = contract.claim_from_batch(
+            hex::encode(&root),
+            "0x02".to_string(),
+            accounts(3),
+            U128(MIN_DEPOSIT + 2),
+            proof,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal exceeds bridge liabilities")]
+    fn test_forward_exceeding_liabilities() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        let destination = ChainDestination::Chain {
+            chain_id: "solana".to_string(),
+            recipient: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+        };
+        testing_env!(get_context(accounts(2)).build());
+        let message = with_destination(
+            contract.withdrawal_message("0xaa", &accounts(3), MIN_DEPOSIT + 1),
+            &destination,
+        );
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        let _ = contract.process_withdrawal(
+            "0xaa".to_string(),
+            accounts(3),
+            U128(MIN_DEPOSIT + 1),
+            vec![signature],
+            WithdrawalRequest {
+                destination,
+                ..Default::default()
+            },
+        );
+    }
+
+    fn nullifier_fixture() -> (CashioBridge, NullifierReveal) {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+
+        let nullifier = vec![7u8; 32];
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        contract.deposit("0x01".to_string(), None, Some(hex::encode(env::sha256(&nullifier))), 0);
+
+        let reveal = NullifierReveal {
+            commitment: "0x01".to_string(),
+            nullifier: hex::encode(&nullifier),
+        };
+        (contract, reveal)
+    }
+
+    #[test]
+    fn test_withdrawal_with_matching_nullifier() {
+        let (mut contract, reveal) = nullifier_fixture();
+        assert!(contract.get_commitment_nullifier("0x01".to_string()).is_some());
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, Some(reveal));
+
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        assert!(contract.get_commitment_nullifier("0x01".to_string()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nullifier mismatch")]
+    fn test_withdrawal_with_mismatched_nullifier() {
+        let (mut contract, mut reveal) = nullifier_fixture();
+        reveal.nullifier = hex::encode([8u8; 32]);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, Some(reveal));
+    }
+
+    #[test]
+    #[should_panic(expected = "No nullifier linked to commitment")]
+    fn test_nullifier_cannot_be_revealed_twice() {
+        let (mut contract, reveal) = nullifier_fixture();
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, Some(reveal.clone()));
+        let _ = signed_withdrawal(&mut contract, "0xbb", accounts(3), MIN_DEPOSIT, Some(reveal));
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_signed_nullifier_cannot_be_dropped() {
+        let (mut contract, reveal) = nullifier_fixture();
+
+        testing_env!(get_context(accounts(2)).build());
+        let message = with_nullifier(contract.withdrawal_message("0xaa", &accounts(3), MIN_DEPOSIT), Some(&reveal));
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        let _ = contract.process_withdrawal(
+            "0xaa".to_string(),
+            accounts(3),
+            U128(MIN_DEPOSIT),
+            vec![signature],
+            WithdrawalRequest::default(),
+        );
+    }
+
+    #[test]
+    fn test_cumulative_at_nonce() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT * 2);
+        seed_deposit(&mut contract, "0x03", MIN_DEPOSIT * 3);
+
+        assert_eq!(contract.get_cumulative_at_nonce(0), (U128(MIN_DEPOSIT), 1));
+        assert_eq!(contract.get_cumulative_at_nonce(1), (U128(MIN_DEPOSIT * 3), 2));
+        assert_eq!(contract.get_cumulative_at_nonce(2), (U128(MIN_DEPOSIT * 6), 3));
+        // Nonces past the latest deposit report the current totals
+        assert_eq!(contract.get_cumulative_at_nonce(10), (U128(MIN_DEPOSIT * 6), 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Nonce exceeds scan limit")]
+    fn test_cumulative_at_nonce_scan_limit() {
+        testing_env!(get_context(accounts(0)).build());
+        let contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.get_cumulative_at_nonce(MAX_NONCE_SCAN);
+    }
+
+    #[test]
+    fn test_withdrawal_with_aged_signature() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_min_sign_age(NANOS_PER_HOUR);
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        let message = contract.withdrawal_message("0xaa", &accounts(3), MIN_DEPOSIT);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        contract.submit_guardian_signature(hex::encode(message), signature.clone());
+
+        testing_env!(get_context(accounts(2)).block_timestamp(NANOS_PER_HOUR).build());
+        let _ = contract.process_withdrawal("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT), vec![signature], WithdrawalRequest::default());
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_withdrawal_with_fresh_signature() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_min_sign_age(NANOS_PER_HOUR);
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        let message = contract.withdrawal_message("0xaa", &accounts(3), MIN_DEPOSIT);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        contract.submit_guardian_signature(hex::encode(message), signature.clone());
+
+        testing_env!(get_context(accounts(2)).block_timestamp(NANOS_PER_HOUR - 1).build());
+        let _ = contract.process_withdrawal("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT), vec![signature], WithdrawalRequest::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_signature_age_counts_from_submission() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_min_sign_age(NANOS_PER_HOUR);
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        // Signed with an old signed_at, but only just submitted
+        let message = contract.withdrawal_message("0xaa", &accounts(3), MIN_DEPOSIT);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        testing_env!(get_context(accounts(2)).block_timestamp(NANOS_PER_HOUR).build());
+        contract.submit_guardian_signature(hex::encode(message), signature.clone());
+        let _ = contract.process_withdrawal("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT), vec![signature], WithdrawalRequest::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid guardian signature")]
+    fn test_submit_rejects_invalid_signature() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+
+        let message = contract.withdrawal_message("0xaa", &accounts(3), MIN_DEPOSIT);
+        let signature = guardian_signature(accounts(2), &signing_key(3), &message);
+        contract.submit_guardian_signature(hex::encode(message), signature);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_withdrawal_with_backdated_signature() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_min_sign_age(NANOS_PER_HOUR);
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        // Rewriting signed_at invalidates the signature
+        testing_env!(get_context(accounts(2)).block_timestamp(NANOS_PER_HOUR).build());
+        let message = contract.withdrawal_message("0xaa", &accounts(3), MIN_DEPOSIT);
+        let mut signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        signature.signed_at = 0;
+        let _ = contract.process_withdrawal("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT), vec![signature], WithdrawalRequest::default());
+    }
+
+    #[test]
+    fn test_are_commitments_used() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        seed_deposit(&mut contract, "0x03", MIN_DEPOSIT);
+
+        let query = ["0x01", "0x02", "0x03", "0x04"].map(String::from).to_vec();
+        assert_eq!(contract.are_commitments_used(accounts(1), query), vec![true, false, true, false]);
+        assert!(contract.are_commitments_used(accounts(1), Vec::new()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many commitments")]
+    fn test_are_commitments_used_limit() {
+        testing_env!(get_context(accounts(0)).build());
+        let contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.are_commitments_used(accounts(1), vec!["0x01".to_string(); MAX_COMMITMENT_QUERY + 1]);
+    }
+
+    #[test]
+    fn test_commitment_namespaced_by_asset() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let token: AccountId = "usdc.near".parse().unwrap();
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        contract.mark_commitment_used(Some(&token), &accounts(1), "0x01");
+
+        assert!(contract.is_commitment_used(accounts(1), "0x01".to_string()));
+        assert!(contract.is_token_commitment_used(token.clone(), accounts(1), "0x01".to_string()));
+        assert!(!contract.is_token_commitment_used(accounts(5), accounts(1), "0x01".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Commitment already used by depositor")]
+    fn test_commitment_reuse_within_asset() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let token: AccountId = "usdc.near".parse().unwrap();
+        contract.mark_commitment_used(Some(&token), &accounts(1), "0x01");
+        contract.mark_commitment_used(Some(&token), &accounts(1), "0x01");
+    }
+
+    #[test]
+    fn test_same_commitment_different_depositors() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(3))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        let nonce = contract.deposit("0x01".to_string(), None, None, 0).nonce;
+
+        assert_eq!(nonce, 1);
+        assert!(contract.is_commitment_used(accounts(1), "0x01".to_string()));
+        assert!(contract.is_commitment_used(accounts(3), "0x01".to_string()));
+        assert!(!contract.is_commitment_used(accounts(4), "0x01".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Commitment already used by depositor")]
+    fn test_same_commitment_same_depositor() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+    }
+
+    fn governance_fixture() -> CashioBridge {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            2,
+            None,
+        );
+        contract.add_guardian(accounts(2));
+        contract.add_guardian(accounts(3));
+        contract.add_guardian(accounts(4));
+        contract
+    }
+
+    #[test]
+    fn test_proposal_reaches_quorum() {
+        let mut contract = governance_fixture();
+
+        testing_env!(get_context(accounts(2)).build());
+        let id = contract.propose_param_change(ParamChange::DepositLimits {
+            min: U128(MIN_DEPOSIT * 2),
+            max: U128(MAX_DEPOSIT / 2),
+        });
+        assert!(!contract.get_proposal(id).unwrap().executed);
+
+        testing_env!(get_context(accounts(3)).build());
+        contract.vote_on_proposal(id, true);
+
+        assert!(contract.get_proposal(id).unwrap().executed);
+        assert_eq!(contract.get_deposit_limits(), (U128(MIN_DEPOSIT * 2), U128(MAX_DEPOSIT / 2)));
+    }
+
+    #[test]
+    fn test_proposal_fails_quorum() {
+        let mut contract = governance_fixture();
+
+        testing_env!(get_context(accounts(2)).build());
+        let id = contract.propose_param_change(ParamChange::GuardianThreshold { threshold: 3 });
+
+        testing_env!(get_context(accounts(3)).build());
+        contract.vote_on_proposal(id, false);
+        testing_env!(get_context(accounts(4)).build());
+        contract.vote_on_proposal(id, false);
+
+        let proposal = contract.get_proposal(id).unwrap();
+        assert!(!proposal.executed);
+        assert_eq!(proposal.rejections.len(), 2);
+        assert_eq!(contract.get_guardian_threshold(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Already voted")]
+    fn test_proposal_double_vote() {
+        let mut contract = governance_fixture();
+
+        testing_env!(get_context(accounts(2)).build());
+        let id = contract.propose_param_change(ParamChange::MinSignAge { nanos: NANOS_PER_HOUR });
+        contract.vote_on_proposal(id, true);
+    }
+
+    #[test]
+    fn test_can_deposit_blocking_conditions() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_deposit_caps(
+            Some(U128(MIN_DEPOSIT * 5)),
+            Some(U128(MIN_DEPOSIT * 3)),
+            Some(U128(MIN_DEPOSIT * 4)),
+        );
+        assert!(contract.can_deposit(accounts(1), U128(MIN_DEPOSIT)).ok);
+
+        let too_small = contract.can_deposit(accounts(1), U128(MIN_DEPOSIT - 1));
+        assert!(!too_small.within_tx_limits && !too_small.ok);
+
+        // accounts(1) deposits up to its cap, accounts(2) up to the TVL cap
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 3);
+        let capped = contract.can_deposit(accounts(1), U128(MIN_DEPOSIT));
+        assert!(!capped.within_account_cap && capped.within_tvl && !capped.ok);
+
+        let over_tvl = contract.can_deposit(accounts(2), U128(MIN_DEPOSIT * 2));
+        assert!(over_tvl.within_account_cap && !over_tvl.within_tvl && !over_tvl.ok);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_deposit_caps(Some(U128(MIN_DEPOSIT * 3)), None, None);
+        let over_daily = contract.can_deposit(accounts(2), U128(MIN_DEPOSIT));
+        assert!(!over_daily.within_daily_limit && !over_daily.ok);
+
+        // The daily window rolls off after METRIC_BUCKETS hours
+        testing_env!(get_context(accounts(0)).block_timestamp(METRIC_BUCKETS * NANOS_PER_HOUR).build());
+        assert!(contract.can_deposit(accounts(2), U128(MIN_DEPOSIT)).ok);
+
+        contract.pause();
+        let paused = contract.can_deposit(accounts(2), U128(MIN_DEPOSIT));
+        assert!(paused.paused && !paused.ok);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account deposit cap exceeded")]
+    fn test_deposit_enforces_account_cap() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_deposit_caps(None, Some(U128(MIN_DEPOSIT * 2)), None);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT);
+    }
+
+    #[test]
+    fn test_rescue_tokens() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let stray: AccountId = "stray-token.near".parse().unwrap();
+        let _ = contract.rescue_tokens(stray.clone(), accounts(1), U128(500));
+
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id, stray);
+        match &receipts[0].actions[0] {
+            MockAction::FunctionCallWeight { method_name, args, attached_deposit, .. } => {
+                assert_eq!(method_name, b"ft_transfer");
+                assert_eq!(*attached_deposit, NearToken::from_yoctonear(1));
+                let args: near_sdk::serde_json::Value = near_sdk::serde_json::from_slice(args).unwrap();
+                assert_eq!(args["receiver_id"], accounts(1).as_str());
+                assert_eq!(args["amount"], "500");
+            }
+            _ => panic!("Expected ft_transfer call"),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot rescue a bridge token")]
+    fn test_rescue_bridge_token_rejected() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let token: AccountId = "usdc.near".parse().unwrap();
+        contract.add_supported_token(token.clone());
+        let _ = contract.rescue_tokens(token, accounts(1), U128(500));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot rescue a token backing deposits")]
+    fn test_rescue_removed_token_with_deposits_rejected() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let token: AccountId = "usdc.near".parse().unwrap();
+        contract.add_supported_token(token.clone());
+        testing_env!(get_context(token.clone()).build());
+        assert_eq!(refunded(contract.ft_on_transfer(accounts(1), U128(500), "0x01".to_string())), 0);
+
+        // Dropping support doesn't release the deposited tokens
+        testing_env!(get_context(accounts(0)).build());
+        contract.remove_supported_token(token.clone());
+        let _ = contract.rescue_tokens(token, accounts(1), U128(500));
+    }
+
+    #[test]
+    fn test_withdrawal_at_minimum() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_min_withdrawal(U128(MIN_DEPOSIT));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal amount too small")]
+    fn test_withdrawal_below_minimum() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_min_withdrawal(U128(MIN_DEPOSIT));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT - 1, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal amount must be positive")]
+    fn test_zero_amount_withdrawal_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), 0, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal exceeds contract balance")]
+    fn test_withdrawal_over_balance_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        testing_env!(get_context(accounts(2))
+            .account_balance(NearToken::from_yoctonear(MIN_DEPOSIT - 1))
+            .build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+    }
+
+    /// Root of a depth-`DEPOSIT_TREE_DEPTH` tree holding `leaves`, built level by level
+    fn reference_root(leaves: &[Vec<u8>]) -> Vec<u8> {
+        let zeros = zero_hashes();
+        let mut nodes = leaves.to_vec();
+        for zero in zeros.iter().take(DEPOSIT_TREE_DEPTH) {
+            if nodes.len() % 2 == 1 {
+                nodes.push(zero.clone());
+            }
+            nodes = nodes.chunks(2).map(|pair| hash_children(&pair[0], &pair[1])).collect();
+        }
+        nodes.pop().unwrap_or_else(|| zeros[DEPOSIT_TREE_DEPTH].clone())
+    }
+
+    fn last_deposit_event() -> near_sdk::serde_json::Value {
+        let log = get_logs()
+            .into_iter()
+            .rev()
+            .find(|log| log.starts_with("EVENT_JSON:") && log.contains("leaf_index"))
+            .unwrap();
+        near_sdk::serde_json::from_str(&log["EVENT_JSON:".len()..]).unwrap()
+    }
+
+    #[test]
+    fn test_deposit_event_carries_merkle_root() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        assert_eq!(contract.get_deposit_root().0, hex::encode(reference_root(&[])));
+
+        let commitments = ["0x01", "0x02", "0x03"];
+        let mut leaves = Vec::new();
+        for (index, commitment) in commitments.iter().enumerate() {
+            seed_deposit(&mut contract, commitment, MIN_DEPOSIT);
+            leaves.push(deposit_leaf(commitment));
+
+            let event = last_deposit_event();
+            assert_eq!(event["leaf_index"], index as u64);
+            assert_eq!(event["merkle_root"], hex::encode(reference_root(&leaves)));
+        }
+        assert_eq!(contract.get_deposit_root(), (hex::encode(reference_root(&leaves)), 3));
+    }
+
+    #[test]
+    fn test_attest_config() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        let first_hash = contract.config_hash();
+        let signature = guardian_signature(accounts(2), &signing_key(2), &first_hash);
+        let attestation = contract.attest_config(vec![signature.clone()]);
+        assert_eq!(attestation.nonce, 0);
+        assert_eq!(attestation.config_hash, hex::encode(&first_hash));
+        assert_eq!(attestation.guardians, vec![accounts(2)]);
+        assert_eq!(attestation.total_deposited.0, MIN_DEPOSIT);
+        assert_eq!(attestation.signers, 1);
+
+        // Unchanged config still hashes differently at the next nonce
+        let (next_hash, next_nonce) = contract.get_config_hash();
+        assert_eq!(next_nonce, 1);
+        assert_ne!(next_hash, hex::encode(&first_hash));
+        let signature = guardian_signature(accounts(2), &signing_key(2), &contract.config_hash());
+        assert_eq!(contract.attest_config(vec![signature]).nonce, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_attest_config_replay() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+
+        let signature = guardian_signature(accounts(2), &signing_key(2), &contract.config_hash());
+        contract.attest_config(vec![signature.clone()]);
+        contract.attest_config(vec![signature]);
+    }
+
+    #[test]
+    fn test_storage_stats() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let empty = contract.get_storage_stats();
+        assert_eq!(empty.deposits, 0);
+        assert_eq!(empty.guardians, 0);
+
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+
+        let stats = contract.get_storage_stats();
+        assert_eq!(stats.deposits, 2);
+        assert_eq!(stats.processed_deposits, 2);
+        assert_eq!(stats.processed_withdrawals, 1);
+        assert_eq!(stats.guardians, 1);
+        assert!(stats.storage_usage > 0);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.add_guardian(accounts(4));
+        assert_eq!(contract.get_storage_stats().guardians, 2);
+        contract.remove_guardian(accounts(4));
+        assert_eq!(contract.get_storage_stats().guardians, 1);
+    }
+
+    #[test]
+    fn test_renounce_ownership() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.schedule_renounce();
+        contract.cancel_renounce();
+        contract.schedule_renounce();
+
+        testing_env!(get_context(accounts(0))
+            .block_timestamp(RENOUNCE_DELAY)
+            .build());
+        contract.finalize_renounce();
+        assert_eq!(contract.get_owner().as_str(), BURN_ACCOUNT);
+    }
+
+    #[test]
+    #[should_panic(expected = "Renounce delay has not elapsed")]
+    fn test_renounce_before_delay() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.schedule_renounce();
+
+        testing_env!(get_context(accounts(0))
+            .block_timestamp(RENOUNCE_DELAY - 1)
+            .build());
+        contract.finalize_renounce();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only owner can call this method")]
+    fn test_owner_locked_out_after_renounce() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.schedule_renounce();
+        testing_env!(get_context(accounts(0))
+            .block_timestamp(RENOUNCE_DELAY)
+            .build());
+        contract.finalize_renounce();
+
+        contract.pause();
+    }
+
+    fn refunded(result: PromiseOrValue<U128>) -> u128 {
+        match result {
+            PromiseOrValue::Value(unused) => unused.0,
+            PromiseOrValue::Promise(_) => panic!("Expected a value"),
+        }
+    }
+
+    #[test]
+    fn test_ft_on_transfer_whitelisted_token() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let token: AccountId = "usdc.near".parse().unwrap();
+        contract.add_supported_token(token.clone());
+
+        testing_env!(get_context(token.clone()).build());
+        let result = contract.ft_on_transfer(accounts(1), U128(500), "0x01".to_string());
+        assert_eq!(refunded(result), 0);
+        assert!(contract.is_token_commitment_used(token, accounts(1), "0x01".to_string()));
+        assert!(get_logs().iter().any(|log| log.contains("\"commitment\":\"0x01\"")));
+    }
+
+    #[test]
+    fn test_ft_on_transfer_spoofed_token_refunded() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.add_supported_token("usdc.near".parse().unwrap());
+
+        let spoof: AccountId = "fake-usdc.near".parse().unwrap();
+        testing_env!(get_context(spoof.clone()).build());
+        let result = contract.ft_on_transfer(accounts(1), U128(500), "0x01".to_string());
+        assert_eq!(refunded(result), 500);
+        assert!(!contract.is_token_commitment_used(spoof, accounts(1), "0x01".to_string()));
+    }
+
+    #[test]
+    fn test_get_unprocessed_deposits() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        for commitment in ["0x01", "0x02", "0x03", "0x04"] {
+            seed_deposit(&mut contract, commitment, MIN_DEPOSIT);
+        }
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.confirm_deposit_relayed(0);
+        contract.confirm_deposit_relayed(2);
+
+        let nonces = |deposits: Vec<Deposit>| deposits.iter().map(|d| d.nonce).collect::<Vec<_>>();
+        assert_eq!(nonces(contract.get_unprocessed_deposits(0, 10)), vec![1, 3]);
+        assert_eq!(nonces(contract.get_unprocessed_deposits(0, 1)), vec![1]);
+        assert_eq!(nonces(contract.get_unprocessed_deposits(2, 10)), vec![3]);
+        assert!(contract.get_unprocessed_deposits(4, 10).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only guardians can call this method")]
+    fn test_confirm_deposit_relayed_guardian_only() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        contract.confirm_deposit_relayed(0);
+    }
+
+    fn deposit_at_height(contract: &mut CashioBridge, commitment: &str) -> u64 {
+        testing_env!(get_context(accounts(1))
+            .block_height(1_000)
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        contract.deposit(commitment.to_string(), None, None, 0).nonce
+    }
+
+    #[test]
+    fn test_commitment_in_window() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_commitment_window(Some(100));
+        let nonce = deposit_at_height(&mut contract, "0x01@900");
+        assert_eq!(contract.get_deposit(nonce).unwrap().commitment, "0x01");
+        assert!(contract.is_commitment_used(accounts(1), "0x01".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Commitment is stale")]
+    fn test_stale_commitment_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_commitment_window(Some(100));
+        deposit_at_height(&mut contract, "0x01@899");
+    }
+
+    #[test]
+    #[should_panic(expected = "Commitment height missing")]
+    fn test_commitment_without_height_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_commitment_window(Some(100));
+        deposit_at_height(&mut contract, "0x01");
+    }
+
+    #[test]
+    fn test_withdrawal_cooldown_per_recipient() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_withdrawal_cooldown(NANOS_PER_HOUR);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 4);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+        // A different recipient is unaffected
+        let _ = signed_withdrawal(&mut contract, "0xbb", accounts(4), MIN_DEPOSIT, None);
+
+        testing_env!(get_context(accounts(2))
+            .block_timestamp(NANOS_PER_HOUR)
+            .build());
+        let _ = signed_withdrawal(&mut contract, "0xcc", accounts(3), MIN_DEPOSIT, None);
+        assert!(contract.is_withdrawal_processed("0xcc".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Recipient withdrawal cooldown active")]
+    fn test_withdrawal_cooldown_back_to_back() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_withdrawal_cooldown(NANOS_PER_HOUR);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 4);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+        let _ = signed_withdrawal(&mut contract, "0xbb", accounts(3), MIN_DEPOSIT, None);
+    }
+
+    #[test]
+    fn test_counts_survive_pruning() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+        contract.confirm_deposit_relayed(0);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.prune_deposit(0);
+        assert!(contract.get_deposit(0).is_none());
+        assert_eq!(contract.get_storage_stats().deposits, 1);
+
+        assert_eq!(contract.get_deposit_count(), 2);
+        assert_eq!(contract.get_withdrawal_count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit not yet relayed")]
+    fn test_prune_unrelayed_deposit() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(0)).build());
+        contract.prune_deposit(0);
+    }
+
+    #[test]
+    fn test_confirm_deposits_relayed_batch() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        for commitment in ["0x01", "0x02", "0x03"] {
+            seed_deposit(&mut contract, commitment, MIN_DEPOSIT);
+        }
+        testing_env!(get_context(accounts(2)).build());
+        contract.confirm_deposit_relayed(1);
+
+        // Nonce 1 is already relayed and nonce 9 does not exist
+        let nonces = vec![0, 1, 2, 9];
+        let message = contract.relay_confirmation_message(&nonces);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        testing_env!(get_context(accounts(4)).build());
+        contract.confirm_deposits_relayed(nonces, vec![signature]);
+
+        assert!((0..3).all(|n| contract.get_deposit(n).unwrap().processed));
+        let logs = get_logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].contains("\"nonces\":[0,2]"));
+        assert!(logs[0].contains("\"skipped\":2"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_confirm_deposits_relayed_requires_quorum() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        // Signed for a different batch
+        let message = contract.relay_confirmation_message(&[1]);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        contract.confirm_deposits_relayed(vec![0], vec![signature]);
+    }
+
+    #[test]
+    fn test_preview_withdrawal_digest() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        let epoch = contract.get_guardian_set_epoch();
+        assert_eq!(epoch, 1);
+        let preview = contract.preview_withdrawal_digest(
+            "0xaa".to_string(),
+            accounts(3),
+            U128(MIN_DEPOSIT),
+            epoch,
+            None,
+            ChainDestination::Near,
+        );
+        assert_ne!(
+            preview,
+            contract.preview_withdrawal_digest("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT), epoch + 1, None, ChainDestination::Near)
+        );
+
+        // A signature over the previewed digest is accepted
+        testing_env!(get_context(accounts(2)).build());
+        let signature = guardian_signature(accounts(2), &signing_key(2), &hex::decode(&preview).unwrap());
+        let _ = contract.process_withdrawal("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT), vec![signature], WithdrawalRequest::default());
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid recipient: bridge contract")]
+    fn test_withdrawal_to_contract_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", env::current_account_id(), MIN_DEPOSIT, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid recipient: system account")]
+    fn test_withdrawal_to_zero_account_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", BURN_ACCOUNT.parse().unwrap(), MIN_DEPOSIT, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid recipient: system account")]
+    fn test_withdrawal_to_system_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", "system".parse().unwrap(), MIN_DEPOSIT, None);
+    }
+
+    #[test]
+    fn test_withdrawal_to_guardian_opt_in() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        assert!(!contract.get_exclude_guardian_recipients());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(2), MIN_DEPOSIT / 2, None);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid recipient: guardian")]
+    fn test_withdrawal_to_guardian_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_exclude_guardian_recipients(true);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(2), MIN_DEPOSIT, None);
+    }
+
+    fn register_relayer_at(contract: &mut CashioBridge, relayer: AccountId, stake: Balance) {
+        testing_env!(get_context(relayer)
+            .attached_deposit(NearToken::from_yoctonear(stake))
+            .build());
+        contract.register_relayer();
+    }
+
+    fn last_transfer() -> (AccountId, NearToken) {
+        get_created_receipts()
+            .into_iter()
+            .rev()
+            .find_map(|receipt| match receipt.actions[0] {
+                MockAction::Transfer { deposit, .. } => Some((receipt.receiver_id.clone(), deposit)),
+                _ => None,
+            })
+            .expect("Expected a transfer")
+    }
+
+    #[test]
+    fn test_register_relayer() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        register_relayer_at(&mut contract, accounts(5), MIN_RELAYER_STAKE);
+        register_relayer_at(&mut contract, accounts(5), MIN_RELAYER_STAKE);
+
+        assert!(contract.is_staked_relayer(accounts(5)));
+        assert_eq!(contract.get_relayer(accounts(5)).unwrap().stake.0, MIN_RELAYER_STAKE * 2);
+        assert!(!contract.is_staked_relayer(accounts(4)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Relayer stake too small")]
+    fn test_register_relayer_stake_too_small() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        register_relayer_at(&mut contract, accounts(5), MIN_RELAYER_STAKE - 1);
+    }
+
+    #[test]
+    fn test_slash_relayer() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_treasury(accounts(3));
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        register_relayer_at(&mut contract, accounts(5), MIN_RELAYER_STAKE);
+
+        let message = contract.slash_message(&accounts(5), "forged relay");
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        testing_env!(get_context(accounts(4)).build());
+        let _ = contract.slash_relayer(accounts(5), "forged relay".to_string(), vec![signature]);
+
+        assert!(contract.get_relayer(accounts(5)).is_none());
+        assert_eq!(last_transfer(), (accounts(3), NearToken::from_yoctonear(MIN_RELAYER_STAKE)));
+    }
+
+    #[test]
+    fn test_relayer_stake_unbonding() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        register_relayer_at(&mut contract, accounts(5), MIN_RELAYER_STAKE);
+        testing_env!(get_context(accounts(5)).build());
+        contract.deregister_relayer();
+        assert!(!contract.is_staked_relayer(accounts(5)));
+
+        testing_env!(get_context(accounts(5))
+            .block_timestamp(RELAYER_UNBONDING)
+            .build());
+        let _ = contract.withdraw_relayer_stake();
+        assert!(contract.get_relayer(accounts(5)).is_none());
+        assert_eq!(last_transfer(), (accounts(5), NearToken::from_yoctonear(MIN_RELAYER_STAKE)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Relayer stake is still unbonding")]
+    fn test_relayer_stake_withdrawn_early() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        register_relayer_at(&mut contract, accounts(5), MIN_RELAYER_STAKE);
+        testing_env!(get_context(accounts(5)).build());
+        contract.deregister_relayer();
+
+        testing_env!(get_context(accounts(5))
+            .block_timestamp(RELAYER_UNBONDING - 1)
+            .build());
+        let _ = contract.withdraw_relayer_stake();
+    }
+
+    fn rejection_reason(contract: &mut CashioBridge, commitment: &str, amount: Balance) -> String {
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(amount))
+            .build());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.deposit(commitment.to_string(), None, None, 0)
+        }));
+        assert!(result.is_err(), "Deposit should have been rejected");
+        let logs = get_logs();
+        let event: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(logs.last().unwrap().trim_start_matches("EVENT_JSON:")).unwrap();
+        assert_eq!(event["account"], accounts(1).as_str());
+        assert_eq!(event["amount"], amount.to_string());
+        event["reason"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_deposit_rejected_events() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        assert_eq!(rejection_reason(&mut contract, "0x02", MIN_DEPOSIT - 1), "Deposit amount too small");
+        assert_eq!(rejection_reason(&mut contract, "0x02", MAX_DEPOSIT + 1), "Deposit amount too large");
+        assert_eq!(rejection_reason(&mut contract, "0x01", MIN_DEPOSIT), "Commitment already used by depositor");
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_deposit_caps(None, None, Some(U128(MIN_DEPOSIT)));
+        assert_eq!(rejection_reason(&mut contract, "0x02", MIN_DEPOSIT), "TVL cap exceeded");
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.pause();
+        assert_eq!(rejection_reason(&mut contract, "0x02", MIN_DEPOSIT), "Bridge is paused");
+    }
+
+    /// Withdraw `MIN_DEPOSIT` to accounts(1) at `processed_at`, with guardian
+    /// accounts(i) signing at each `(i, signed_at)`
+    fn withdrawal_signed_at(
+        contract: &mut CashioBridge,
+        withdrawal_hash: &str,
+        signers: &[(usize, u64)],
+        processed_at: u64,
+    ) {
+        let message = contract.withdrawal_message(withdrawal_hash, &accounts(1), MIN_DEPOSIT);
+        let signatures = signers
+            .iter()
+            .map(|&(i, signed_at)| {
+                testing_env!(get_context(accounts(i)).block_timestamp(signed_at).build());
+                guardian_signature(accounts(i), &signing_key(i as u8), &message)
+            })
+            .collect();
+        testing_env!(get_context(accounts(2)).block_timestamp(processed_at).build());
+        let _ = contract.process_withdrawal(
+            withdrawal_hash.to_string(),
+            accounts(1),
+            U128(MIN_DEPOSIT),
+            signatures,
+            WithdrawalRequest::default(),
+        );
+    }
+
+    #[test]
+    fn test_guardian_sla_stats() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            2,
+            None,
+        );
+        for i in 2..5 {
+            add_signing_guardian(&mut contract, accounts(i), &signing_key(i as u8));
+        }
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_guardian_sla_target(Some(150));
+
+        // Quorum is the second signature, whatever order they are submitted in
+        withdrawal_signed_at(&mut contract, "0xaa", &[(4, 900), (2, 100), (3, 400)], 1_000);
+        let window = contract.get_signing_window("0xaa".to_string()).unwrap();
+        assert_eq!(window.first_signed_at, 100);
+        assert_eq!(window.quorum_signed_at, 400);
+
+        withdrawal_signed_at(&mut contract, "0xbb", &[(2, 1_000), (3, 1_100)], 1_200);
+        let stats = contract.get_guardian_sla_stats();
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.average_latency, 200);
+        assert_eq!(stats.max_latency, 300);
+        assert_eq!(stats.target, Some(150));
+        assert_eq!(stats.breaches, 1);
+        assert!(contract.get_signing_window("0xcc".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_guardian_sla_stats_empty() {
+        testing_env!(get_context(accounts(0)).build());
+        let contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let stats = contract.get_guardian_sla_stats();
+        assert_eq!(stats.samples, 0);
+        assert_eq!(stats.average_latency, 0);
+        assert_eq!(stats.max_latency, 0);
+        assert!(stats.target.is_none());
+    }
+
+    #[test]
+    fn test_whitelisted_depositor_can_deposit() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_deposit_whitelist_enabled(true);
+        contract.add_whitelisted_depositor(accounts(1));
+        assert!(contract.is_deposit_whitelist_enabled());
+        assert!(contract.is_depositor_whitelisted(accounts(1)));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        assert!(contract.is_commitment_used(accounts(1), "0x01".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Depositor not whitelisted")]
+    fn test_unlisted_depositor_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_deposit_whitelist_enabled(true);
+        contract.add_whitelisted_depositor(accounts(1));
+        assert!(!contract.can_deposit(accounts(3), U128(MIN_DEPOSIT)).whitelisted);
+        testing_env!(get_context(accounts(3))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        contract.deposit("0x01".to_string(), None, None, 0);
+    }
+
+    #[test]
+    fn test_deposit_whitelist_disabled() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_deposit_whitelist_enabled(true);
+        contract.add_whitelisted_depositor(accounts(1));
+        contract.remove_whitelisted_depositor(accounts(1));
+        contract.set_deposit_whitelist_enabled(false);
+
+        assert!(contract.can_deposit(accounts(3), U128(MIN_DEPOSIT)).whitelisted);
+        testing_env!(get_context(accounts(3))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        contract.deposit("0x01".to_string(), None, None, 0);
+        assert!(contract.is_commitment_used(accounts(3), "0x01".to_string()));
+        assert!(!contract.is_depositor_whitelisted(accounts(1)));
+    }
+
+    #[test]
+    fn test_pause_history() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        assert!(contract.get_pause_history(10).is_empty());
+
+        testing_env!(get_context(accounts(0)).block_timestamp(100).build());
+        contract.pause();
+        testing_env!(get_context(accounts(0)).block_timestamp(200).build());
+        contract.unpause();
+        testing_env!(get_context(accounts(0)).block_timestamp(300).build());
+        contract.pause();
+
+        let history = contract.get_pause_history(10);
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history.iter().map(|r| (r.paused, r.timestamp)).collect::<Vec<_>>(),
+            vec![(true, 300), (false, 200), (true, 100)]
+        );
+        assert!(history.iter().all(|r| r.actor == accounts(0)));
+        assert_eq!(contract.get_pause_history(1)[0].timestamp, 300);
+    }
+
+    #[test]
+    fn test_pause_history_bounded() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let changes = MAX_PAUSE_HISTORY as u64 + 5;
+        for t in 1..=changes {
+            testing_env!(get_context(accounts(0)).block_timestamp(t).build());
+            if t % 2 == 1 {
+                contract.pause();
+            } else {
+                contract.unpause();
+            }
+        }
+
+        let history = contract.get_pause_history(u32::MAX);
+        assert_eq!(history.len(), MAX_PAUSE_HISTORY as usize);
+        assert_eq!(history[0].timestamp, changes);
+        assert!(history[0].paused);
+        assert_eq!(history.last().unwrap().timestamp, changes - MAX_PAUSE_HISTORY as u64 + 1);
+    }
+
+    #[test]
+    fn test_init_with_guardians() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            2,
+            Some(vec![accounts(2), accounts(3)]),
+        );
+        assert_eq!(contract.get_guardians().len(), 2);
+        assert!(!contract.withdrawals_ready());
+
+        testing_env!(get_context(accounts(2)).build());
+        contract.register_guardian_key(near_public_key(&signing_key(2)));
+        assert!(!contract.withdrawals_ready());
+        testing_env!(get_context(accounts(3)).build());
+        contract.register_guardian_key(near_public_key(&signing_key(3)));
+        assert!(contract.withdrawals_ready());
+    }
+
+    #[test]
+    #[should_panic(expected = "Guardian threshold must be between 1 and the initial guardian count")]
+    fn test_init_threshold_above_guardian_count() {
+        testing_env!(get_context(accounts(0)).build());
+        CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            3,
+            Some(vec![accounts(2), accounts(3)]),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Guardian threshold must be between 1 and the initial guardian count")]
+    fn test_init_zero_threshold_with_guardians() {
+        testing_env!(get_context(accounts(0)).build());
+        CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            0,
+            Some(vec![accounts(2)]),
+        );
+    }
+
+    #[test]
+    fn test_init_without_guardians_not_ready() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        assert!(!contract.withdrawals_ready());
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        assert!(contract.withdrawals_ready());
+    }
+
+    #[test]
+    fn test_withdrawal_rerouted_to_fallback() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let fallback: AccountId = "cold-wallet.near".parse().unwrap();
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_fallback_recipient(Some(fallback.clone()));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+        assert!(get_created_receipts().iter().any(|r| r.receiver_id == accounts(0)));
+
+        testing_env_with_promise_result(get_context(accounts(0)).build(), PromiseResult::Failed);
+        let result = contract.on_withdrawal_transferred("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT));
+        assert!(matches!(result, PromiseOrValue::Promise(_)));
+        // Promises are only scheduled once dropped
+        drop(result);
+        assert_eq!(last_transfer(), (fallback.clone(), NearToken::from_yoctonear(MIN_DEPOSIT)));
+        assert!(get_created_receipts().iter().any(|r| matches!(
+            &r.actions[0],
+            MockAction::FunctionCallWeight { method_name, .. } if method_name == b"on_withdrawal_rerouted"
+        )));
+
+        let logs = get_logs();
+        let event: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(logs.last().unwrap().trim_start_matches("EVENT_JSON:")).unwrap();
+        assert_eq!(event["withdrawal_hash"], "0xaa");
+        assert_eq!(event["recipient"], accounts(3).as_str());
+        assert_eq!(event["fallback_recipient"], fallback.as_str());
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+    }
+
+    #[test]
+    fn test_failed_reroute_recorded_for_reissue() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let fallback: AccountId = "cold-wallet.near".parse().unwrap();
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_fallback_recipient(Some(fallback.clone()));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+
+        testing_env_with_promise_result(get_context(accounts(0)).build(), PromiseResult::Failed);
+        assert!(!contract.on_withdrawal_rerouted("0xaa".to_string(), fallback, U128(MIN_DEPOSIT)));
+        assert_eq!(contract.get_failed_withdrawal("0xaa".to_string()), Some(U128(MIN_DEPOSIT)));
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn, U128(0));
+    }
+
+    #[test]
+    fn test_withdrawal_transfer_succeeds_without_reroute() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_fallback_recipient(Some("cold-wallet.near".parse().unwrap()));
+
+        testing_env_with_promise_result(get_context(accounts(0)).build(), PromiseResult::Successful(vec![]));
+        let result = contract.on_withdrawal_transferred("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT));
+        assert!(matches!(result, PromiseOrValue::Value(true)));
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Guardian added too recently")]
+    fn test_back_to_back_guardian_additions_rate_limited() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_min_guardian_add_interval(NANOS_PER_HOUR);
+        testing_env!(get_context(accounts(0)).block_timestamp(NANOS_PER_HOUR).build());
+        contract.add_guardian(accounts(3));
+        testing_env!(get_context(accounts(0)).block_timestamp(2 * NANOS_PER_HOUR - 1).build());
+        contract.add_guardian(accounts(4));
+    }
+
+    #[test]
+    fn test_guardian_addition_after_interval() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_min_guardian_add_interval(NANOS_PER_HOUR);
+        testing_env!(get_context(accounts(0)).block_timestamp(NANOS_PER_HOUR).build());
+        contract.add_guardian(accounts(3));
+        testing_env!(get_context(accounts(0)).block_timestamp(2 * NANOS_PER_HOUR).build());
+        contract.add_guardian(accounts(4));
+        assert!(contract.is_guardian(accounts(4)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Guardian add interval can only be lowered by proposal")]
+    fn test_owner_cannot_lower_guardian_add_interval() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_min_guardian_add_interval(NANOS_PER_HOUR);
+        contract.set_min_guardian_add_interval(0);
+    }
+
+    #[test]
+    fn test_deposit_receipt_resolves() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(3))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT * 2))
+            .build());
+        let receipt = contract.deposit("0x02".to_string(), None, None, 0);
+
+        assert_eq!(receipt.nonce, 1);
+        assert_eq!(receipt.receipt_id, deposit_receipt_id(1, &accounts(3), "0x02"));
+        let deposit = contract.get_deposit_by_receipt(receipt.receipt_id.clone()).unwrap();
+        assert_eq!(deposit.nonce, 1);
+        assert_eq!(deposit.depositor, accounts(3));
+        assert_eq!(deposit.amount.0, MIN_DEPOSIT * 2);
+        assert!(get_logs().iter().any(|log| log.contains(&receipt.receipt_id)));
+
+        // Same commitment from another depositor gets a different receipt
+        assert_ne!(receipt.receipt_id, deposit_receipt_id(1, &accounts(1), "0x02"));
+        assert!(contract.get_deposit_by_receipt("00".repeat(32)).is_none());
+    }
+
+    #[test]
+    fn test_deposit_cost_estimate_covers_storage() {
+        testing_env!(get_context(accounts(0)).build());
+        let contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let (storage, gas_tgas) = contract.deposit_cost_estimate();
+        assert!(storage.0 > 0);
+        assert!(gas_tgas > 0);
+
+        // Persist and reload so only the deposit's own records are flushed
+        env::state_write(&contract);
+        drop(contract);
+        let depositor: AccountId = "a".repeat(64).parse().unwrap();
+        testing_env!(get_context(depositor)
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        let mut contract: CashioBridge = env::state_read().unwrap();
+        let before = env::storage_usage();
+        contract.deposit(format!("0x{}", "ab".repeat(32)), None, Some("11".repeat(32)), 0);
+        drop(contract);
+
+        let delta = env::storage_usage() - before;
+        assert!(delta > 0);
+        assert!(storage.0 >= delta as u128 * env::storage_byte_cost().as_yoctonear());
+    }
+
+    #[test]
+    fn test_weighted_guardian_meets_threshold_alone() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        add_signing_guardian(&mut contract, accounts(3), &signing_key(3));
+        add_signing_guardian(&mut contract, accounts(4), &signing_key(4));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_guardian_weight(accounts(2), 3);
+        contract.update_threshold(3);
+        assert_eq!(contract.get_guardian_weight(accounts(2)), 3);
+        assert_eq!(contract.get_guardian_weight(accounts(3)), 1);
+        assert_eq!(contract.get_total_guardian_weight(), 5);
+
+        // Two weight-1 guardians fall short of the threshold
+        testing_env!(get_context(accounts(3)).build());
+        let message = contract.withdrawal_message("0xaa", &accounts(1), MIN_DEPOSIT);
+        let signatures = vec![
+            guardian_signature(accounts(3), &signing_key(3), &message),
+            guardian_signature(accounts(4), &signing_key(4), &message),
+        ];
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            contract.process_withdrawal("0xaa".to_string(), accounts(1), U128(MIN_DEPOSIT), signatures, WithdrawalRequest::default())
+        }));
+        assert!(result.is_err());
+        assert!(!contract.is_withdrawal_processed("0xaa".to_string()));
+
+        // The weight-3 guardian meets it alone
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(1), MIN_DEPOSIT, None);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot reweight: would go below threshold")]
+    fn test_guardian_weight_cannot_drop_below_threshold() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            2,
+            Some(vec![accounts(2), accounts(3)]),
+        );
+        contract.set_guardian_weight(accounts(2), 2);
+        contract.update_threshold(3);
+        contract.set_guardian_weight(accounts(2), 1);
+    }
+
+    #[test]
+    fn test_abi_schema_lists_methods_and_events() {
+        testing_env!(get_context(accounts(0)).build());
+        let contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let schema: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(&contract.get_abi_schema()).unwrap();
+
+        let method = |name: &str| {
+            schema["methods"].as_array().unwrap().iter()
+                .find(|m| m["name"] == name)
+                .cloned()
+                .unwrap_or_else(|| panic!("{} missing from schema", name))
+        };
+        assert_eq!(method("deposit")["kind"], "payable");
+        assert_eq!(method("deposit")["args"][0], "commitment");
+        assert_eq!(method("process_withdrawal")["kind"], "call");
+        assert_eq!(method("get_stats")["kind"], "view");
+        assert_eq!(method("new")["kind"], "init");
+
+        let events: Vec<&str> = schema["events"].as_array().unwrap().iter()
+            .map(|e| e["name"].as_str().unwrap())
+            .collect();
+        for event in ["DepositEvent", "WithdrawalEvent", "GuardianEvent", "ProposalEvent"] {
+            assert!(events.contains(&event), "{} missing from schema", event);
+        }
+    }
+
+    #[test]
+    fn test_deposit_batch_records_each_commitment() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT * 6))
+            .build());
+        let receipts = contract.deposit_batch(
+            vec!["0x01".to_string(), "0x02".to_string(), "0x03".to_string()],
+            vec![U128(MIN_DEPOSIT), U128(MIN_DEPOSIT * 2), U128(MIN_DEPOSIT * 3)],
+        );
+
+        assert_eq!(receipts.iter().map(|r| r.nonce).collect::<Vec<_>>(), vec![0, 1, 2]);
+        for (nonce, (commitment, amount)) in [("0x01", 1), ("0x02", 2), ("0x03", 3)].into_iter().enumerate() {
+            let deposit = contract.get_deposit(nonce as u64).unwrap();
+            assert_eq!(deposit.commitment, commitment);
+            assert_eq!(deposit.amount.0, MIN_DEPOSIT * amount);
+            assert!(contract.is_commitment_used(accounts(1), commitment.to_string()));
+        }
+        assert_eq!(get_logs().iter().filter(|log| log.contains("\"receipt_id\"")).count(), 3);
+        let (_, total_deposited, _, _, _) = contract.get_stats();
+        assert_eq!(total_deposited.0, MIN_DEPOSIT * 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Amounts do not sum to attached deposit")]
+    fn test_deposit_batch_sum_mismatch() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT * 2))
+            .build());
+        contract.deposit_batch(
+            vec!["0x01".to_string(), "0x02".to_string()],
+            vec![U128(MIN_DEPOSIT), U128(MIN_DEPOSIT * 2)],
+        );
+    }
+
+    #[test]
+    fn test_withdrawal_forwarded_to_spoke_chain() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+
+        let destination = ChainDestination::Chain {
+            chain_id: "solana".to_string(),
+            recipient: "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin".to_string(),
+        };
+        testing_env!(get_context(accounts(2)).build());
+        let message = with_destination(
+            contract.withdrawal_message("0xaa", &accounts(3), MIN_DEPOSIT),
+            &destination,
+        );
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        let _ = contract.process_withdrawal(
+            "0xaa".to_string(),
+            accounts(3),
+            U128(MIN_DEPOSIT),
+            vec![signature],
+            WithdrawalRequest {
+                destination,
+                ..Default::default()
+            },
+        );
+
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        assert_eq!(contract.get_total_forwarded().0, MIN_DEPOSIT);
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn.0, 0);
+        assert!(get_created_receipts().is_empty());
+        assert!(get_logs().iter().any(|log| log.contains("\"chain_id\":\"solana\"")));
+
+        // Settling on NEAR pays out as before
+        let _ = signed_withdrawal(&mut contract, "0xbb", accounts(3), MIN_DEPOSIT, None);
+        assert_eq!(last_transfer().0, accounts(3));
+        assert_eq!(contract.get_total_forwarded().0, MIN_DEPOSIT);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_forward_destination_must_be_signed() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+
+        // Guardians signed a NEAR payout; a relaying guardian can't redirect it
+        testing_env!(get_context(accounts(2)).build());
+        let message = contract.withdrawal_message("0xaa", &accounts(3), MIN_DEPOSIT);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        let _ = contract.process_withdrawal(
+            "0xaa".to_string(),
+            accounts(3),
+            U128(MIN_DEPOSIT),
+            vec![signature],
+            WithdrawalRequest {
+                destination: ChainDestination::Chain { chain_id: "solana".to_string(), recipient: "attacker".to_string() },
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_are_withdrawals_processed() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+        let _ = signed_withdrawal(&mut contract, "0xcc", accounts(3), MIN_DEPOSIT, None);
+
+        let query = ["0xbb", "0xaa", "0xdd", "0xcc"].map(String::from).to_vec();
+        assert_eq!(contract.are_withdrawals_processed(query), vec![false, true, false, true]);
+        assert!(contract.are_withdrawals_processed(Vec::new()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Too many withdrawal hashes")]
+    fn test_are_withdrawals_processed_limit() {
+        testing_env!(get_context(accounts(0)).build());
+        let contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.are_withdrawals_processed(vec!["0xaa".to_string(); MAX_WITHDRAWAL_QUERY + 1]);
+    }
+
+    #[test]
+    fn test_deposit_fee_follows_utilization() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let near = MIN_DEPOSIT * 100;
+        contract.set_deposit_caps(None, None, Some(U128(near * 10)));
+        contract.set_deposit_fee_curve(vec![
+            FeeTier { utilization_bps: 2000, fee_bps: 10 },
+            FeeTier { utilization_bps: 5000, fee_bps: 50 },
+            FeeTier { utilization_bps: 8000, fee_bps: 200 },
+        ]);
+
+        // Low: 10% utilization sits below the first tier
+        seed_deposit(&mut contract, "0x01", near);
+        assert_eq!(contract.get_tvl().0, near);
+        assert_eq!(contract.get_current_deposit_fee_bps(), 0);
+
+        // Medium: 60% utilization, and the next deposit is credited net
+        seed_deposit(&mut contract, "0x02", near * 5);
+        assert_eq!(contract.get_current_deposit_fee_bps(), 50);
+        seed_deposit(&mut contract, "0x03", near * 2);
+        assert_eq!(contract.get_collected_deposit_fees().0, near * 2 / 200);
+        assert_eq!(contract.get_deposit(2).unwrap().amount.0, near * 2 - near * 2 / 200);
+
+        // High: past 80% utilization
+        seed_deposit(&mut contract, "0x04", near);
+        assert_eq!(contract.get_current_deposit_fee_bps(), 200);
+        let (_, total_deposited, _, _, _) = contract.get_stats();
+        assert_eq!(total_deposited.0 + contract.get_collected_deposit_fees().0, near * 9);
+    }
+
+    #[test]
+    #[should_panic(expected = "Fee tiers must have ascending utilization")]
+    fn test_deposit_fee_curve_must_ascend() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_deposit_fee_curve(vec![
+            FeeTier { utilization_bps: 5000, fee_bps: 50 },
+            FeeTier { utilization_bps: 5000, fee_bps: 100 },
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit not yet final")]
+    fn test_confirm_deposit_before_finality() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_min_deposit_confirmations(NANOS_PER_HOUR);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).block_timestamp(NANOS_PER_HOUR - 1).build());
+        contract.confirm_deposit_relayed(0);
+    }
+
+    #[test]
+    fn test_confirm_deposit_after_finality() {
+        let mut contract = bridge_with_guardian(1);
+        contract.set_min_deposit_confirmations(NANOS_PER_HOUR);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .block_timestamp(NANOS_PER_HOUR)
+            .build());
+        contract.deposit("0x02".to_string(), None, None, 0);
+
+        // Only the older deposit has aged past the window
+        testing_env!(get_context(accounts(2)).block_timestamp(NANOS_PER_HOUR).build());
+        let nonces = vec![0, 1];
+        let message = contract.relay_confirmation_message(&nonces);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        contract.confirm_deposits_relayed(nonces, vec![signature]);
+        assert!(contract.get_deposit(0).unwrap().processed);
+        assert!(!contract.get_deposit(1).unwrap().processed);
+
+        testing_env!(get_context(accounts(2)).block_timestamp(2 * NANOS_PER_HOUR).build());
+        contract.confirm_deposit_relayed(1);
+        assert!(contract.get_deposit(1).unwrap().processed);
+    }
+
+    #[test]
+    fn test_merkle_path_reconstructs_root() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let commitments = ["0x01", "0x02", "0x03", "0x04", "0x05"];
+        for commitment in commitments {
+            seed_deposit(&mut contract, commitment, MIN_DEPOSIT);
+        }
+
+        let (root, _) = contract.get_deposit_root();
+        for (leaf_index, commitment) in commitments.iter().enumerate() {
+            let path = contract.get_merkle_path(leaf_index as u64);
+            assert_eq!(path.len(), DEPOSIT_TREE_DEPTH);
+            let mut node = deposit_leaf(commitment);
+            let mut index = leaf_index;
+            for sibling in path {
+                let sibling = hex::decode(sibling).unwrap();
+                node = if index & 1 == 0 {
+                    hash_children(&node, &sibling)
+                } else {
+                    hash_children(&sibling, &node)
+                };
+                index /= 2;
+            }
+            assert_eq!(hex::encode(node), root);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Leaf index out of range")]
+    fn test_merkle_path_out_of_range() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        contract.get_merkle_path(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Bridge is paused")]
+    fn test_timed_pause_blocks_before_expiry() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+        testing_env!(get_context(accounts(0)).build());
+        contract.pause_for(NANOS_PER_HOUR);
+        assert_eq!(rejection_reason(&mut contract, "0x02", MIN_DEPOSIT), "Bridge is paused");
+        assert_eq!(contract.get_auto_unpause_at(), Some(NANOS_PER_HOUR));
+
+        testing_env!(get_context(accounts(2)).block_timestamp(NANOS_PER_HOUR - 1).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+    }
+
+    #[test]
+    fn test_timed_pause_resumes_after_expiry() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 2);
+        testing_env!(get_context(accounts(0)).build());
+        contract.pause_for(NANOS_PER_HOUR);
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .block_timestamp(NANOS_PER_HOUR)
+            .build());
+        contract.deposit("0x02".to_string(), None, None, 0);
+        let (_, _, _, _, is_paused) = contract.get_stats();
+        assert!(!is_paused);
+
+        testing_env!(get_context(accounts(2)).block_timestamp(NANOS_PER_HOUR).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+
+        // An indefinite pause drops the timer
+        testing_env!(get_context(accounts(0)).block_timestamp(NANOS_PER_HOUR).build());
+        contract.pause_for(NANOS_PER_HOUR);
+        contract.pause();
+        assert_eq!(contract.get_auto_unpause_at(), None);
+        testing_env!(get_context(accounts(0)).block_timestamp(3 * NANOS_PER_HOUR).build());
+        let (_, _, _, _, is_paused) = contract.get_stats();
+        assert!(is_paused);
+    }
+
+    #[test]
+    fn test_asset_tvl_caps_per_token() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        let usdc: AccountId = "usdc.near".parse().unwrap();
+        let wbtc: AccountId = "wbtc.near".parse().unwrap();
+        contract.add_supported_token(usdc.clone());
+        contract.add_supported_token(wbtc.clone());
+        contract.set_asset_tvl_cap(usdc.clone(), Some(U128(1_000)));
+        contract.set_asset_tvl_cap(wbtc.clone(), Some(U128(10)));
+
+        testing_env!(get_context(usdc.clone()).build());
+        assert_eq!(refunded(contract.ft_on_transfer(accounts(1), U128(800), "0x01".to_string())), 0);
+        assert_eq!(refunded(contract.ft_on_transfer(accounts(1), U128(300), "0x02".to_string())), 300);
+        assert!(!contract.is_token_commitment_used(usdc.clone(), accounts(1), "0x02".to_string()));
+
+        // wbtc's lower cap is hit independently of usdc's headroom
+        testing_env!(get_context(wbtc.clone()).build());
+        assert_eq!(refunded(contract.ft_on_transfer(accounts(1), U128(10), "0x03".to_string())), 0);
+        assert_eq!(refunded(contract.ft_on_transfer(accounts(1), U128(1), "0x04".to_string())), 1);
+
+        assert_eq!(contract.get_asset_tvl(usdc.clone()).0, 800);
+        assert_eq!(contract.get_asset_tvl(wbtc).0, 10);
+
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_asset_tvl_cap(usdc.clone(), None);
+        testing_env!(get_context(usdc.clone()).build());
+        assert_eq!(refunded(contract.ft_on_transfer(accounts(1), U128(300), "0x02".to_string())), 0);
+        assert_eq!(contract.get_asset_tvl(usdc).0, 1_100);
+    }
+
+    #[test]
+    fn test_effective_guardian_capacity_after_suspensions() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            2,
+            None,
+        );
+        for i in 2..5 {
+            add_signing_guardian(&mut contract, accounts(i), &signing_key(i as u8));
+        }
+        // A guardian without a registered key adds no capacity
+        testing_env!(get_context(accounts(0)).build());
+        contract.add_guardian(accounts(5));
+        assert_eq!(contract.effective_guardian_capacity(), (3, 2, true));
+
+        contract.suspend_guardian(accounts(2));
+        assert_eq!(contract.effective_guardian_capacity(), (2, 2, true));
+
+        contract.suspend_guardian(accounts(3));
+        assert_eq!(contract.effective_guardian_capacity(), (1, 2, false));
+        assert!(!contract.withdrawals_ready());
+    }
+
+    #[test]
+    fn test_get_nonce_gaps() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        for commitment in ["0x01", "0x02", "0x03"] {
+            seed_deposit(&mut contract, commitment, MIN_DEPOSIT);
+        }
+        testing_env!(get_context(accounts(2)).build());
+        contract.confirm_deposit_relayed(1);
+        testing_env!(get_context(accounts(0)).build());
+        contract.prune_deposit(1);
+
+        assert_eq!(contract.get_nonce_gaps(0, 3), vec![1]);
+        assert!(contract.get_nonce_gaps(2, 3).is_empty());
+        // Unissued nonces past the last deposit aren't gaps
+        assert_eq!(contract.get_nonce_gaps(0, 10), vec![1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nonce range exceeds scan limit")]
+    fn test_get_nonce_gaps_limit() {
+        testing_env!(get_context(accounts(0)).build());
+        let contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.get_nonce_gaps(0, MAX_NONCE_SCAN + 1);
+    }
+
+    fn admin_multisig_fixture() -> (CashioBridge, AccountId) {
+        let bridge: AccountId = "bridge.near".parse().unwrap();
+        testing_env!(get_context(accounts(0)).current_account_id(bridge.clone()).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_admin_multisig(vec![accounts(1), accounts(2), accounts(3)], 2);
+        (contract, bridge)
+    }
+
+    #[test]
+    fn test_admin_action_needs_threshold_approvals() {
+        let (mut contract, bridge) = admin_multisig_fixture();
+
+        testing_env!(get_context(accounts(1)).current_account_id(bridge.clone()).build());
+        let id = contract.propose_admin_action("pause".to_string(), "{}".to_string());
+        assert!(!contract.get_admin_proposal(id).unwrap().executed);
+        assert!(get_created_receipts().is_empty());
+
+        testing_env!(get_context(accounts(2)).current_account_id(bridge.clone()).build());
+        contract.approve_admin_action(id);
+        assert!(contract.get_admin_proposal(id).unwrap().executing);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].receiver_id, bridge);
+        match &receipts[0].actions[0] {
+            MockAction::FunctionCallWeight { method_name, .. } => assert_eq!(method_name, b"pause"),
+            _ => panic!("Expected pause call"),
+        }
+
+        // The executed call arrives from the contract itself
+        testing_env!(get_context(bridge.clone()).current_account_id(bridge.clone()).build());
+        contract.pause();
+        let (_, _, _, _, is_paused) = contract.get_stats();
+        assert!(is_paused);
+
+        testing_env_with_promise_result(
+            get_context(bridge.clone()).current_account_id(bridge).build(),
+            PromiseResult::Successful(vec![]),
+        );
+        assert!(contract.on_admin_proposal_executed(id));
+        let proposal = contract.get_admin_proposal(id).unwrap();
+        assert!(proposal.executed && !proposal.executing);
+    }
+
+    #[test]
+    fn test_failed_admin_action_can_be_retried() {
+        let (mut contract, bridge) = admin_multisig_fixture();
+        testing_env!(get_context(accounts(1)).current_account_id(bridge.clone()).build());
+        let id = contract.propose_admin_action("pause".to_string(), "{}".to_string());
+        testing_env!(get_context(accounts(2)).current_account_id(bridge.clone()).build());
+        contract.approve_admin_action(id);
+
+        testing_env_with_promise_result(
+            get_context(bridge.clone()).current_account_id(bridge.clone()).build(),
+            PromiseResult::Failed,
+        );
+        assert!(!contract.on_admin_proposal_executed(id));
+        let proposal = contract.get_admin_proposal(id).unwrap();
+        assert!(!proposal.executed && !proposal.executing);
+
+        testing_env!(get_context(accounts(3)).current_account_id(bridge.clone()).build());
+        contract.execute_admin_proposal(id);
+        assert!(contract.get_admin_proposal(id).unwrap().executing);
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 2);
+        assert_eq!(receipts[0].receiver_id, bridge);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proposal expired")]
+    fn test_expired_admin_proposal_approval() {
+        let (mut contract, bridge) = admin_multisig_fixture();
+        testing_env!(get_context(accounts(1)).current_account_id(bridge.clone()).build());
+        let id = contract.propose_admin_action("pause".to_string(), "{}".to_string());
+
+        testing_env!(get_context(accounts(2))
+            .current_account_id(bridge)
+            .block_timestamp(ADMIN_PROPOSAL_TTL + 1)
+            .build());
+        contract.approve_admin_action(id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner calls require admin approval")]
+    fn test_owner_bypassing_admin_multisig() {
+        let (mut contract, bridge) = admin_multisig_fixture();
+        testing_env!(get_context(accounts(0)).current_account_id(bridge).build());
+        contract.pause();
+    }
+
+    #[test]
+    fn test_deposit_pools_are_isolated() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        let first = contract.deposit("0x01".to_string(), None, None, 1).nonce;
+        assert_eq!(last_deposit_event()["pool_id"], 1);
+        // The same commitment stays free in another pool
+        let second = contract.deposit("0x01".to_string(), None, None, 2).nonce;
+        contract.deposit("0x02".to_string(), None, None, 2);
+
+        assert_eq!(contract.get_deposit(first).unwrap().pool_id, 1);
+        assert_eq!(contract.get_deposit(second).unwrap().pool_id, 2);
+        assert_eq!(contract.get_pool_stats(1), (U128(MIN_DEPOSIT), 1));
+        assert_eq!(contract.get_pool_stats(2), (U128(2 * MIN_DEPOSIT), 2));
+        assert_eq!(contract.get_pool_stats(0), (U128(0), 0));
+        assert!(!contract.is_commitment_used(accounts(1), "0x01".to_string()));
+        assert!(contract.is_commitment_used(accounts(1), "1/0x01".to_string()));
+
+        // Tree leaves are namespaced by pool too
+        let leaves: Vec<Vec<u8>> = ["1/0x01", "2/0x01", "2/0x02"].iter().map(|c| deposit_leaf(c)).collect();
+        assert_eq!(contract.get_deposit_root(), (hex::encode(reference_root(&leaves)), 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Commitment already used by depositor")]
+    fn test_deposit_pool_rejects_reused_commitment() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        contract.deposit("0x01".to_string(), None, None, 3);
+        contract.deposit("0x01".to_string(), None, None, 3);
+    }
+
+    #[test]
+    fn test_prune_old_commitments() {
+        let hour = NANOS_PER_HOUR;
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_commitment_retention(Some(hour));
+        assert_eq!(contract.get_commitment_retention(), Some(hour));
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .block_timestamp(1)
+            .build());
+        contract.deposit("0x01".to_string(), None, None, 0);
+        contract.deposit("0x02".to_string(), None, None, 7);
+
+        testing_env!(get_context(accounts(0)).block_timestamp(2 * hour).build());
+        assert_eq!(contract.prune_old_commitments(hour), 2);
+        assert!(!contract.is_commitment_used(accounts(1), "0x01".to_string()));
+        assert!(!contract.is_commitment_used(accounts(1), "7/0x02".to_string()));
+        // Already scanned deposits are not revisited
+        assert_eq!(contract.prune_old_commitments(hour), 0);
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .block_timestamp(2 * hour)
+            .build());
+        contract.deposit("0x01".to_string(), None, None, 0);
+        assert!(contract.is_commitment_used(accounts(1), "0x01".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Commitment already used by depositor")]
+    fn test_prune_old_commitments_keeps_recent() {
+        let hour = NANOS_PER_HOUR;
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
         );
-        
-        nonce
+        contract.set_commitment_retention(Some(hour));
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .block_timestamp(1)
+            .build());
+        contract.deposit("0x01".to_string(), None, None, 0);
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .block_timestamp(2 * hour)
+            .build());
+        contract.deposit("0x02".to_string(), None, None, 0);
+
+        testing_env!(get_context(accounts(0)).block_timestamp(3 * hour).build());
+        assert_eq!(contract.prune_old_commitments(hour), 1);
+
+        // The deposit inside the protected window still blocks replays
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .block_timestamp(3 * hour)
+            .build());
+        contract.deposit("0x02".to_string(), None, None, 0);
     }
 
-    /// Process a verified withdrawal from hub chain
-    /// Only callable by guardians
-    pub fn process_withdrawal(
-        &mut self,
-        withdrawal_hash: String,
-        recipient: AccountId,
-        amount: U128,
-    ) -> Promise {
-        require!(!self.is_paused, "Bridge is paused");
-        self.assert_guardian();
-        require!(
-            !self.processed_withdrawals.contains(&withdrawal_hash),
-            "Withdrawal already processed"
+    #[test]
+    #[should_panic(expected = "Cutoff within the protected window")]
+    fn test_prune_old_commitments_rejects_recent_cutoff() {
+        let hour = NANOS_PER_HOUR;
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
         );
-        
-        // TODO: In production, verify threshold signatures from guardians
-        // For now, trust single guardian for simplicity
-        
-        // Mark as processed
-        self.processed_withdrawals.insert(withdrawal_hash.clone());
-        self.total_withdrawn += amount.0;
-        
-        // Emit event
-        let event = WithdrawalEvent {
-            withdrawal_hash: withdrawal_hash.clone(),
-            recipient: recipient.clone(),
-            amount,
-            timestamp: env::block_timestamp(),
-        };
-        
-        log!("EVENT_JSON:{}", near_sdk::serde_json::to_string(&event).unwrap());
-        log!("Withdrawal processed: {} yoctoNEAR to {}", amount.0, recipient);
-        
-        // Transfer NEAR to recipient
-        Promise::new(recipient).transfer(NearToken::from_yoctonear(amount.0))
+        contract.set_commitment_retention(Some(hour));
+
+        testing_env!(get_context(accounts(0)).block_timestamp(hour / 2).build());
+        contract.prune_old_commitments(1);
     }
 
-    // ============ View Functions ============
+    #[test]
+    fn test_reissue_failed_withdrawal() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
 
-    /// Get bridge statistics
-    pub fn get_stats(&self) -> (U128, U128, U128, u64, bool) {
-        (
-            U128(env::account_balance().as_yoctonear()),
-            U128(self.total_deposited),
-            U128(self.total_withdrawn),
-            self.deposit_nonce,
-            self.is_paused,
-        )
-    }
+        testing_env_with_promise_result(get_context(accounts(0)).build(), PromiseResult::Failed);
+        let result = contract.on_withdrawal_transferred("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT));
+        assert!(matches!(result, PromiseOrValue::Value(false)));
+        assert_eq!(contract.get_failed_withdrawal("0xaa".to_string()), Some(U128(MIN_DEPOSIT)));
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn, U128(0));
 
-    /// Get deposit details by nonce
-    pub fn get_deposit(&self, nonce: u64) -> Option<Deposit> {
-        self.deposits.get(&nonce).cloned()
+        testing_env!(get_context(accounts(2)).build());
+        let message = contract.reissue_message("0xaa", &accounts(4), MIN_DEPOSIT);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        let _ = contract.reissue_withdrawal("0xaa".to_string(), accounts(4), vec![signature]);
+        assert_eq!(last_transfer(), (accounts(4), NearToken::from_yoctonear(MIN_DEPOSIT)));
+        assert_eq!(contract.get_failed_withdrawal("0xaa".to_string()), None);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        let (_, _, total_withdrawn, _, _) = contract.get_stats();
+        assert_eq!(total_withdrawn, U128(MIN_DEPOSIT));
     }
 
-    /// Check if commitment is used
-    pub fn is_commitment_used(&self, commitment: String) -> bool {
-        self.processed_deposits.contains(&commitment)
-    }
+    #[test]
+    #[should_panic(expected = "Withdrawal exceeds bridge liabilities")]
+    fn test_reissue_exceeding_liabilities() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
 
-    /// Check if withdrawal is processed
-    pub fn is_withdrawal_processed(&self, withdrawal_hash: String) -> bool {
-        self.processed_withdrawals.contains(&withdrawal_hash)
-    }
+        testing_env_with_promise_result(get_context(accounts(0)).build(), PromiseResult::Failed);
+        let _ = contract.on_withdrawal_transferred("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT));
 
-    /// Get guardian list
-    pub fn get_guardians(&self) -> Vec<AccountId> {
-        self.guardians.iter().cloned().collect()
+        // The failed amount was released back and paid out to someone else
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xbb", accounts(3), MIN_DEPOSIT, None);
+
+        let message = contract.reissue_message("0xaa", &accounts(4), MIN_DEPOSIT);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        let _ = contract.reissue_withdrawal("0xaa".to_string(), accounts(4), vec![signature]);
     }
 
-    /// Get guardian count
-    pub fn guardian_count(&self) -> u32 {
-        self.guardians.len()
+    #[test]
+    #[should_panic(expected = "Withdrawal not failed")]
+    fn test_reissue_rejects_successful_withdrawal() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(3), MIN_DEPOSIT, None);
+
+        testing_env_with_promise_result(get_context(accounts(0)).build(), PromiseResult::Successful(vec![]));
+        let result = contract.on_withdrawal_transferred("0xaa".to_string(), accounts(3), U128(MIN_DEPOSIT));
+        assert!(matches!(result, PromiseOrValue::Value(true)));
+
+        testing_env!(get_context(accounts(2)).build());
+        let message = contract.reissue_message("0xaa", &accounts(4), MIN_DEPOSIT);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        let _ = contract.reissue_withdrawal("0xaa".to_string(), accounts(4), vec![signature]);
     }
 
-    /// Check if account is guardian
-    pub fn is_guardian(&self, account_id: AccountId) -> bool {
-        self.guardians.contains(&account_id)
+    /// Withdraw as guardian accounts(2), signing against guardian set `set_hash`
+    fn set_signed_withdrawal(contract: &mut CashioBridge, set_hash: &str) -> PromiseOrValue<()> {
+        let message = contract.withdrawal_set_message("0xaa", &accounts(3), MIN_DEPOSIT, set_hash);
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        contract.process_withdrawal(
+            "0xaa".to_string(),
+            accounts(3),
+            U128(MIN_DEPOSIT),
+            vec![signature],
+            WithdrawalRequest {
+                guardian_set_hash: Some(set_hash.to_string()),
+                ..Default::default()
+            },
+        )
     }
 
-    /// Get owner
-    pub fn get_owner(&self) -> AccountId {
-        self.owner_id.clone()
+    #[test]
+    fn test_withdrawal_signed_against_previous_guardian_set() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let previous = contract.get_guardian_set_hash();
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_guardian_set_window(1);
+        contract.add_guardian(accounts(4));
+        assert_ne!(contract.get_guardian_set_hash(), previous);
+
+        // Approvals gathered before the guardian was added still count
+        testing_env!(get_context(accounts(2)).build());
+        let _ = set_signed_withdrawal(&mut contract, &previous);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
     }
 
-    /// Get hub chain ID
-    pub fn get_hub_chain_id(&self) -> String {
-        self.hub_chain_id.clone()
+    #[test]
+    #[should_panic(expected = "Unknown guardian set")]
+    fn test_withdrawal_rejects_unknown_guardian_set() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = set_signed_withdrawal(&mut contract, &"00".repeat(32));
     }
 
-    /// Get guardian threshold
-    pub fn get_guardian_threshold(&self) -> u32 {
-        self.guardian_threshold
+    #[test]
+    #[should_panic(expected = "Guardian set outside epoch window")]
+    fn test_withdrawal_rejects_expired_guardian_set() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        let previous = contract.get_guardian_set_hash();
+        testing_env!(get_context(accounts(0)).build());
+        contract.add_guardian(accounts(4));
+
+        testing_env!(get_context(accounts(2)).build());
+        let _ = set_signed_withdrawal(&mut contract, &previous);
     }
 
-    // ============ Internal Functions ============
+    #[test]
+    #[should_panic(expected = "Too many commitments in batch")]
+    fn test_deposit_batch_over_cap() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_max_batch_commitments(2);
+        assert_eq!(contract.get_max_batch_commitments(), 2);
 
-    fn assert_owner(&self) {
-        require!(
-            env::predecessor_account_id() == self.owner_id,
-            "Only owner can call this method"
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT * 3))
+            .build());
+        contract.deposit_batch(
+            vec!["0x01".to_string(), "0x02".to_string(), "0x03".to_string()],
+            vec![U128(MIN_DEPOSIT); 3],
         );
     }
 
-    fn assert_guardian(&self) {
-        require!(
-            self.guardians.contains(&env::predecessor_account_id()),
-            "Only guardians can call this method"
+    #[test]
+    #[should_panic(expected = "Duplicate commitment in batch")]
+    fn test_deposit_batch_duplicate_commitment() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT * 3))
+            .build());
+        contract.deposit_batch(
+            vec!["0x01".to_string(), "0x02".to_string(), "0x01".to_string()],
+            vec![U128(MIN_DEPOSIT); 3],
         );
     }
-}
 
-// ============ Tests ============
+    #[test]
+    #[should_panic(expected = "Invalid batch commitment cap")]
+    fn test_batch_cap_above_ceiling() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_max_batch_commitments(MAX_DEPOSIT_BATCH as u32 + 1);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::testing_env;
+    #[test]
+    fn test_deposit_reserves_storage() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        contract.set_deposit_storage_reserve(true);
+        assert!(contract.get_deposit_storage_reserve());
+        let (estimate, _) = contract.deposit_cost_estimate();
 
-    fn get_context(predecessor: AccountId) -> VMContextBuilder {
-        let mut builder = VMContextBuilder::new();
-        builder
-            .predecessor_account_id(predecessor)
-            .current_account_id(accounts(0));
-        builder
+        let attached = MIN_DEPOSIT * 10;
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(attached))
+            .build());
+        let before = env::storage_usage();
+        let nonce = contract.deposit("0x01".to_string(), None, None, 0).nonce;
+
+        // The reserve is the storage the deposit's records actually added
+        let reserve = (env::storage_usage() - before) as u128 * env::storage_byte_cost().as_yoctonear();
+        assert!(reserve > 0 && reserve <= estimate.0);
+        let bridged = attached - reserve;
+        assert_eq!(contract.get_deposit(nonce).unwrap().amount, U128(bridged));
+        let event = last_deposit_event();
+        assert_eq!(event["amount"], bridged.to_string());
+        assert_eq!(event["storage_reserved"], reserve.to_string());
+        let (_, total_deposited, _, _, _) = contract.get_stats();
+        assert_eq!(total_deposited, U128(bridged));
     }
 
     #[test]
-    fn test_init() {
-        let context = get_context(accounts(0));
-        testing_env!(context.build());
-
-        let contract = CashioBridge::new(
+    fn test_deposit_batch_reserves_storage() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
             accounts(0),
             "999888777".to_string(),
             1,
+            None,
         );
+        contract.set_deposit_storage_reserve(true);
 
-        assert_eq!(contract.get_owner(), accounts(0));
-        assert_eq!(contract.get_hub_chain_id(), "999888777");
-        assert_eq!(contract.get_guardian_threshold(), 1);
-        assert!(!contract.is_paused);
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT * 20))
+            .build());
+        let before = env::storage_usage();
+        let receipts = contract.deposit_batch(
+            vec!["0x01".to_string(), "0x02".to_string()],
+            vec![U128(MIN_DEPOSIT * 10), U128(MIN_DEPOSIT * 10)],
+        );
+
+        let events: Vec<near_sdk::serde_json::Value> = get_logs()
+            .iter()
+            .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+            .map(|json| near_sdk::serde_json::from_str(json).unwrap())
+            .filter(|event: &near_sdk::serde_json::Value| event.get("storage_reserved").is_some())
+            .collect();
+        assert_eq!(events.len(), 2);
+        let reserves: Vec<u128> = events
+            .iter()
+            .map(|event| event["storage_reserved"].as_str().unwrap().parse().unwrap())
+            .collect();
+        // The first deposit also fills the deposit tree, so each pays its own share
+        assert!(reserves[0] > reserves[1] && reserves[1] > 0);
+        let used = (env::storage_usage() - before) as u128 * env::storage_byte_cost().as_yoctonear();
+        assert_eq!(reserves.iter().sum::<u128>(), used);
+        for (receipt, reserve) in receipts.iter().zip(&reserves) {
+            let bridged = MIN_DEPOSIT * 10 - reserve;
+            assert_eq!(contract.get_deposit(receipt.nonce).unwrap().amount, U128(bridged));
+        }
+        let (_, total_deposited, _, _, _) = contract.get_stats();
+        assert_eq!(total_deposited.0, MIN_DEPOSIT * 20 - used);
     }
 
     #[test]
-    fn test_add_guardian() {
-        let context = get_context(accounts(0));
-        testing_env!(context.build());
-
+    #[should_panic(expected = "Deposit does not cover storage")]
+    fn test_deposit_below_storage_reserve() {
+        testing_env!(get_context(accounts(0)).build());
         let mut contract = CashioBridge::new(
             accounts(0),
             "999888777".to_string(),
             1,
+            None,
         );
+        contract.set_deposit_storage_reserve(true);
 
-        contract.add_guardian(accounts(1));
-        assert!(contract.is_guardian(accounts(1)));
-        assert_eq!(contract.guardian_count(), 1);
+        // Well short of the storage a first deposit adds
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT / 10))
+            .build());
+        contract.deposit("0x01".to_string(), None, None, 0);
     }
 
     #[test]
-    fn test_deposit() {
-        let context = get_context(accounts(1));
-        testing_env!(context
+    fn test_small_withdrawal_needs_reduced_threshold() {
+        let mut contract = bridge_with_guardian(2);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 4);
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_small_withdrawal_tier(U128(MIN_DEPOSIT * 2), 1);
+        assert_eq!(contract.get_small_withdrawal_tier(), (U128(MIN_DEPOSIT * 2), 1));
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(1), MIN_DEPOSIT, None);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        // Quorum is reached at the reduced threshold
+        assert!(contract.get_signing_window("0xaa".to_string()).is_some());
+
+        // At or above the bound the full set is still required
+        let message = contract.withdrawal_message("0xbb", &accounts(1), MIN_DEPOSIT * 2);
+        let signatures = vec![
+            guardian_signature(accounts(2), &signing_key(2), &message),
+            guardian_signature(accounts(3), &signing_key(3), &message),
+        ];
+        let _ = contract.process_withdrawal(
+            "0xbb".to_string(),
+            accounts(1),
+            U128(MIN_DEPOSIT * 2),
+            signatures,
+            WithdrawalRequest::default(),
+        );
+        assert!(contract.is_withdrawal_processed("0xbb".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_large_withdrawal_needs_full_threshold() {
+        let mut contract = bridge_with_guardian(2);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT * 4);
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_small_withdrawal_tier(U128(MIN_DEPOSIT * 2), 1);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(1), MIN_DEPOSIT * 2, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Small withdrawal threshold must be at least 1")]
+    fn test_small_withdrawal_threshold_zero() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            2,
+            None,
+        );
+        contract.set_small_withdrawal_tier(U128(MIN_DEPOSIT), 0);
+    }
+
+    #[test]
+    fn test_verbose_and_compact_deposit_events() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut contract = CashioBridge::new(
+            accounts(0),
+            "999888777".to_string(),
+            1,
+            None,
+        );
+        assert!(contract.get_verbose_events());
+
+        testing_env!(get_context(accounts(1))
             .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
             .build());
+        contract.deposit("0x01".to_string(), None, None, 0);
+        let event = last_deposit_event();
+        assert_eq!(event["commitment"], "0x01");
+        assert_eq!(event["amount"], MIN_DEPOSIT.to_string());
 
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_verbose_events(false);
+        assert!(!contract.get_verbose_events());
+
+        testing_env!(get_context(accounts(1))
+            .attached_deposit(NearToken::from_yoctonear(MIN_DEPOSIT))
+            .build());
+        let nonce = contract.deposit("0x02".to_string(), None, None, 0).nonce;
+        let log = get_logs()
+            .into_iter()
+            .find(|log| log.starts_with("EVENT_JSON:"))
+            .unwrap();
+        let event: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(&log["EVENT_JSON:".len()..]).unwrap();
+        assert_eq!(
+            event,
+            near_sdk::serde_json::json!({ "nonce": nonce, "commitment": "0x02" })
+        );
+        assert_eq!(contract.get_deposit(nonce).unwrap().amount, U128(MIN_DEPOSIT));
+    }
+
+    #[test]
+    fn test_deposit_carries_schema_version() {
+        testing_env!(get_context(accounts(0)).build());
         let mut contract = CashioBridge::new(
             accounts(0),
             "999888777".to_string(),
             1,
+            None,
         );
-
-        let commitment = "0x0102030405060708091011121314151617181920212223242526272829303132".to_string();
-        let nonce = contract.deposit(commitment.clone());
-        
-        assert_eq!(nonce, 0);
-        assert!(contract.is_commitment_used(commitment));
-        
-        let (_, total_deposited, _, deposit_nonce, _) = contract.get_stats();
-        assert_eq!(total_deposited.0, MIN_DEPOSIT);
-        assert_eq!(deposit_nonce, 1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        assert_eq!(contract.get_deposit(0).unwrap().schema_version, DEPOSIT_SCHEMA_VERSION);
+        assert_eq!(last_deposit_event()["schema_version"], DEPOSIT_SCHEMA_VERSION);
     }
 
     #[test]
-    fn test_pause_unpause() {
-        let context = get_context(accounts(0));
-        testing_env!(context.build());
+    fn test_migrate_legacy_deposits() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut guardians = IterableSet::new(StorageKey::Guardians);
+        guardians.insert(accounts(2));
+        let mut processed_deposits = LookupSet::new(StorageKey::ProcessedDeposits);
+        processed_deposits.insert("0x01".to_string());
+        let mut deposits = IterableMap::new(StorageKey::Deposits);
+        deposits.insert(0, LegacyDeposit {
+            depositor: accounts(1),
+            commitment: "0x01".to_string(),
+            amount: U128(MIN_DEPOSIT),
+            nonce: 0,
+            timestamp: 7,
+            processed: true,
+        });
+        let legacy = LegacyCashioBridge {
+            owner_id: accounts(0),
+            hub_chain_id: "999888777".to_string(),
+            guardian_threshold: 1,
+            guardians,
+            processed_deposits,
+            processed_withdrawals: LookupSet::new(StorageKey::ProcessedWithdrawals),
+            deposits,
+            deposit_nonce: 1,
+            total_deposited: MIN_DEPOSIT,
+            total_withdrawn: 0,
+            is_paused: false,
+        };
+        env::state_write(&legacy);
+        drop(legacy);
+
+        let mut contract = CashioBridge::migrate();
+        let deposit = contract.get_deposit(0).unwrap();
+        assert_eq!(deposit.schema_version, LEGACY_DEPOSIT_SCHEMA_VERSION);
+        assert_eq!(deposit.pool_id, DEFAULT_POOL);
+        assert_eq!(deposit.amount, U128(MIN_DEPOSIT));
+        assert_eq!(deposit.timestamp, 7);
+        assert!(deposit.processed);
+        assert!(contract.is_commitment_used(accounts(1), "0x01".to_string()));
+        assert!(contract.get_guardians().contains(&accounts(2)));
+        let (_, total_deposited, _, _, _) = contract.get_stats();
+        assert_eq!(total_deposited, U128(MIN_DEPOSIT));
+
+        // New deposits continue the nonce sequence in the current layout
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT);
+        let deposit = contract.get_deposit(1).unwrap();
+        assert_eq!(deposit.schema_version, DEPOSIT_SCHEMA_VERSION);
+        assert_eq!(contract.get_deposit(0).unwrap().schema_version, LEGACY_DEPOSIT_SCHEMA_VERSION);
+    }
 
+    #[test]
+    fn test_emergency_snapshot_pauses_and_matches_state() {
+        testing_env!(get_context(accounts(0)).build());
         let mut contract = CashioBridge::new(
             accounts(0),
             "999888777".to_string(),
             1,
+            None,
         );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT);
 
-        assert!(!contract.is_paused);
-        
-        contract.pause();
-        let (_, _, _, _, is_paused) = contract.get_stats();
-        assert!(is_paused);
-        
-        contract.unpause();
-        let (_, _, _, _, is_paused) = contract.get_stats();
-        assert!(!is_paused);
+        testing_env!(get_context(accounts(0)).build());
+        let snapshot = contract.emergency_snapshot(None);
+        let (balance, total_deposited, total_withdrawn, deposit_nonce, paused) = contract.get_stats();
+        assert!(paused);
+        assert_eq!(snapshot.total_deposited, total_deposited);
+        assert_eq!(snapshot.total_withdrawn, total_withdrawn);
+        assert_eq!(snapshot.contract_balance, balance);
+        assert_eq!(snapshot.deposit_nonce, deposit_nonce);
+        assert_eq!(snapshot.guardian_count, 1);
+
+        let log = get_logs()
+            .into_iter()
+            .find(|log| log.starts_with("EVENT_JSON:") && log.contains("contract_balance"))
+            .unwrap();
+        let event: near_sdk::serde_json::Value =
+            near_sdk::serde_json::from_str(&log["EVENT_JSON:".len()..]).unwrap();
+        assert_eq!(event["deposit_nonce"], 2);
+        assert_eq!(event["total_deposited"], (MIN_DEPOSIT * 2).to_string());
     }
 
     #[test]
-    #[should_panic(expected = "Deposit amount too small")]
-    fn test_deposit_too_small() {
-        let context = get_context(accounts(1));
-        testing_env!(context
-            .attached_deposit(NearToken::from_yoctonear(1000)) // Too small
-            .build());
-
+    fn test_emergency_snapshot_by_guardian_quorum() {
+        testing_env!(get_context(accounts(0)).build());
         let mut contract = CashioBridge::new(
             accounts(0),
             "999888777".to_string(),
             1,
+            None,
         );
+        add_signing_guardian(&mut contract, accounts(2), &signing_key(2));
 
-        let commitment = "0x0102030405060708".to_string();
-        contract.deposit(commitment);
+        testing_env!(get_context(accounts(3)).build());
+        let message = contract.incident_snapshot_message();
+        let signature = guardian_signature(accounts(2), &signing_key(2), &message);
+        contract.emergency_snapshot(Some(vec![signature]));
+        assert!(contract.get_stats().4);
     }
 
     #[test]
-    #[should_panic(expected = "Only owner can call this method")]
-    fn test_only_owner() {
-        let context = get_context(accounts(1)); // Not owner
-        testing_env!(context.build());
-
+    #[should_panic(expected = "Only owner")]
+    fn test_emergency_snapshot_unauthorized() {
+        testing_env!(get_context(accounts(0)).build());
         let mut contract = CashioBridge::new(
-            accounts(0), // Owner is accounts(0)
+            accounts(0),
             "999888777".to_string(),
             1,
+            None,
         );
+        testing_env!(get_context(accounts(3)).build());
+        contract.emergency_snapshot(None);
+    }
 
-        contract.pause(); // Should fail
+    #[test]
+    fn test_relayer_submits_guardian_signatures() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        // The caller is neither the owner nor a guardian
+        testing_env!(get_context(accounts(4)).build());
+        assert!(!contract.is_guardian(accounts(4)));
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(1), MIN_DEPOSIT, None);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        assert_eq!(last_transfer(), (accounts(1), NearToken::from_yoctonear(MIN_DEPOSIT)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_relayer_needs_valid_guardian_signatures() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(2)).build());
+        testing_env!(get_context(accounts(4)).build());
+        let message = contract.withdrawal_message("0xaa", &accounts(1), MIN_DEPOSIT);
+        // Claims to be the guardian but is signed with the relayer's own key
+        let signature = guardian_signature(accounts(2), &signing_key(4), &message);
+        let _ = contract.process_withdrawal(
+            "0xaa".to_string(),
+            accounts(1),
+            U128(MIN_DEPOSIT),
+            vec![signature],
+            WithdrawalRequest::default(),
+        );
+    }
+
+    fn nonced_guardian_signature(guardian: AccountId, key: &SigningKey, message: &[u8], nonce: u64) -> GuardianSignature {
+        let signed_at = env::block_timestamp();
+        GuardianSignature {
+            guardian_id: guardian,
+            signature: sign_hex(key, &signed_message(&nonced_message(message, nonce), signed_at)),
+            signed_at,
+            nonce: Some(nonce),
+        }
+    }
+
+    fn nonced_withdrawal(contract: &mut CashioBridge, withdrawal_hash: &str, nonce: u64) {
+        let message = contract.withdrawal_message(withdrawal_hash, &accounts(1), MIN_DEPOSIT);
+        let signature = nonced_guardian_signature(accounts(2), &signing_key(2), &message, nonce);
+        let _ = contract.process_withdrawal(
+            withdrawal_hash.to_string(),
+            accounts(1),
+            U128(MIN_DEPOSIT),
+            vec![signature],
+            WithdrawalRequest::default(),
+        );
+    }
+
+    #[test]
+    fn test_guardian_nonce_advances_on_use() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_require_guardian_nonces(true);
+        assert!(contract.get_require_guardian_nonces());
+        testing_env!(get_context(accounts(2)).build());
+        assert_eq!(contract.get_guardian_nonce(accounts(2)), 0);
+        nonced_withdrawal(&mut contract, "0xaa", 0);
+        assert!(contract.is_withdrawal_processed("0xaa".to_string()));
+        assert_eq!(contract.get_guardian_nonce(accounts(2)), 1);
+        nonced_withdrawal(&mut contract, "0xbb", 1);
+        assert_eq!(contract.get_guardian_nonce(accounts(2)), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_stale_guardian_nonce_rejected() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_require_guardian_nonces(true);
+        testing_env!(get_context(accounts(2)).build());
+        nonced_withdrawal(&mut contract, "0xaa", 0);
+        nonced_withdrawal(&mut contract, "0xbb", 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient guardian signatures")]
+    fn test_guardian_nonce_required_when_enabled() {
+        let mut contract = bridge_with_guardian(1);
+        seed_deposit(&mut contract, "0x01", MIN_DEPOSIT);
+        seed_deposit(&mut contract, "0x02", MIN_DEPOSIT);
+        testing_env!(get_context(accounts(0)).build());
+        contract.set_require_guardian_nonces(true);
+        testing_env!(get_context(accounts(2)).build());
+        let _ = signed_withdrawal(&mut contract, "0xaa", accounts(1), MIN_DEPOSIT, None);
     }
 }
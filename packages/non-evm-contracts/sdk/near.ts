@@ -39,6 +39,28 @@ export interface WithdrawalEvent {
   timestamp: number;
 }
 
+// Guardian signature over a withdrawal, as the contract reads it
+export interface GuardianSignature {
+  guardian_id: string;
+  signature: string;
+  signed_at: number;
+  nonce?: number;
+}
+
+// Where a withdrawal settles: paid out on NEAR, or forwarded to another chain
+export type ChainDestination =
+  | "Near"
+  | { Chain: { chain_id: string; recipient: string } };
+
+// How a withdrawal settles beyond its hash, recipient and amount; every
+// field is covered by the guardian signatures
+export interface WithdrawalRequest {
+  nullifier?: { commitment: string; nullifier: string };
+  recipient_msg?: string;
+  destination: ChainDestination;
+  guardian_set_hash?: string;
+}
+
 export class NearBridgeClient {
   private connection: any;
   private contract: any;
@@ -88,7 +110,12 @@ export class NearBridgeClient {
         "get_stats",
         "get_deposit",
         "is_commitment_used",
+        "deposit_cost_estimate",
+        "get_current_deposit_fee_bps",
+        "get_merkle_path",
+        "get_pool_stats",
         "is_withdrawal_processed",
+        "are_withdrawals_processed",
         "get_guardians",
         "guardian_count",
         "is_guardian",
@@ -103,6 +130,7 @@ export class NearBridgeClient {
         "remove_guardian",
         "update_threshold",
         "pause",
+        "pause_for",
         "unpause",
         "transfer_ownership",
       ],
@@ -131,11 +159,27 @@ export class NearBridgeClient {
   }
 
   /**
-   * Deposit NEAR with commitment
+   * Get the sibling hashes from a deposit leaf up to the current root
+   */
+  async getMerklePath(leafIndex: number): Promise<string[]> {
+    return await this.contract.get_merkle_path({ leaf_index: leafIndex });
+  }
+
+  /**
+   * Get the NEAR volume (yoctoNEAR) deposited into a pool and its deposit count
+   */
+  async getPoolStats(poolId: number): Promise<{ volume: string; count: number }> {
+    const [volume, count] = await this.contract.get_pool_stats({ pool_id: poolId });
+    return { volume, count };
+  }
+
+  /**
+   * Deposit NEAR with commitment into an anonymity pool
    */
   async deposit(
     amountNear: string,
-    commitment: string
+    commitment: string,
+    poolId = 0
   ): Promise<FinalExecutionOutcome> {
     const amountYocto = utils.format.parseNearAmount(amountNear);
     if (!amountYocto) {
@@ -143,25 +187,44 @@ export class NearBridgeClient {
     }
 
     return await this.contract.deposit(
-      { commitment },
+      { commitment, pool_id: poolId },
       "300000000000000", // 300 TGas
       amountYocto
     );
   }
 
   /**
-   * Process withdrawal (guardian only)
+   * Estimate the storage cost (yoctoNEAR) and gas (TGas) to attach to a deposit
+   */
+  async getDepositCostEstimate(): Promise<{ storageYocto: string; gasTgas: number }> {
+    const [storageYocto, gasTgas] = await this.contract.deposit_cost_estimate();
+    return { storageYocto, gasTgas };
+  }
+
+  /**
+   * Get the fee (basis points) the next deposit pays at current utilization
+   */
+  async getCurrentDepositFeeBps(): Promise<number> {
+    return await this.contract.get_current_deposit_fee_bps();
+  }
+
+  /**
+   * Process withdrawal (any caller holding threshold guardian signatures)
    */
   async processWithdrawal(
     withdrawalHash: string,
     recipient: string,
-    amount: string
+    amount: string,
+    signatures: GuardianSignature[],
+    request: WithdrawalRequest = { destination: "Near" }
   ): Promise<FinalExecutionOutcome> {
     return await this.contract.process_withdrawal(
       {
         withdrawal_hash: withdrawalHash,
         recipient,
         amount,
+        signatures,
+        request,
       },
       "300000000000000" // 300 TGas
     );
@@ -188,10 +251,10 @@ export class NearBridgeClient {
   }
 
   /**
-   * Check if commitment is used
+   * Check if a depositor has used a commitment
    */
-  async isCommitmentUsed(commitment: string): Promise<boolean> {
-    return await this.contract.is_commitment_used({ commitment });
+  async isCommitmentUsed(depositor: string, commitment: string): Promise<boolean> {
+    return await this.contract.is_commitment_used({ depositor, commitment });
   }
 
   /**
@@ -203,6 +266,13 @@ export class NearBridgeClient {
     });
   }
 
+  /**
+   * Check many withdrawal hashes at once, in input order
+   */
+  async areWithdrawalsProcessed(hashes: string[]): Promise<boolean[]> {
+    return await this.contract.are_withdrawals_processed({ hashes });
+  }
+
   /**
    * Get guardian list
    */
@@ -239,6 +309,13 @@ export class NearBridgeClient {
     return await this.contract.pause({}, "50000000000000");
   }
 
+  /**
+   * Pause bridge for a duration in nanoseconds, resuming on its own (owner only)
+   */
+  async pauseFor(durationNs: number): Promise<FinalExecutionOutcome> {
+    return await this.contract.pause_for({ duration_ns: durationNs }, "50000000000000");
+  }
+
   /**
    * Unpause bridge (owner only)
    */
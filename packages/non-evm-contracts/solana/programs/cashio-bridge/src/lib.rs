@@ -1,5 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use solana_instructions_sysvar as ix_sysvar;
+use solana_program::compute_units::sol_remaining_compute_units;
+use solana_sdk_ids::{ed25519_program, sysvar::instructions::ID as INSTRUCTIONS_SYSVAR_ID};
+use solana_sha256_hasher::hashv;
 
 declare_id!("FeRHaZXb3tbmjWWSwZXQX1HH7DSvAM7nR3mdSxN6VjpJ");
 
@@ -22,16 +27,43 @@ pub mod cashio_bridge {
         ctx: Context<Initialize>,
         hub_chain_id: u64,
         guardian_threshold: u8,
+        emergency_recipient: Pubkey,
+        domain_tag: [u8; 16],
     ) -> Result<()> {
         let bridge = &mut ctx.accounts.bridge_state;
         bridge.authority = ctx.accounts.authority.key();
         bridge.hub_chain_id = hub_chain_id;
+        bridge.domain_tag = domain_tag;
         bridge.guardian_threshold = guardian_threshold;
         bridge.deposit_nonce = 0;
         bridge.total_deposited = 0;
         bridge.total_withdrawn = 0;
         bridge.is_paused = false;
         bridge.bump = ctx.bumps.bridge_state;
+        bridge.withdrawal_fee_bps = 0;
+        bridge.fee_collector = ctx.accounts.authority.key();
+        bridge.min_confirmations = 0;
+        bridge.min_withdrawal = 0;
+        bridge.emergency_recipient = emergency_recipient;
+        bridge.withdrawal_delay = 0;
+        bridge.pending_withdrawal_count = 0;
+        bridge.max_pending_withdrawals = DEFAULT_MAX_PENDING_WITHDRAWALS;
+        bridge.withdrawal_retention = DEFAULT_WITHDRAWAL_RETENTION;
+        bridge.allowed_token_program = token::ID;
+
+        // Fund the vault to rent-exemption so it can never be reclaimed
+        let rent_exempt = Rent::get()?.minimum_balance(0);
+        let shortfall = rent_exempt.saturating_sub(ctx.accounts.vault.lamports());
+        if shortfall > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, shortfall)?;
+        }
         
         msg!("Cash.io Bridge initialized");
         msg!("Hub Chain ID: {}", hub_chain_id);
@@ -45,13 +77,31 @@ pub mod cashio_bridge {
         ctx: Context<ManageGuardian>,
         guardian_pubkey: Pubkey,
     ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
         let guardian_account = &mut ctx.accounts.guardian;
+        require!(!guardian_account.is_active, BridgeError::GuardianAlreadyActive);
+        let now = Clock::get()?.unix_timestamp;
+        check_guardian_change_interval(bridge, now)?;
+        bridge.last_guardian_change = now;
+        
+        // Signer bitmap slots are permanent, so re-added guardians keep theirs
+        if guardian_account.added_at == 0 {
+            require!(
+                (bridge.next_guardian_index as usize) < MAX_GUARDIANS,
+                BridgeError::TooManyGuardians
+            );
+            guardian_account.index = bridge.next_guardian_index;
+            bridge.next_guardian_index += 1;
+        }
+        
         guardian_account.pubkey = guardian_pubkey;
         guardian_account.is_active = true;
         guardian_account.added_at = Clock::get()?.unix_timestamp;
+        guardian_account.removed_at = 0;
+        guardian_account.weight = guardian_account.weight.max(1);
         
-        let bridge = &mut ctx.accounts.bridge_state;
         bridge.guardian_count += 1;
+        bridge.total_weight += guardian_account.weight as u32;
         
         emit!(GuardianAdded {
             guardian: guardian_pubkey,
@@ -62,18 +112,48 @@ pub mod cashio_bridge {
         Ok(())
     }
 
+    /// Label a guardian for operator tooling
+    pub fn update_guardian_metadata(
+        ctx: Context<UpdateGuardianMetadata>,
+        _guardian_pubkey: Pubkey,
+        name: [u8; 32],
+        endpoint_hash: [u8; 32],
+    ) -> Result<()> {
+        let guardian = &mut ctx.accounts.guardian;
+        guardian.name = name;
+        guardian.endpoint_hash = endpoint_hash;
+
+        emit!(GuardianMetadataUpdated {
+            guardian: guardian.pubkey,
+            name,
+            endpoint_hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Remove a guardian from the verification set
-    pub fn remove_guardian(ctx: Context<ManageGuardian>) -> Result<()> {
+    pub fn remove_guardian(
+        ctx: Context<ManageGuardian>,
+        _guardian_pubkey: Pubkey,
+    ) -> Result<()> {
         let guardian = &mut ctx.accounts.guardian;
         require!(guardian.is_active, BridgeError::GuardianNotActive);
+        let now = Clock::get()?.unix_timestamp;
         
         guardian.is_active = false;
+        guardian.removed_at = now;
         
         let bridge = &mut ctx.accounts.bridge_state;
+        check_guardian_change_interval(bridge, now)?;
+        bridge.last_guardian_change = now;
         bridge.guardian_count -= 1;
+        bridge.total_weight -= guardian_weight(guardian);
         
         require!(
-            bridge.guardian_count >= bridge.guardian_threshold as u64,
+            bridge.guardian_count >= bridge.guardian_threshold as u64
+                && bridge.total_weight >= bridge.weight_threshold,
             BridgeError::InsufficientGuardians
         );
         
@@ -86,6 +166,203 @@ pub mod cashio_bridge {
         Ok(())
     }
 
+    /// Replace the whole guardian set in one transaction, e.g. after a
+    /// compromise. `remaining_accounts` holds every currently active guardian
+    /// account, followed by the guardian PDA of each entry in `new_guardians`;
+    /// PDAs that don't exist yet are created. Bumps `guardian_set_epoch`, so
+    /// signature collections started under the old set can't be completed.
+    /// The new set starts with unit weights and count-based thresholds, and
+    /// the rotation is rate-limited like any other guardian change.
+    pub fn rotate_guardian_set<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RotateGuardianSet<'info>>,
+        new_guardians: Vec<Pubkey>,
+        new_threshold: u8,
+    ) -> Result<()> {
+        require!(
+            new_threshold >= 1 && new_threshold as usize <= new_guardians.len(),
+            BridgeError::InvalidThreshold
+        );
+        let current_count = ctx.accounts.bridge_state.guardian_count as usize;
+        require!(
+            ctx.remaining_accounts.len() == current_count + new_guardians.len(),
+            BridgeError::GuardianAccountsMismatch
+        );
+        let now = Clock::get()?.unix_timestamp;
+        check_guardian_change_interval(&ctx.accounts.bridge_state, now)?;
+        let (current, incoming) = ctx.remaining_accounts.split_at(current_count);
+
+        // Each active guardian must be passed exactly once; a repeat is
+        // already inactive by the time it is seen again
+        for info in current {
+            let mut guardian = Account::<Guardian>::try_from(info)?;
+            require!(guardian.is_active, BridgeError::GuardianNotActive);
+            guardian.is_active = false;
+            guardian.removed_at = now;
+            guardian.exit(&crate::ID)?;
+        }
+
+        let mut next_guardian_index = ctx.accounts.bridge_state.next_guardian_index;
+        for (guardian_pubkey, info) in new_guardians.iter().zip(incoming) {
+            let (address, bump) =
+                Pubkey::find_program_address(&[b"guardian", guardian_pubkey.as_ref()], &crate::ID);
+            require_keys_eq!(info.key(), address, BridgeError::GuardianAccountsMismatch);
+            if info.owner == &System::id() {
+                let seeds: &[&[u8]] = &[b"guardian", guardian_pubkey.as_ref(), &[bump]];
+                let signer_seeds = &[seeds];
+                let cpi_context = CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: info.clone(),
+                    },
+                    signer_seeds,
+                );
+                anchor_lang::system_program::create_account(
+                    cpi_context,
+                    Rent::get()?.minimum_balance(GUARDIAN_SPACE),
+                    GUARDIAN_SPACE as u64,
+                    &crate::ID,
+                )?;
+                let blank = Guardian {
+                    pubkey: *guardian_pubkey,
+                    is_active: false,
+                    added_at: 0,
+                    index: 0,
+                    removed_at: 0,
+                    name: [0u8; 32],
+                    endpoint_hash: [0u8; 32],
+                    weight: 0,
+                };
+                blank.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+            }
+
+            // Also rejects a key listed twice in `new_guardians`
+            let mut guardian = Account::<Guardian>::try_from(info)?;
+            require!(!guardian.is_active, BridgeError::GuardianAlreadyActive);
+            if guardian.added_at == 0 {
+                require!(
+                    (next_guardian_index as usize) < MAX_GUARDIANS,
+                    BridgeError::TooManyGuardians
+                );
+                guardian.index = next_guardian_index;
+                next_guardian_index += 1;
+            }
+            guardian.pubkey = *guardian_pubkey;
+            guardian.is_active = true;
+            guardian.added_at = now;
+            guardian.removed_at = 0;
+            guardian.weight = 1;
+            guardian.exit(&crate::ID)?;
+        }
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.last_guardian_change = now;
+        bridge.next_guardian_index = next_guardian_index;
+        bridge.guardian_count = new_guardians.len() as u64;
+        bridge.guardian_threshold = new_threshold;
+        bridge.total_weight = new_guardians.len() as u32;
+        bridge.weight_threshold = 0;
+        bridge.guardian_set_epoch += 1;
+
+        emit!(GuardianSetRotated {
+            guardians: new_guardians,
+            threshold: new_threshold,
+            epoch: bridge.guardian_set_epoch,
+            rotated_by: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Emit the active guardian set as one event, for indexers that would
+    /// otherwise scan guardian PDAs. `remaining_accounts` must hold every
+    /// active guardian account exactly once.
+    pub fn dump_guardians<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DumpGuardians<'info>>,
+    ) -> Result<()> {
+        let bridge = &ctx.accounts.bridge_state;
+        require!(
+            ctx.remaining_accounts.len() as u64 == bridge.guardian_count,
+            BridgeError::GuardianAccountsMismatch
+        );
+
+        let mut seen = 0u64;
+        let mut guardians = Vec::with_capacity(ctx.remaining_accounts.len());
+        for info in ctx.remaining_accounts {
+            let guardian = Account::<Guardian>::try_from(info)?;
+            require!(guardian.is_active, BridgeError::GuardianNotActive);
+            let bit = 1u64 << guardian.index;
+            require!(seen & bit == 0, BridgeError::GuardianAccountsMismatch);
+            seen |= bit;
+            guardians.push(guardian.pubkey);
+        }
+
+        emit!(GuardianSetDump {
+            guardians,
+            threshold: bridge.guardian_threshold,
+            epoch: bridge.guardian_set_epoch,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Verify guardian signatures over an arbitrary `message` the way
+    /// withdrawals do, logging the compute units spent. Lets operators size
+    /// the guardian set against the transaction compute limit.
+    pub fn benchmark_verification<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BenchmarkVerification<'info>>,
+        message: [u8; 32],
+        guardian_signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        require!(
+            ctx.remaining_accounts.len() == guardian_signatures.len(),
+            BridgeError::GuardianAccountsMismatch
+        );
+
+        let before = sol_remaining_compute_units();
+        let (signers, _) = count_guardian_signers(
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            &message,
+            &guardian_signatures,
+        )?;
+        let consumed = before.saturating_sub(sol_remaining_compute_units());
+
+        msg!(
+            "Verified {} signatures ({} active guardians) in {} compute units",
+            guardian_signatures.len(),
+            signers,
+            consumed
+        );
+        Ok(())
+    }
+
+    /// Top up the SOL vault without creating a deposit
+    /// Callable by anyone, e.g. to keep the vault rent-exempt
+    pub fn fund_vault(ctx: Context<FundVault>, amount: u64) -> Result<()> {
+        require!(amount > 0, BridgeError::AmountTooSmall);
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, amount)?;
+
+        emit!(VaultFunded {
+            funder: ctx.accounts.funder.key(),
+            amount,
+            vault_balance: ctx.accounts.vault.lamports(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     /// Deposit SOL with a privacy commitment
     /// This creates a shielded note on the hub chain
     pub fn deposit_sol(
@@ -117,6 +394,7 @@ pub mod cashio_bridge {
         deposit.timestamp = Clock::get()?.unix_timestamp;
         deposit.processed = false;
         deposit.bump = ctx.bumps.deposit;
+        deposit.pool_id = SOL_POOL_ID;
 
         // Update bridge state
         let bridge = &mut ctx.accounts.bridge_state;
@@ -128,6 +406,7 @@ pub mod cashio_bridge {
             commitment,
             amount,
             nonce: deposit.nonce,
+            pool_id: SOL_POOL_ID,
             timestamp: deposit.timestamp,
         });
 
@@ -136,6 +415,171 @@ pub mod cashio_bridge {
         Ok(())
     }
 
+    /// Allocate the next deposit nonce to the caller for
+    /// NONCE_RESERVATION_WINDOW seconds, so the deposit PDA can be derived
+    /// without racing other depositors
+    pub fn reserve_nonce(ctx: Context<ReserveNonce>) -> Result<()> {
+        require!(!ctx.accounts.bridge_state.is_paused, BridgeError::BridgePaused);
+        let reservation = &mut ctx.accounts.reservation;
+        reservation.depositor = ctx.accounts.depositor.key();
+        reservation.nonce = ctx.accounts.bridge_state.deposit_nonce;
+        reservation.expires_at = Clock::get()?
+            .unix_timestamp
+            .saturating_add(NONCE_RESERVATION_WINDOW);
+        reservation.bump = ctx.bumps.reservation;
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.deposit_nonce += 1;
+
+        emit!(NonceReserved {
+            depositor: reservation.depositor,
+            nonce: reservation.nonce,
+            expires_at: reservation.expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Deposit SOL under a nonce reserved with `reserve_nonce`
+    pub fn deposit_sol_reserved(
+        ctx: Context<DepositSolReserved>,
+        amount: u64,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        let bridge = &ctx.accounts.bridge_state;
+        require!(!bridge.is_paused, BridgeError::BridgePaused);
+        require!(amount >= MIN_DEPOSIT, BridgeError::AmountTooSmall);
+        require!(amount <= MAX_DEPOSIT, BridgeError::AmountTooLarge);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= ctx.accounts.reservation.expires_at, BridgeError::ReservationExpired);
+
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.depositor.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, amount)?;
+
+        let deposit = &mut ctx.accounts.deposit;
+        deposit.depositor = ctx.accounts.depositor.key();
+        deposit.commitment = commitment;
+        deposit.amount = amount;
+        deposit.nonce = ctx.accounts.reservation.nonce;
+        deposit.timestamp = now;
+        deposit.processed = false;
+        deposit.bump = ctx.bumps.deposit;
+        deposit.pool_id = SOL_POOL_ID;
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.total_deposited += amount;
+
+        emit!(DepositEvent {
+            depositor: ctx.accounts.depositor.key(),
+            commitment,
+            amount,
+            nonce: deposit.nonce,
+            pool_id: SOL_POOL_ID,
+            timestamp: now,
+        });
+
+        msg!("Deposit {} lamports with reserved nonce {}", amount, deposit.nonce);
+
+        Ok(())
+    }
+
+    /// Drop an unused nonce reservation; its nonce is left unused
+    pub fn release_nonce(_ctx: Context<ReleaseNonce>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Close a relayed SOL deposit record, once guardians acknowledged it, and return its rent to the depositor
+    pub fn close_deposit(ctx: Context<CloseDeposit>, nonce: u64) -> Result<()> {
+        let deposit = &ctx.accounts.deposit;
+        require!(deposit.processed, BridgeError::DepositNotAcknowledged);
+        let rent_returned = deposit.to_account_info().lamports();
+
+        emit!(DepositClosed {
+            nonce,
+            depositor: deposit.depositor,
+            rent_returned,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Closed deposit #{}", nonce);
+
+        Ok(())
+    }
+
+    /// Mark a SOL deposit as relayed once threshold guardians sign that the
+    /// hub received its nonce and commitment
+    pub fn ack_deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AckDeposit<'info>>,
+        guardian_signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.deposit.processed, BridgeError::DepositAlreadyAcknowledged);
+        let deposit = &ctx.accounts.deposit;
+        let signers = verify_deposit_ack(
+            &ctx.accounts.bridge_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            deposit.nonce,
+            &deposit.commitment,
+            &guardian_signatures,
+        )?;
+
+        let deposit = &mut ctx.accounts.deposit;
+        deposit.processed = true;
+
+        emit!(DepositAcknowledged {
+            nonce: deposit.nonce,
+            commitment: deposit.commitment,
+            signers,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Acknowledged deposit #{}", deposit.nonce);
+
+        Ok(())
+    }
+
+    /// Mark a token deposit as relayed, as `ack_deposit` does for SOL
+    /// Deposit nonces are shared across SOL and tokens, so guardians sign
+    /// the same message for either.
+    pub fn ack_token_deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AckTokenDeposit<'info>>,
+        guardian_signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.token_deposit.processed,
+            BridgeError::DepositAlreadyAcknowledged
+        );
+        let deposit = &ctx.accounts.token_deposit;
+        let signers = verify_deposit_ack(
+            &ctx.accounts.bridge_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            deposit.nonce,
+            &deposit.commitment,
+            &guardian_signatures,
+        )?;
+
+        let deposit = &mut ctx.accounts.token_deposit;
+        deposit.processed = true;
+
+        emit!(DepositAcknowledged {
+            nonce: deposit.nonce,
+            commitment: deposit.commitment,
+            signers,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Acknowledged deposit #{}", deposit.nonce);
+
+        Ok(())
+    }
+
     /// Deposit SPL tokens with a privacy commitment
     pub fn deposit_token(
         ctx: Context<DepositToken>,
@@ -144,28 +588,57 @@ pub mod cashio_bridge {
     ) -> Result<()> {
         let bridge = &ctx.accounts.bridge_state;
         require!(!bridge.is_paused, BridgeError::BridgePaused);
-        require!(amount >= MIN_DEPOSIT, BridgeError::AmountTooSmall);
+        require_keys_eq!(
+            ctx.accounts.token_program.key(),
+            bridge.allowed_token_program,
+            BridgeError::TokenProgramNotAllowed
+        );
+
+        // First deposit of a mint creates its (unpaused) config
+        let token_config = &mut ctx.accounts.token_config;
+        if token_config.mint == Pubkey::default() {
+            token_config.mint = ctx.accounts.mint.key();
+            token_config.pool_id = DEFAULT_TOKEN_POOL_ID;
+            token_config.bump = ctx.bumps.token_config;
+        }
+        require!(!token_config.is_paused, BridgeError::TokenPaused);
+        require!(amount >= token_config.min_deposit(), BridgeError::AmountTooSmall);
+        require!(amount <= token_config.max_deposit(), BridgeError::AmountTooLarge);
+        let pool_id = token_config.pool_id;
 
-        // Transfer tokens to bridge vault
-        let cpi_accounts = Transfer {
+        // Transfer tokens to bridge vault, recording what actually arrived
+        // so fee-on-transfer tokens can't inflate the bridge's liabilities
+        let vault_before = ctx.accounts.vault_token_account.amount;
+        let cpi_accounts = TransferChecked {
             from: ctx.accounts.depositor_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
             to: ctx.accounts.vault_token_account.to_account_info(),
             authority: ctx.accounts.depositor.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        ctx.accounts.vault_token_account.reload()?;
+        let received = ctx.accounts.vault_token_account.amount.saturating_sub(vault_before);
+        require!(received > 0, BridgeError::NothingReceived);
+        let normalized_amount = normalize_amount(received, ctx.accounts.mint.decimals)?;
+        // An empty vault backs no deposits from before tracking
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.liability_tracked |= vault_before == 0;
+        token_config.liability += received;
 
         // Create deposit record
         let deposit = &mut ctx.accounts.token_deposit;
         deposit.depositor = ctx.accounts.depositor.key();
         deposit.mint = ctx.accounts.mint.key();
         deposit.commitment = commitment;
-        deposit.amount = amount;
+        deposit.amount = received;
+        deposit.normalized_amount = normalized_amount;
         deposit.nonce = ctx.accounts.bridge_state.deposit_nonce;
         deposit.timestamp = Clock::get()?.unix_timestamp;
         deposit.processed = false;
         deposit.bump = ctx.bumps.token_deposit;
+        deposit.pool_id = pool_id;
 
         // Update bridge state
         let bridge = &mut ctx.accounts.bridge_state;
@@ -175,8 +648,10 @@ pub mod cashio_bridge {
             depositor: ctx.accounts.depositor.key(),
             mint: ctx.accounts.mint.key(),
             commitment,
-            amount,
+            amount: received,
+            normalized_amount,
             nonce: deposit.nonce,
+            pool_id,
             timestamp: deposit.timestamp,
         });
 
@@ -184,27 +659,67 @@ pub mod cashio_bridge {
     }
 
     /// Process a verified withdrawal from the hub chain
-    /// Requires guardian signatures
-    pub fn process_withdrawal(
-        ctx: Context<ProcessWithdrawal>,
+    /// Requires guardian signatures, each verified by an Ed25519 program
+    /// instruction earlier in the transaction. The signing guardians' PDAs
+    /// are passed as remaining accounts in the same order as the signatures.
+    /// Guardians also sign the hub block height and hash of the source event
+    /// and the hub height they observed, which must be `min_confirmations`
+    /// apart, and a `valid_until` timestamp after which the signatures expire.
+    pub fn process_withdrawal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessWithdrawal<'info>>,
         withdrawal_hash: [u8; 32],
         amount: u64,
+        source_block: SourceBlock,
+        current_height: u64,
+        valid_until: i64,
         guardian_signatures: Vec<[u8; 64]>,
     ) -> Result<()> {
+        let SourceBlock {
+            height: source_height,
+            hash: source_block_hash,
+        } = source_block;
         let bridge = &ctx.accounts.bridge_state;
         require!(!bridge.is_paused, BridgeError::BridgePaused);
         require!(
-            guardian_signatures.len() >= bridge.guardian_threshold as usize,
-            BridgeError::InsufficientSignatures
+            Clock::get()?.unix_timestamp <= valid_until,
+            BridgeError::SignatureExpired
+        );
+        require!(amount >= bridge.min_withdrawal, BridgeError::WithdrawalTooSmall);
+        check_confirmations(bridge, source_height, current_height)?;
+        require!(
+            ctx.remaining_accounts.len() == guardian_signatures.len(),
+            BridgeError::GuardianAccountsMismatch
         );
 
+        check_hub_block(
+            bridge,
+            ctx.accounts.hub_block.as_deref(),
+            source_height,
+            &source_block_hash,
+        )?;
+
         // Verify the withdrawal hasn't been processed
         let withdrawal = &ctx.accounts.withdrawal;
         require!(!withdrawal.processed, BridgeError::WithdrawalAlreadyProcessed);
+        check_not_compacted(bridge, &withdrawal_hash)?;
 
-        // TODO: Verify Ed25519 signatures from guardians
-        // In production, use ed25519 signature verification
-        // For each guardian signature, verify against the withdrawal message
+        let message = confirmed_withdrawal_message(
+            &withdrawal_message(bridge, &withdrawal_hash, &ctx.accounts.recipient.key(), amount),
+            source_height,
+            &source_block_hash,
+            current_height,
+            valid_until,
+        );
+        let (signers, weight) = count_guardian_signers(
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            &message,
+            &guardian_signatures,
+        )?;
+        require!(
+            quorum_reached(bridge, signers, weight),
+            BridgeError::InsufficientSignatures
+        );
         
         // Mark as processed
         let withdrawal = &mut ctx.accounts.withdrawal;
@@ -215,19 +730,27 @@ pub mod cashio_bridge {
         withdrawal.timestamp = Clock::get()?.unix_timestamp;
         withdrawal.bump = ctx.bumps.withdrawal;
 
-        // Transfer SOL from vault to recipient
-        let transfer_amount = amount;
-        **ctx.accounts.vault.try_borrow_mut_lamports()? -= transfer_amount;
-        **ctx.accounts.recipient.try_borrow_mut_lamports()? += transfer_amount;
+        // Transfer SOL from vault to recipient, less the protocol fee
+        let fee = pay_out_withdrawal(
+            &ctx.accounts.bridge_state,
+            &ctx.accounts.vault,
+            &ctx.accounts.recipient,
+            &ctx.accounts.fee_collector,
+            &ctx.accounts.system_program,
+            ctx.bumps.vault,
+            amount,
+        )?;
 
         // Update bridge state
         let bridge = &mut ctx.accounts.bridge_state;
         bridge.total_withdrawn += amount;
+        consume_bloom_override(bridge, &withdrawal_hash);
 
         emit!(WithdrawalEvent {
             withdrawal_hash,
             recipient: ctx.accounts.recipient.key(),
             amount,
+            fee,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
@@ -236,50 +759,1342 @@ pub mod cashio_bridge {
         Ok(())
     }
 
-    /// Emergency pause the bridge
-    pub fn pause(ctx: Context<AdminAction>) -> Result<()> {
-        let bridge = &mut ctx.accounts.bridge_state;
-        bridge.is_paused = true;
-        msg!("Bridge paused");
-        Ok(())
-    }
+    /// Process a verified SPL token withdrawal from the hub chain
+    /// Guardians sign the mint and the destination token account alongside
+    /// the hash and amount, so a signed withdrawal can't be redirected to
+    /// another account. Otherwise verified as in `process_withdrawal`.
+    pub fn process_token_withdrawal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessTokenWithdrawal<'info>>,
+        withdrawal_hash: [u8; 32],
+        amount: u64,
+        source_block: SourceBlock,
+        current_height: u64,
+        valid_until: i64,
+        guardian_signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let SourceBlock {
+            height: source_height,
+            hash: source_block_hash,
+        } = source_block;
+        let bridge = &ctx.accounts.bridge_state;
+        require!(!bridge.is_paused, BridgeError::BridgePaused);
+        require!(!ctx.accounts.token_config.is_paused, BridgeError::TokenPaused);
+        require!(amount > 0, BridgeError::AmountTooSmall);
+        require!(
+            amount >= ctx.accounts.token_config.min_withdrawal,
+            BridgeError::WithdrawalTooSmall
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= valid_until,
+            BridgeError::SignatureExpired
+        );
+        require_keys_eq!(
+            ctx.accounts.token_program.key(),
+            bridge.allowed_token_program,
+            BridgeError::TokenProgramNotAllowed
+        );
+        check_confirmations(bridge, source_height, current_height)?;
+        check_hub_block(
+            bridge,
+            ctx.accounts.hub_block.as_deref(),
+            source_height,
+            &source_block_hash,
+        )?;
+        require!(
+            ctx.remaining_accounts.len() == guardian_signatures.len(),
+            BridgeError::GuardianAccountsMismatch
+        );
+        check_not_compacted(bridge, &withdrawal_hash)?;
+
+        let recipient_token_account = ctx.accounts.recipient_token_account.key();
+        let message = confirmed_withdrawal_message(
+            &token_withdrawal_message(
+                bridge,
+                &withdrawal_hash,
+                &ctx.accounts.mint.key(),
+                &recipient_token_account,
+                amount,
+            ),
+            source_height,
+            &source_block_hash,
+            current_height,
+            valid_until,
+        );
+        let (signers, weight) = count_guardian_signers(
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            &message,
+            &guardian_signatures,
+        )?;
+        require!(
+            quorum_reached(bridge, signers, weight),
+            BridgeError::InsufficientSignatures
+        );
+
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        withdrawal.withdrawal_hash = withdrawal_hash;
+        withdrawal.recipient = recipient_token_account;
+        withdrawal.amount = amount;
+        withdrawal.processed = true;
+        withdrawal.timestamp = Clock::get()?.unix_timestamp;
+        withdrawal.bump = ctx.bumps.withdrawal;
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.liability = token_config.liability.saturating_sub(amount);
+
+        // The bridge state PDA owns every vault token account
+        let seeds: &[&[u8]] = &[b"bridge_state", &[ctx.accounts.bridge_state.bump]];
+        let signer_seeds = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: ctx.accounts.bridge_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+        consume_bloom_override(&mut ctx.accounts.bridge_state, &withdrawal_hash);
+
+        emit!(TokenWithdrawalEvent {
+            withdrawal_hash,
+            mint: ctx.accounts.mint.key(),
+            recipient_token_account,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-    /// Unpause the bridge
-    pub fn unpause(ctx: Context<AdminAction>) -> Result<()> {
-        let bridge = &mut ctx.accounts.bridge_state;
-        bridge.is_paused = false;
-        msg!("Bridge unpaused");
         Ok(())
     }
 
-    /// Update guardian threshold
-    pub fn update_threshold(
-        ctx: Context<AdminAction>,
-        new_threshold: u8,
-    ) -> Result<()> {
-        let bridge = &mut ctx.accounts.bridge_state;
-        require!(
-            new_threshold as u64 <= bridge.guardian_count,
-            BridgeError::ThresholdTooHigh
+    /// Send a mint's vault balance above its deposit liability (dust, or
+    /// tokens sent to the vault directly) to the fee collector. Tokens
+    /// backing deposits are never touched, so mints whose liability isn't
+    /// fully tracked can't be swept until it is backfilled.
+    pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.token_program.key(),
+            ctx.accounts.bridge_state.allowed_token_program,
+            BridgeError::TokenProgramNotAllowed
         );
-        bridge.guardian_threshold = new_threshold;
-        
-        emit!(ThresholdUpdated {
-            old_threshold: bridge.guardian_threshold,
-            new_threshold,
+        require!(ctx.accounts.token_config.liability_tracked, BridgeError::LiabilityNotTracked);
+        let excess = ctx.accounts.vault_token_account.amount
+            .saturating_sub(ctx.accounts.token_config.liability);
+        require!(excess > 0, BridgeError::NothingToSweep);
+
+        let seeds: &[&[u8]] = &[b"bridge_state", &[ctx.accounts.bridge_state.bump]];
+        let signer_seeds = &[seeds];
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.collector_token_account.to_account_info(),
+                authority: ctx.accounts.bridge_state.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token_interface::transfer_checked(cpi_ctx, excess, ctx.accounts.mint.decimals)?;
+
+        emit!(DustSwept {
+            mint: ctx.accounts.mint.key(),
+            amount: excess,
+            collector_token_account: ctx.accounts.collector_token_account.key(),
             timestamp: Clock::get()?.unix_timestamp,
         });
-        
+
         Ok(())
     }
-}
 
-// ============ Constants ============
+    /// Record a hub chain block hash on threshold guardian signatures, so
+    /// withdrawals can prove their source event came from a committed block
+    pub fn commit_hub_block<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CommitHubBlock<'info>>,
+        block_hash: [u8; 32],
+        height: u64,
+        valid_until: i64,
+        guardian_signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let bridge = &ctx.accounts.bridge_state;
+        require!(
+            Clock::get()?.unix_timestamp <= valid_until,
+            BridgeError::SignatureExpired
+        );
+        require!(
+            ctx.remaining_accounts.len() == guardian_signatures.len(),
+            BridgeError::GuardianAccountsMismatch
+        );
 
-pub const MIN_DEPOSIT: u64 = 10_000_000;      // 0.01 SOL (10M lamports)
-pub const MAX_DEPOSIT: u64 = 100_000_000_000; // 100 SOL
+        let (signers, weight) = count_guardian_signers(
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            &hub_block_message(bridge, &block_hash, height, valid_until),
+            &guardian_signatures,
+        )?;
+        require!(
+            signers >= 1 && quorum_reached(bridge, signers, weight),
+            BridgeError::InsufficientSignatures
+        );
 
-// ============ State Accounts ============
+        let hub_block = &mut ctx.accounts.hub_block;
+        hub_block.block_hash = block_hash;
+        hub_block.height = height;
+        hub_block.committed_at = Clock::get()?.unix_timestamp;
+        hub_block.bump = ctx.bumps.hub_block;
+
+        emit!(HubBlockCommitted {
+            block_hash,
+            height,
+            signers,
+            timestamp: hub_block.committed_at,
+        });
+
+        Ok(())
+    }
+
+    /// Require withdrawals to reference a committed hub block
+    pub fn set_require_hub_block(ctx: Context<AdminAction>, required: bool) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.require_hub_block = required;
+        msg!("Hub block references {}", if required { "required" } else { "optional" });
+        Ok(())
+    }
+
+    /// Approve a merkle root covering many withdrawals with one set of
+    /// threshold guardian signatures. The signatures are verified here, once
+    /// for the whole batch; the batch account can only be created once, so
+    /// the same set can't be counted again.
+    pub fn commit_withdrawal_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CommitWithdrawalBatch<'info>>,
+        root: [u8; 32],
+        valid_until: i64,
+        guardian_signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let bridge = &ctx.accounts.bridge_state;
+        require!(!bridge.is_paused, BridgeError::BridgePaused);
+        require!(
+            Clock::get()?.unix_timestamp <= valid_until,
+            BridgeError::SignatureExpired
+        );
+        require!(
+            ctx.remaining_accounts.len() == guardian_signatures.len(),
+            BridgeError::GuardianAccountsMismatch
+        );
+
+        let (signers, weight) = count_guardian_signers(
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            &batch_message(bridge, &root, valid_until),
+            &guardian_signatures,
+        )?;
+        require!(
+            signers >= 1 && quorum_reached(bridge, signers, weight),
+            BridgeError::InsufficientSignatures
+        );
+
+        let batch = &mut ctx.accounts.withdrawal_batch;
+        batch.root = root;
+        batch.signers = signers;
+        batch.committed_at = Clock::get()?.unix_timestamp;
+        batch.bump = ctx.bumps.withdrawal_batch;
+        batch.guardian_set_epoch = bridge.guardian_set_epoch;
+        batch.valid_until = valid_until;
+
+        emit!(WithdrawalBatchCommitted {
+            root,
+            signers,
+            timestamp: batch.committed_at,
+        });
+
+        Ok(())
+    }
+
+    /// Pay out a withdrawal included in a committed batch
+    /// Callable by anyone holding a valid inclusion proof; no signatures are
+    /// checked per item. The batch only pays out before its signed expiry
+    /// and while the guardian set that approved it is current.
+    pub fn claim_from_batch(
+        ctx: Context<ClaimFromBatch>,
+        root: [u8; 32],
+        withdrawal_hash: [u8; 32],
+        amount: u64,
+        source_block: SourceBlock,
+        current_height: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let SourceBlock {
+            height: source_height,
+            hash: source_block_hash,
+        } = source_block;
+        let bridge = &ctx.accounts.bridge_state;
+        require!(!bridge.is_paused, BridgeError::BridgePaused);
+        let batch = &ctx.accounts.withdrawal_batch;
+        require!(
+            batch.guardian_set_epoch == bridge.guardian_set_epoch,
+            BridgeError::StaleGuardianSet
+        );
+        require!(
+            Clock::get()?.unix_timestamp <= batch.valid_until,
+            BridgeError::SignatureExpired
+        );
+        require!(amount >= bridge.min_withdrawal, BridgeError::WithdrawalTooSmall);
+        check_confirmations(bridge, source_height, current_height)?;
+        check_hub_block(
+            bridge,
+            ctx.accounts.hub_block.as_deref(),
+            source_height,
+            &source_block_hash,
+        )?;
+        check_not_compacted(bridge, &withdrawal_hash)?;
+
+        let recipient = ctx.accounts.recipient.key();
+        let leaf = batch_leaf(
+            &withdrawal_hash,
+            &recipient,
+            amount,
+            source_height,
+            &source_block_hash,
+            current_height,
+        );
+        let node = proof
+            .iter()
+            .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+        require!(node == root, BridgeError::InvalidBatchProof);
+
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        withdrawal.withdrawal_hash = withdrawal_hash;
+        withdrawal.recipient = recipient;
+        withdrawal.amount = amount;
+        withdrawal.processed = true;
+        withdrawal.timestamp = Clock::get()?.unix_timestamp;
+        withdrawal.bump = ctx.bumps.withdrawal;
+
+        let fee = pay_out_withdrawal(
+            &ctx.accounts.bridge_state,
+            &ctx.accounts.vault,
+            &ctx.accounts.recipient,
+            &ctx.accounts.fee_collector,
+            &ctx.accounts.system_program,
+            ctx.bumps.vault,
+            amount,
+        )?;
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.total_withdrawn += amount;
+        consume_bloom_override(bridge, &withdrawal_hash);
+
+        emit!(WithdrawalEvent {
+            withdrawal_hash,
+            recipient,
+            amount,
+            fee,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Submit a single guardian signature for a withdrawal
+    /// The signature must be verified by an Ed25519 program instruction
+    /// earlier in the same transaction. Each guardian is verified once and
+    /// recorded in the collection's signer bitmap. Guardians sign the same
+    /// height- and expiry-bound digest as in `process_withdrawal`.
+    pub fn submit_signature(
+        ctx: Context<SubmitSignature>,
+        withdrawal_hash: [u8; 32],
+        request: WithdrawalRequest,
+        signature: [u8; 64],
+    ) -> Result<()> {
+        let WithdrawalRequest {
+            recipient,
+            amount,
+            source_height,
+            source_block_hash,
+            current_height,
+            valid_until,
+        } = request;
+        let bridge = &ctx.accounts.bridge_state;
+        require!(!bridge.is_paused, BridgeError::BridgePaused);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= valid_until, BridgeError::SignatureExpired);
+
+        let collection = &mut ctx.accounts.signature_collection;
+        if collection.withdrawal_hash == [0u8; 32] {
+            collection.withdrawal_hash = withdrawal_hash;
+            collection.recipient = recipient;
+            collection.amount = amount;
+            collection.created_at = now;
+            collection.guardian_set_epoch = bridge.guardian_set_epoch;
+            collection.bump = ctx.bumps.signature_collection;
+            collection.source_height = source_height;
+            collection.source_block_hash = source_block_hash;
+            collection.current_height = current_height;
+            collection.valid_until = valid_until;
+        }
+        require!(
+            collection.guardian_set_epoch == bridge.guardian_set_epoch,
+            BridgeError::StaleGuardianSet
+        );
+
+        // Removed guardians may still sign withdrawals queued before their
+        // removal, until the grace period runs out
+        let guardian = &ctx.accounts.guardian;
+        require!(
+            guardian.is_active
+                || (collection.created_at <= guardian.removed_at
+                    && now <= guardian.removed_at.saturating_add(bridge.signature_grace_period)),
+            BridgeError::GuardianNotActive
+        );
+
+        require!(
+            collection.recipient == recipient
+                && collection.amount == amount
+                && collection.source_height == source_height
+                && collection.source_block_hash == source_block_hash
+                && collection.current_height == current_height
+                && collection.valid_until == valid_until,
+            BridgeError::WithdrawalMismatch
+        );
+        require!(!collection.finalized, BridgeError::WithdrawalAlreadyProcessed);
+
+        let bit = 1u64 << guardian.index;
+        if collection.signer_bitmap & bit != 0 {
+            msg!("Guardian signature already verified");
+            return Ok(());
+        }
+
+        let message = confirmed_withdrawal_message(
+            &withdrawal_message(bridge, &withdrawal_hash, &recipient, amount),
+            source_height,
+            &source_block_hash,
+            current_height,
+            valid_until,
+        );
+        verify_ed25519_signature(
+            &ctx.accounts.instructions.to_account_info(),
+            &guardian.pubkey,
+            &message,
+            &signature,
+        )?;
+        collection.signer_bitmap |= bit;
+        collection.signer_weight += guardian_weight(guardian);
+
+        emit!(SignatureSubmitted {
+            withdrawal_hash,
+            guardian: guardian.pubkey,
+            signature_count: collection.signer_bitmap.count_ones() as u8,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Finalize a withdrawal once enough guardian signatures are collected
+    pub fn finalize_withdrawal(
+        ctx: Context<FinalizeWithdrawal>,
+        withdrawal_hash: [u8; 32],
+    ) -> Result<()> {
+        let bridge = &ctx.accounts.bridge_state;
+        require!(!bridge.is_paused, BridgeError::BridgePaused);
+
+        let collection = &mut ctx.accounts.signature_collection;
+        require!(!collection.finalized, BridgeError::WithdrawalAlreadyProcessed);
+        check_not_compacted(bridge, &withdrawal_hash)?;
+        require!(
+            collection.guardian_set_epoch == bridge.guardian_set_epoch,
+            BridgeError::StaleGuardianSet
+        );
+        require!(
+            quorum_reached(bridge, collection.signer_bitmap.count_ones(), collection.signer_weight),
+            BridgeError::InsufficientSignatures
+        );
+        require!(collection.amount >= bridge.min_withdrawal, BridgeError::WithdrawalTooSmall);
+        check_confirmations(bridge, collection.source_height, collection.current_height)?;
+        check_hub_block(
+            bridge,
+            ctx.accounts.hub_block.as_deref(),
+            collection.source_height,
+            &collection.source_block_hash,
+        )?;
+        require!(
+            Clock::get()?.unix_timestamp <= collection.valid_until,
+            BridgeError::SignatureExpired
+        );
+        collection.finalized = true;
+        let amount = collection.amount;
+
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        withdrawal.withdrawal_hash = withdrawal_hash;
+        withdrawal.recipient = ctx.accounts.recipient.key();
+        withdrawal.amount = amount;
+        withdrawal.processed = true;
+        withdrawal.timestamp = Clock::get()?.unix_timestamp;
+        withdrawal.bump = ctx.bumps.withdrawal;
+
+        let fee = pay_out_withdrawal(
+            &ctx.accounts.bridge_state,
+            &ctx.accounts.vault,
+            &ctx.accounts.recipient,
+            &ctx.accounts.fee_collector,
+            &ctx.accounts.system_program,
+            ctx.bumps.vault,
+            amount,
+        )?;
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.total_withdrawn += amount;
+        consume_bloom_override(bridge, &withdrawal_hash);
+
+        emit!(WithdrawalEvent {
+            withdrawal_hash,
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            fee,
+            timestamp: withdrawal.timestamp,
+        });
+
+        msg!("Finalized withdrawal of {} lamports", amount);
+
+        Ok(())
+    }
+
+    /// Queue a fully signed withdrawal behind the withdrawal delay
+    /// Rejected once `max_pending_withdrawals` entries are waiting, so the
+    /// queue cannot grow without bound during an incident.
+    pub fn queue_withdrawal(
+        ctx: Context<QueueWithdrawal>,
+        withdrawal_hash: [u8; 32],
+    ) -> Result<()> {
+        let bridge = &ctx.accounts.bridge_state;
+        require!(!bridge.is_paused, BridgeError::BridgePaused);
+        require!(
+            bridge.pending_withdrawal_count < bridge.max_pending_withdrawals,
+            BridgeError::WithdrawalQueueFull
+        );
+
+        let collection = &mut ctx.accounts.signature_collection;
+        require!(!collection.finalized, BridgeError::WithdrawalAlreadyProcessed);
+        check_not_compacted(bridge, &withdrawal_hash)?;
+        require!(
+            collection.guardian_set_epoch == bridge.guardian_set_epoch,
+            BridgeError::StaleGuardianSet
+        );
+        require!(
+            quorum_reached(bridge, collection.signer_bitmap.count_ones(), collection.signer_weight),
+            BridgeError::InsufficientSignatures
+        );
+        require!(collection.amount >= bridge.min_withdrawal, BridgeError::WithdrawalTooSmall);
+        check_confirmations(bridge, collection.source_height, collection.current_height)?;
+        check_hub_block(
+            bridge,
+            ctx.accounts.hub_block.as_deref(),
+            collection.source_height,
+            &collection.source_block_hash,
+        )?;
+        require!(
+            Clock::get()?.unix_timestamp <= collection.valid_until,
+            BridgeError::SignatureExpired
+        );
+        collection.finalized = true;
+
+        let queued = &mut ctx.accounts.queued_withdrawal;
+        queued.withdrawal_hash = withdrawal_hash;
+        queued.recipient = collection.recipient;
+        queued.amount = collection.amount;
+        queued.payer = ctx.accounts.payer.key();
+        queued.ready_at = Clock::get()?.unix_timestamp.saturating_add(bridge.withdrawal_delay);
+        queued.bump = ctx.bumps.queued_withdrawal;
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.pending_withdrawal_count += 1;
+
+        emit!(WithdrawalQueued {
+            withdrawal_hash,
+            recipient: queued.recipient,
+            amount: queued.amount,
+            ready_at: queued.ready_at,
+        });
+
+        Ok(())
+    }
+
+    /// Pay out a queued withdrawal once its delay has elapsed
+    pub fn execute_withdrawal(
+        ctx: Context<ExecuteWithdrawal>,
+        withdrawal_hash: [u8; 32],
+    ) -> Result<()> {
+        let bridge = &ctx.accounts.bridge_state;
+        require!(!bridge.is_paused, BridgeError::BridgePaused);
+        check_not_compacted(bridge, &withdrawal_hash)?;
+        let queued = &ctx.accounts.queued_withdrawal;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= queued.ready_at, BridgeError::TimelockNotElapsed);
+        let amount = queued.amount;
+
+        let withdrawal = &mut ctx.accounts.withdrawal;
+        withdrawal.withdrawal_hash = withdrawal_hash;
+        withdrawal.recipient = ctx.accounts.recipient.key();
+        withdrawal.amount = amount;
+        withdrawal.processed = true;
+        withdrawal.timestamp = now;
+        withdrawal.bump = ctx.bumps.withdrawal;
+
+        let fee = pay_out_withdrawal(
+            &ctx.accounts.bridge_state,
+            &ctx.accounts.vault,
+            &ctx.accounts.recipient,
+            &ctx.accounts.fee_collector,
+            &ctx.accounts.system_program,
+            ctx.bumps.vault,
+            amount,
+        )?;
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.total_withdrawn += amount;
+        consume_bloom_override(bridge, &withdrawal_hash);
+        bridge.pending_withdrawal_count -= 1;
+
+        emit!(WithdrawalEvent {
+            withdrawal_hash,
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            fee,
+            timestamp: now,
+        });
+
+        msg!("Executed queued withdrawal of {} lamports", amount);
+
+        Ok(())
+    }
+
+    /// Cancel a queued withdrawal before it is executed
+    pub fn cancel_withdrawal(
+        ctx: Context<CancelWithdrawal>,
+        withdrawal_hash: [u8; 32],
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.pending_withdrawal_count -= 1;
+
+        emit!(WithdrawalCancelled {
+            withdrawal_hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Drop a queued withdrawal nobody executed within the expiry window
+    pub fn reap_withdrawal(
+        ctx: Context<ReapWithdrawal>,
+        withdrawal_hash: [u8; 32],
+    ) -> Result<()> {
+        let expires_at = ctx
+            .accounts
+            .queued_withdrawal
+            .ready_at
+            .saturating_add(QUEUED_WITHDRAWAL_EXPIRY);
+        require!(
+            Clock::get()?.unix_timestamp >= expires_at,
+            BridgeError::WithdrawalNotExpired
+        );
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.pending_withdrawal_count -= 1;
+
+        msg!("Reaped expired withdrawal {:?}", withdrawal_hash);
+        Ok(())
+    }
+
+    /// Close processed withdrawal markers older than the retention period,
+    /// passed as writable remaining accounts, refunding their rent to the
+    /// authority. Each hash is first folded into the bridge's withdrawal
+    /// bloom filter, which keeps rejecting it as already processed.
+    /// Compaction stops for good once MAX_COMPACTED_WITHDRAWALS markers have
+    /// been folded in; later markers simply stay open, still guarding against
+    /// replays but keeping their rent locked.
+    pub fn compact_withdrawals<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CompactWithdrawals<'info>>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let bridge = &mut ctx.accounts.bridge_state;
+        require!(
+            bridge.compacted_withdrawals + ctx.remaining_accounts.len() as u64
+                <= MAX_COMPACTED_WITHDRAWALS,
+            BridgeError::WithdrawalBloomFull
+        );
+
+        for info in ctx.remaining_accounts.iter() {
+            let withdrawal = Account::<Withdrawal>::try_from(info)?;
+            let marker = Pubkey::create_program_address(
+                &[b"withdrawal", &withdrawal.withdrawal_hash, &[withdrawal.bump]],
+                &crate::ID,
+            )
+            .map_err(|_| BridgeError::WithdrawalMismatch)?;
+            require_keys_eq!(marker, info.key(), BridgeError::WithdrawalMismatch);
+            require!(withdrawal.processed, BridgeError::WithdrawalMismatch);
+            require!(
+                now >= withdrawal.timestamp.saturating_add(bridge.withdrawal_retention),
+                BridgeError::WithdrawalRetained
+            );
+
+            withdrawal_bloom_insert(&mut bridge.withdrawal_bloom, &withdrawal.withdrawal_hash);
+            withdrawal.close(ctx.accounts.authority.to_account_info())?;
+        }
+        bridge.compacted_withdrawals += ctx.remaining_accounts.len() as u64;
+
+        emit!(WithdrawalsCompacted {
+            count: ctx.remaining_accounts.len() as u64,
+            total_compacted: bridge.compacted_withdrawals,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Let a withdrawal through the bloom filter once threshold guardians
+    /// have confirmed off-chain that its hash is a false positive, i.e. it
+    /// was never paid out. The authority alone can't approve one, since a
+    /// wrong override would replay a compacted withdrawal. The override is
+    /// cleared when the withdrawal pays out.
+    pub fn approve_bloom_override<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ApproveBloomOverride<'info>>,
+        withdrawal_hash: [u8; 32],
+        valid_until: i64,
+        guardian_signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp <= valid_until,
+            BridgeError::SignatureExpired
+        );
+        require!(
+            ctx.remaining_accounts.len() == guardian_signatures.len(),
+            BridgeError::GuardianAccountsMismatch
+        );
+        let (signers, weight) = count_guardian_signers(
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            &bloom_override_message(&ctx.accounts.bridge_state, &withdrawal_hash, valid_until),
+            &guardian_signatures,
+        )?;
+        require!(
+            signers >= 1 && quorum_reached(&ctx.accounts.bridge_state, signers, weight),
+            BridgeError::InsufficientSignatures
+        );
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        require!(
+            withdrawal_hash != [0; 32]
+                && withdrawal_bloom_contains(&bridge.withdrawal_bloom, &withdrawal_hash)
+                && !bridge.bloom_overrides.contains(&withdrawal_hash),
+            BridgeError::BloomOverrideNotNeeded
+        );
+        let slot = bridge
+            .bloom_overrides
+            .iter_mut()
+            .find(|slot| **slot == [0; 32])
+            .ok_or(BridgeError::BloomOverridesFull)?;
+        *slot = withdrawal_hash;
+
+        emit!(BloomOverrideApproved {
+            withdrawal_hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long a processed withdrawal marker is kept before it can be
+    /// compacted
+    pub fn set_withdrawal_retention(
+        ctx: Context<AdminAction>,
+        retention: i64,
+    ) -> Result<()> {
+        require!(retention >= 0, BridgeError::InvalidRetention);
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.withdrawal_retention = retention;
+        msg!("Withdrawal retention set to {} seconds", retention);
+        Ok(())
+    }
+
+    /// Set how many seconds must pass between guardian additions and
+    /// removals, slowing set manipulation by a compromised authority
+    pub fn set_min_guardian_change_interval(
+        ctx: Context<AdminAction>,
+        interval: i64,
+    ) -> Result<()> {
+        require!(interval >= 0, BridgeError::InvalidGuardianChangeInterval);
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.min_guardian_change_interval = interval;
+        msg!("Guardian change interval set to {} seconds", interval);
+        Ok(())
+    }
+
+    /// Set the token program (SPL Token or Token-2022) that token deposits,
+    /// withdrawals and dust sweeps must go through
+    pub fn set_allowed_token_program(
+        ctx: Context<AdminAction>,
+        token_program: Pubkey,
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.allowed_token_program = token_program;
+        msg!("Allowed token program set to {}", token_program);
+        Ok(())
+    }
+
+    /// Pause or unpause deposits and withdrawals of a single mint
+    pub fn set_token_paused(ctx: Context<ConfigureToken>, paused: bool) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        if token_config.mint == Pubkey::default() {
+            token_config.pool_id = DEFAULT_TOKEN_POOL_ID;
+        }
+        token_config.mint = ctx.accounts.mint.key();
+        token_config.is_paused = paused;
+        token_config.bump = ctx.bumps.token_config;
+
+        emit!(TokenPauseUpdated {
+            mint: token_config.mint,
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set a mint's deposit limits, in its own base units, and its pause
+    /// state in one update
+    pub fn update_token_config(
+        ctx: Context<ConfigureToken>,
+        min_deposit: u64,
+        max_deposit: u64,
+        paused: bool,
+    ) -> Result<()> {
+        require!(min_deposit < max_deposit, BridgeError::InvalidDepositLimits);
+        let token_config = &mut ctx.accounts.token_config;
+        if token_config.mint == Pubkey::default() {
+            token_config.pool_id = DEFAULT_TOKEN_POOL_ID;
+        }
+        token_config.mint = ctx.accounts.mint.key();
+        token_config.min_deposit = min_deposit;
+        token_config.max_deposit = max_deposit;
+        token_config.is_paused = paused;
+        token_config.bump = ctx.bumps.token_config;
+
+        emit!(TokenConfigUpdated {
+            mint: token_config.mint,
+            min: min_deposit,
+            max: max_deposit,
+            paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the smallest withdrawal of a mint, in its own base units
+    pub fn set_token_min_withdrawal(
+        ctx: Context<ConfigureToken>,
+        min_withdrawal: u64,
+    ) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        if token_config.mint == Pubkey::default() {
+            token_config.pool_id = DEFAULT_TOKEN_POOL_ID;
+        }
+        token_config.mint = ctx.accounts.mint.key();
+        token_config.min_withdrawal = min_withdrawal;
+        token_config.bump = ctx.bumps.token_config;
+        msg!("Minimum withdrawal of {} set to {}", token_config.mint, min_withdrawal);
+        Ok(())
+    }
+
+    /// Record the outstanding deposits of a mint whose deposits predate
+    /// liability tracking, in its own base units. Can only be set once;
+    /// afterwards deposits and withdrawals keep the liability current.
+    pub fn backfill_token_liability(ctx: Context<ConfigureToken>, liability: u64) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        require!(!token_config.liability_tracked, BridgeError::LiabilityAlreadyTracked);
+        if token_config.mint == Pubkey::default() {
+            token_config.pool_id = DEFAULT_TOKEN_POOL_ID;
+        }
+        token_config.mint = ctx.accounts.mint.key();
+        token_config.liability = liability;
+        token_config.liability_tracked = true;
+        token_config.bump = ctx.bumps.token_config;
+        msg!("Liability of {} backfilled to {}", token_config.mint, liability);
+        Ok(())
+    }
+
+    /// Route a mint's future deposits to a hub shielded pool
+    /// Pool SOL_POOL_ID is reserved for native SOL.
+    pub fn set_token_pool(ctx: Context<ConfigureToken>, pool_id: u8) -> Result<()> {
+        require!(pool_id != SOL_POOL_ID, BridgeError::InvalidPoolId);
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.mint = ctx.accounts.mint.key();
+        token_config.pool_id = pool_id;
+        token_config.bump = ctx.bumps.token_config;
+
+        emit!(TokenPoolUpdated {
+            mint: token_config.mint,
+            pool_id,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pause the bridge on threshold guardian signatures over a pause
+    /// message, so guardians can freeze it when the authority is
+    /// unreachable. Signing guardians' PDAs are passed as remaining accounts
+    /// in signature order, as for `process_withdrawal`.
+    pub fn guardian_pause<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GuardianPause<'info>>,
+        valid_until: i64,
+        guardian_signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let bridge = &ctx.accounts.bridge_state;
+        require!(!bridge.is_paused, BridgeError::BridgePaused);
+        require!(
+            Clock::get()?.unix_timestamp <= valid_until,
+            BridgeError::SignatureExpired
+        );
+        require!(
+            ctx.remaining_accounts.len() == guardian_signatures.len(),
+            BridgeError::GuardianAccountsMismatch
+        );
+
+        let (signers, weight) = count_guardian_signers(
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            &pause_message(bridge, valid_until),
+            &guardian_signatures,
+        )?;
+        require!(
+            signers >= 1 && quorum_reached(bridge, signers, weight),
+            BridgeError::InsufficientSignatures
+        );
+
+        ctx.accounts.bridge_state.is_paused = true;
+
+        emit!(GuardianPaused {
+            signers,
+            submitted_by: ctx.accounts.payer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        msg!("Bridge paused by {} guardians", signers);
+
+        Ok(())
+    }
+
+    /// Publish a guardian-signed proof of reserves. Threshold guardians sign
+    /// the vault balance and deposit/withdrawal totals they observed at
+    /// `timestamp`; the attestation only stands if those match on-chain state.
+    pub fn attest_reserves<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AttestReserves<'info>>,
+        vault_balance: u64,
+        total_deposited: u64,
+        total_withdrawn: u64,
+        timestamp: i64,
+        guardian_signatures: Vec<[u8; 64]>,
+    ) -> Result<()> {
+        let bridge = &ctx.accounts.bridge_state;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            timestamp <= now && now - timestamp <= RESERVE_ATTESTATION_MAX_AGE,
+            BridgeError::SignatureExpired
+        );
+        require!(
+            vault_balance == ctx.accounts.vault.lamports()
+                && total_deposited == bridge.total_deposited
+                && total_withdrawn == bridge.total_withdrawn,
+            BridgeError::ReserveMismatch
+        );
+        require!(
+            ctx.remaining_accounts.len() == guardian_signatures.len(),
+            BridgeError::GuardianAccountsMismatch
+        );
+
+        let (signers, weight) = count_guardian_signers(
+            ctx.remaining_accounts,
+            &ctx.accounts.instructions.to_account_info(),
+            &reserves_message(bridge, vault_balance, total_deposited, total_withdrawn, timestamp),
+            &guardian_signatures,
+        )?;
+        require!(
+            signers >= 1 && quorum_reached(bridge, signers, weight),
+            BridgeError::InsufficientSignatures
+        );
+
+        emit!(ReserveAttestation {
+            vault_balance,
+            total_deposited,
+            total_withdrawn,
+            signers,
+            epoch: bridge.guardian_set_epoch,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Emergency pause the bridge
+    pub fn pause(ctx: Context<AdminAction>) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.is_paused = true;
+        msg!("Bridge paused");
+        Ok(())
+    }
+
+    /// Unpause the bridge
+    pub fn unpause(ctx: Context<AdminAction>) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.is_paused = false;
+        msg!("Bridge unpaused");
+        Ok(())
+    }
+
+    /// Move vault funds to the pre-committed emergency recipient
+    /// Only allowed while the bridge is paused.
+    pub fn emergency_drain(ctx: Context<EmergencyDrain>, amount: u64) -> Result<()> {
+        require!(ctx.accounts.bridge_state.is_paused, BridgeError::BridgeNotPaused);
+
+        transfer_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.emergency_recipient,
+            &ctx.accounts.system_program,
+            ctx.bumps.vault,
+            amount,
+        )?;
+
+        emit!(EmergencyDrained {
+            recipient: ctx.accounts.emergency_recipient.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Emergency drained {} lamports", amount);
+        Ok(())
+    }
+
+    /// Start the timelock for changing the emergency recipient
+    pub fn propose_emergency_recipient(
+        ctx: Context<AdminAction>,
+        new_recipient: Pubkey,
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.pending_emergency_recipient = new_recipient;
+        bridge.emergency_recipient_ready_at = Clock::get()?
+            .unix_timestamp
+            .saturating_add(EMERGENCY_RECIPIENT_DELAY);
+        msg!("Emergency recipient change ready at {}", bridge.emergency_recipient_ready_at);
+        Ok(())
+    }
+
+    /// Apply a proposed emergency recipient once its timelock has elapsed
+    pub fn apply_emergency_recipient(ctx: Context<AdminAction>) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        require!(bridge.emergency_recipient_ready_at != 0, BridgeError::NoPendingChange);
+        require!(
+            Clock::get()?.unix_timestamp >= bridge.emergency_recipient_ready_at,
+            BridgeError::TimelockNotElapsed
+        );
+        bridge.emergency_recipient = bridge.pending_emergency_recipient;
+        bridge.pending_emergency_recipient = Pubkey::default();
+        bridge.emergency_recipient_ready_at = 0;
+        msg!("Emergency recipient updated to {}", bridge.emergency_recipient);
+        Ok(())
+    }
+
+    /// Set how long removed guardians' signatures stay valid for
+    /// withdrawals queued before their removal
+    pub fn set_signature_grace_period(
+        ctx: Context<AdminAction>,
+        grace_period: i64,
+    ) -> Result<()> {
+        require!(grace_period >= 0, BridgeError::InvalidGracePeriod);
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.signature_grace_period = grace_period;
+        msg!("Signature grace period set to {} seconds", grace_period);
+        Ok(())
+    }
+
+    /// Update the withdrawal fee and the account that receives it
+    pub fn update_withdrawal_fee(
+        ctx: Context<AdminAction>,
+        fee_bps: u16,
+        fee_collector: Pubkey,
+    ) -> Result<()> {
+        require!(fee_bps <= MAX_WITHDRAWAL_FEE_BPS, BridgeError::FeeTooHigh);
+        let bridge = &mut ctx.accounts.bridge_state;
+        let old_fee_bps = bridge.withdrawal_fee_bps;
+        bridge.withdrawal_fee_bps = fee_bps;
+        bridge.fee_collector = fee_collector;
+
+        emit!(WithdrawalFeeUpdated {
+            old_fee_bps,
+            new_fee_bps: fee_bps,
+            fee_collector,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set how many hub blocks must follow a withdrawal's source event
+    pub fn set_min_confirmations(
+        ctx: Context<AdminAction>,
+        min_confirmations: u64,
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.min_confirmations = min_confirmations;
+        msg!("Minimum hub confirmations set to {}", min_confirmations);
+        Ok(())
+    }
+
+    /// Set the smallest withdrawal amount to stop dust griefing
+    pub fn set_min_withdrawal(
+        ctx: Context<AdminAction>,
+        min_withdrawal: u64,
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.min_withdrawal = min_withdrawal;
+        msg!("Minimum withdrawal set to {} lamports", min_withdrawal);
+        Ok(())
+    }
+
+    /// Set the lamports withdrawals must leave in the vault above its
+    /// rent-exempt minimum
+    pub fn set_min_liquidity_buffer(
+        ctx: Context<AdminAction>,
+        buffer: u64,
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.min_liquidity_buffer = buffer;
+        msg!("Vault liquidity buffer set to {} lamports", buffer);
+        Ok(())
+    }
+
+    /// Configure the withdrawal queue delay and its size cap
+    pub fn set_withdrawal_queue(
+        ctx: Context<AdminAction>,
+        withdrawal_delay: i64,
+        max_pending_withdrawals: u64,
+    ) -> Result<()> {
+        require!(withdrawal_delay >= 0, BridgeError::InvalidWithdrawalDelay);
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.withdrawal_delay = withdrawal_delay;
+        bridge.max_pending_withdrawals = max_pending_withdrawals;
+        msg!(
+            "Withdrawal queue: {}s delay, at most {} pending",
+            withdrawal_delay,
+            max_pending_withdrawals
+        );
+        Ok(())
+    }
+
+    /// Grow a bridge_state created under an older, shorter layout to the
+    /// current size, topping up rent and defaulting the new fields
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let info = ctx.accounts.bridge_state.to_account_info();
+        let old_len = info.data_len();
+        {
+            // Only the discriminator and leading authority field are read
+            // before the account is resized
+            let data = info.try_borrow_data()?;
+            require!(
+                old_len >= 8 + 32 && data[..8] == *BridgeState::DISCRIMINATOR,
+                ErrorCode::AccountDiscriminatorMismatch
+            );
+            require_keys_eq!(
+                Pubkey::new_from_array(data[8..40].try_into().unwrap()),
+                ctx.accounts.authority.key(),
+                BridgeError::Unauthorized
+            );
+        }
+        if !grow_account(
+            &info,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            BRIDGE_STATE_SPACE,
+        )? {
+            msg!("Bridge state already at current layout");
+            return Ok(());
+        }
+
+        // New fields are zero-filled; fix up those whose default isn't zero
+        let mut bridge = BridgeState::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        if bridge.fee_collector == Pubkey::default() {
+            bridge.fee_collector = bridge.authority;
+        }
+        if bridge.max_pending_withdrawals == 0 {
+            bridge.max_pending_withdrawals = DEFAULT_MAX_PENDING_WITHDRAWALS;
+        }
+        if bridge.withdrawal_retention == 0 {
+            bridge.withdrawal_retention = DEFAULT_WITHDRAWAL_RETENTION;
+        }
+        if bridge.allowed_token_program == Pubkey::default() {
+            bridge.allowed_token_program = token::ID;
+        }
+        if bridge.total_weight == 0 {
+            // Guardians created before weights were added count as 1
+            bridge.total_weight = bridge.guardian_count as u32;
+        }
+        bridge.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!("Migrated bridge state from {} to {} bytes", old_len, BRIDGE_STATE_SPACE);
+        Ok(())
+    }
+
+    /// Grow a guardian, token config or SOL deposit account created under an
+    /// older, shorter layout to the current size, topping up rent and
+    /// defaulting the new fields. Run `migrate_state` first.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let info = ctx.accounts.account.to_account_info();
+        let old_len = info.data_len();
+        let space = {
+            let data = info.try_borrow_data()?;
+            let discriminator = data.get(..8).ok_or(ErrorCode::AccountDiscriminatorNotFound)?;
+            if discriminator == Guardian::DISCRIMINATOR {
+                GUARDIAN_SPACE
+            } else if discriminator == TokenConfig::DISCRIMINATOR {
+                TOKEN_CONFIG_SPACE
+            } else if discriminator == Deposit::DISCRIMINATOR {
+                DEPOSIT_SPACE
+            } else {
+                return err!(ErrorCode::AccountDiscriminatorMismatch);
+            }
+        };
+        if !grow_account(
+            &info,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            space,
+        )? {
+            msg!("Account already at current layout");
+            return Ok(());
+        }
+
+        // New fields are zero-filled; fix up those whose default isn't zero.
+        // Deposits only gained pool_id, whose default SOL_POOL_ID is zero.
+        if space == GUARDIAN_SPACE {
+            let mut guardian = Guardian::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            // Guardians from before signer bitmaps have no slot yet
+            if old_len < 8 + 32 + 1 + 8 + 1 {
+                let bridge = &mut ctx.accounts.bridge_state;
+                require!(
+                    (bridge.next_guardian_index as usize) < MAX_GUARDIANS,
+                    BridgeError::TooManyGuardians
+                );
+                guardian.index = bridge.next_guardian_index;
+                bridge.next_guardian_index += 1;
+            }
+            guardian.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        } else if space == TOKEN_CONFIG_SPACE {
+            let mut token_config =
+                TokenConfig::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            if token_config.pool_id == SOL_POOL_ID {
+                token_config.pool_id = DEFAULT_TOKEN_POOL_ID;
+            }
+            token_config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        }
+
+        msg!("Migrated account from {} to {} bytes", old_len, space);
+        Ok(())
+    }
+
+    /// Set an active guardian's voting weight (at least 1)
+    pub fn set_guardian_weight(
+        ctx: Context<SetGuardianWeight>,
+        _guardian_pubkey: Pubkey,
+        weight: u16,
+    ) -> Result<()> {
+        require!(weight >= 1, BridgeError::InvalidWeight);
+        let guardian = &mut ctx.accounts.guardian;
+        require!(guardian.is_active, BridgeError::GuardianNotActive);
+
+        let bridge = &mut ctx.accounts.bridge_state;
+        bridge.total_weight = bridge.total_weight - guardian_weight(guardian) + weight as u32;
+        require!(
+            bridge.total_weight >= bridge.weight_threshold,
+            BridgeError::InsufficientGuardians
+        );
+        guardian.weight = weight;
+
+        emit!(GuardianWeightUpdated {
+            guardian: guardian.pubkey,
+            weight,
+            total_weight: bridge.total_weight,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Require signers' summed weight to reach `weight_threshold` instead
+    /// of counting them against `guardian_threshold` (0 restores counting)
+    pub fn set_weight_threshold(
+        ctx: Context<AdminAction>,
+        weight_threshold: u32,
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        require!(
+            weight_threshold <= bridge.total_weight,
+            BridgeError::ThresholdTooHigh
+        );
+        bridge.weight_threshold = weight_threshold;
+        msg!("Weight threshold set to {}", weight_threshold);
+        Ok(())
+    }
+
+    /// Update guardian threshold
+    pub fn update_threshold(
+        ctx: Context<AdminAction>,
+        new_threshold: u8,
+    ) -> Result<()> {
+        let bridge = &mut ctx.accounts.bridge_state;
+        require!(
+            new_threshold as u64 <= bridge.guardian_count,
+            BridgeError::ThresholdTooHigh
+        );
+        bridge.guardian_threshold = new_threshold;
+        
+        emit!(ThresholdUpdated {
+            old_threshold: bridge.guardian_threshold,
+            new_threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+}
+
+// ============ Constants ============
+
+pub const MIN_DEPOSIT: u64 = 10_000_000;      // 0.01 SOL (10M lamports)
+pub const MAX_DEPOSIT: u64 = 100_000_000_000; // 100 SOL
+pub const MAX_GUARDIANS: usize = 64;          // Width of the signer bitmap
+pub const CANONICAL_DECIMALS: u8 = 18;        // Hub chain token precision
+pub const MAX_WITHDRAWAL_FEE_BPS: u16 = 100;  // 1% cap on withdrawal fees
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const EMERGENCY_RECIPIENT_DELAY: i64 = 48 * 60 * 60; // 48h timelock on cold wallet changes
+pub const DEFAULT_MAX_PENDING_WITHDRAWALS: u64 = 256;
+pub const SOL_POOL_ID: u8 = 0;                // Hub shielded pool for native SOL
+pub const DEFAULT_TOKEN_POOL_ID: u8 = 1;      // Pool for mints without a configured one
+pub const NONCE_RESERVATION_WINDOW: i64 = 60;  // Seconds a reserved deposit nonce stays usable
+pub const BRIDGE_STATE_SPACE: usize =
+    8 + 32 + 8 + 1 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 8 + 2 + 32 + 8 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 16 + 8 + 8 + 8 + 16 * 32 + 32 + 4 + 4 + 1 + 8 + 8 + 8 + 32 * MAX_BLOOM_OVERRIDES;
+pub const TOKEN_CONFIG_SPACE: usize = 8 + 32 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 1;
+pub const GUARDIAN_SPACE: usize = 8 + 32 + 1 + 8 + 1 + 8 + 32 + 32 + 2;
+pub const DEPOSIT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 1;
+pub const QUEUED_WITHDRAWAL_EXPIRY: i64 = 7 * 24 * 60 * 60; // Unexecuted entries can be reaped after 7 days
+pub const DEFAULT_WITHDRAWAL_RETENTION: i64 = 30 * 24 * 60 * 60; // Markers kept 30 days before compaction
+pub const WITHDRAWAL_BLOOM_BITS: usize = 32 * 128;  // Width of BridgeState::withdrawal_bloom
+pub const WITHDRAWAL_BLOOM_HASHES: usize = 3;
+pub const MAX_COMPACTED_WITHDRAWALS: u64 = 256;     // Keeps the bloom's false positive rate under ~0.5%; compaction stops here
+pub const MAX_BLOOM_OVERRIDES: usize = 4;            // Pending guardian-approved bloom false positives
+pub const RESERVE_ATTESTATION_MAX_AGE: i64 = 10 * 60; // Reserve snapshots older than 10 minutes are stale
+
+// ============ State Accounts ============
 
 #[account]
 #[derive(Default)]
@@ -302,86 +2117,904 @@ pub struct BridgeState {
     pub is_paused: bool,
     /// PDA bump
     pub bump: u8,
+    /// Next unassigned guardian bitmap index
+    pub next_guardian_index: u8,
+    /// Seconds a removed guardian's signatures remain valid
+    pub signature_grace_period: i64,
+    /// Fee taken from each withdrawal, in basis points
+    pub withdrawal_fee_bps: u16,
+    /// Account that receives withdrawal fees
+    pub fee_collector: Pubkey,
+    /// Hub blocks required after a withdrawal's source event
+    pub min_confirmations: u64,
+    /// Smallest withdrawal amount in lamports
+    pub min_withdrawal: u64,
+    /// Cold wallet that receives emergency drains
+    pub emergency_recipient: Pubkey,
+    /// Proposed emergency recipient awaiting its timelock
+    pub pending_emergency_recipient: Pubkey,
+    /// When the pending emergency recipient can be applied (0 if none)
+    pub emergency_recipient_ready_at: i64,
+    /// Seconds a queued withdrawal waits before it can be executed
+    pub withdrawal_delay: i64,
+    /// Withdrawals currently waiting in the queue
+    pub pending_withdrawal_count: u64,
+    /// Cap on `pending_withdrawal_count`
+    pub max_pending_withdrawals: u64,
+    /// Hub deployment tag bound into signed withdrawals, so signatures
+    /// for one deployment (e.g. testnet) are invalid on another
+    pub domain_tag: [u8; 16],
+    /// Bumped on every full guardian set rotation
+    pub guardian_set_epoch: u64,
+    /// Seconds a processed withdrawal marker is kept before compaction
+    pub withdrawal_retention: i64,
+    /// Withdrawal markers folded into `withdrawal_bloom` so far
+    pub compacted_withdrawals: u64,
+    /// Bloom filter of compacted withdrawal hashes
+    pub withdrawal_bloom: [u128; 32],
+    /// Token program token transfers must go through
+    pub allowed_token_program: Pubkey,
+    /// Summed weight of active guardians
+    pub total_weight: u32,
+    /// Signer weight required for withdrawals and guardian pauses; 0 falls
+    /// back to counting signers against `guardian_threshold`
+    pub weight_threshold: u32,
+    /// Whether withdrawals must reference a guardian-committed hub block
+    pub require_hub_block: bool,
+    /// Unix timestamp of the last add_guardian/remove_guardian
+    pub last_guardian_change: i64,
+    /// Seconds required between guardian additions and removals
+    pub min_guardian_change_interval: i64,
+    /// Lamports withdrawals must leave in the vault above its rent-exempt
+    /// minimum
+    pub min_liquidity_buffer: u64,
+    /// Withdrawal hashes approved past a bloom false positive, zeroed once
+    /// used
+    pub bloom_overrides: [[u8; 32]; MAX_BLOOM_OVERRIDES],
+}
+
+#[account]
+pub struct Guardian {
+    /// Guardian public key
+    pub pubkey: Pubkey,
+    /// Whether guardian is active
+    pub is_active: bool,
+    /// When guardian was added
+    pub added_at: i64,
+    /// Position in signature collection bitmaps
+    pub index: u8,
+    /// When guardian was last removed (0 if never)
+    pub removed_at: i64,
+    /// Operator-facing label, zero padded
+    pub name: [u8; 32],
+    /// Hash of the guardian's service endpoint
+    pub endpoint_hash: [u8; 32],
+    /// Voting weight toward `weight_threshold` (0 is read as 1)
+    pub weight: u16,
+}
+
+#[account]
+pub struct Deposit {
+    /// Depositor's public key
+    pub depositor: Pubkey,
+    /// Poseidon commitment hash
+    pub commitment: [u8; 32],
+    /// Deposit amount in lamports
+    pub amount: u64,
+    /// Unique deposit nonce
+    pub nonce: u64,
+    /// Unix timestamp
+    pub timestamp: i64,
+    /// Whether deposit was relayed to hub
+    pub processed: bool,
+    /// PDA bump
+    pub bump: u8,
+    /// Hub shielded pool the commitment belongs to (always SOL_POOL_ID)
+    pub pool_id: u8,
+}
+
+#[account]
+pub struct TokenDeposit {
+    /// Depositor's public key
+    pub depositor: Pubkey,
+    /// Token mint address
+    pub mint: Pubkey,
+    /// Poseidon commitment hash
+    pub commitment: [u8; 32],
+    /// Deposit amount
+    pub amount: u64,
+    /// Deposit amount scaled to CANONICAL_DECIMALS
+    pub normalized_amount: u128,
+    /// Unique deposit nonce
+    pub nonce: u64,
+    /// Unix timestamp
+    pub timestamp: i64,
+    /// Whether deposit was relayed to hub
+    pub processed: bool,
+    /// PDA bump
+    pub bump: u8,
+    /// Hub shielded pool the commitment belongs to
+    pub pool_id: u8,
+}
+
+#[account]
+pub struct Withdrawal {
+    /// Hash of the withdrawal request from hub chain
+    pub withdrawal_hash: [u8; 32],
+    /// Recipient's public key
+    pub recipient: Pubkey,
+    /// Withdrawal amount
+    pub amount: u64,
+    /// Whether withdrawal was processed
+    pub processed: bool,
+    /// Unix timestamp
+    pub timestamp: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[account]
+pub struct HubBlock {
+    /// Hub chain block hash
+    pub block_hash: [u8; 32],
+    /// Hub chain block height
+    pub height: u64,
+    /// Unix timestamp
+    pub committed_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[account]
+pub struct WithdrawalBatch {
+    /// Merkle root of the batch's withdrawal leaves
+    pub root: [u8; 32],
+    /// Guardians whose signatures approved the root
+    pub signers: u32,
+    /// Unix timestamp
+    pub committed_at: i64,
+    /// PDA bump
+    pub bump: u8,
+    /// Guardian set epoch the root was signed under
+    pub guardian_set_epoch: u64,
+    /// Expiry bound into the signatures
+    pub valid_until: i64,
+}
+
+#[account]
+pub struct SignatureCollection {
+    /// Withdrawal being authorized
+    pub withdrawal_hash: [u8; 32],
+    /// Recipient bound into the signed message
+    pub recipient: Pubkey,
+    /// Amount bound into the signed message
+    pub amount: u64,
+    /// Bitmap of guardian indices with verified signatures
+    pub signer_bitmap: u64,
+    /// When the first signature was submitted
+    pub created_at: i64,
+    /// Whether the withdrawal has been paid out
+    pub finalized: bool,
+    /// PDA bump
+    pub bump: u8,
+    /// Guardian set epoch the collection was started under
+    pub guardian_set_epoch: u64,
+    /// Summed weight of the guardians in `signer_bitmap`
+    pub signer_weight: u32,
+    /// Hub height of the withdrawal's source event, bound into the signatures
+    pub source_height: u64,
+    /// Hub height guardians observed when signing
+    pub current_height: u64,
+    /// Expiry bound into the signatures
+    pub valid_until: i64,
+    /// Hash of the hub block holding the source event, bound into the
+    /// signatures
+    pub source_block_hash: [u8; 32],
+}
+
+/// Hub block holding a withdrawal's source event, as guardians signed it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SourceBlock {
+    /// Hub height of the source event
+    pub height: u64,
+    /// Hash of the hub block at `height`
+    pub hash: [u8; 32],
+}
+
+/// Withdrawal parameters a guardian signs when submitting a signature
+/// incrementally
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct WithdrawalRequest {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// Hub height of the withdrawal's source event
+    pub source_height: u64,
+    /// Hash of the hub block holding the source event
+    pub source_block_hash: [u8; 32],
+    /// Hub height the guardian observed when signing
+    pub current_height: u64,
+    /// Time after which the signature expires
+    pub valid_until: i64,
+}
+
+#[account]
+pub struct QueuedWithdrawal {
+    /// Withdrawal being delayed
+    pub withdrawal_hash: [u8; 32],
+    /// Recipient the guardians signed for
+    pub recipient: Pubkey,
+    /// Amount the guardians signed for
+    pub amount: u64,
+    /// Account that paid rent for this entry
+    pub payer: Pubkey,
+    /// When the withdrawal can be executed
+    pub ready_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[account]
+pub struct NonceReservation {
+    /// Depositor holding the reservation
+    pub depositor: Pubkey,
+    /// Reserved deposit nonce
+    pub nonce: u64,
+    /// When the reservation lapses
+    pub expires_at: i64,
+    /// PDA bump
+    pub bump: u8,
+}
+
+#[account]
+pub struct TokenConfig {
+    /// Mint this config applies to
+    pub mint: Pubkey,
+    /// Whether deposits and withdrawals of this mint are paused
+    pub is_paused: bool,
+    /// PDA bump
+    pub bump: u8,
+    /// Hub shielded pool this mint's commitments are routed to
+    pub pool_id: u8,
+    /// Smallest deposit in the mint's base units (0 uses MIN_DEPOSIT)
+    pub min_deposit: u64,
+    /// Largest deposit in the mint's base units (0 means unlimited)
+    pub max_deposit: u64,
+    /// Vault balance backing deposits not yet withdrawn, in base units
+    pub liability: u64,
+    /// Smallest withdrawal in the mint's base units
+    pub min_withdrawal: u64,
+    /// Whether `liability` covers every deposit of the mint; false for mints
+    /// with deposits from before liabilities were tracked, until backfilled
+    pub liability_tracked: bool,
+}
+
+impl TokenConfig {
+    pub fn min_deposit(&self) -> u64 {
+        if self.min_deposit == 0 { MIN_DEPOSIT } else { self.min_deposit }
+    }
+
+    pub fn max_deposit(&self) -> u64 {
+        if self.max_deposit == 0 { u64::MAX } else { self.max_deposit }
+    }
+}
+
+// ============ Contexts ============
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = BRIDGE_STATE_SPACE,
+        seeds = [b"bridge_state"],
+        bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    /// CHECK: This is the SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(guardian_pubkey: Pubkey)]
+pub struct ManageGuardian<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = GUARDIAN_SPACE,
+        seeds = [b"guardian", guardian_pubkey.as_ref()],
+        bump
+    )]
+    pub guardian: Account<'info, Guardian>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(guardian_pubkey: Pubkey)]
+pub struct UpdateGuardianMetadata<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        seeds = [b"guardian", guardian_pubkey.as_ref()],
+        bump
+    )]
+    pub guardian: Account<'info, Guardian>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, commitment: [u8; 32])]
+pub struct DepositSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = depositor,
+        space = DEPOSIT_SPACE,
+        seeds = [b"deposit", bridge_state.deposit_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub deposit: Account<'info, Deposit>,
+    
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReserveNonce<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"nonce_reservation", depositor.key().as_ref()],
+        bump
+    )]
+    pub reservation: Account<'info, NonceReservation>,
+    
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSolReserved<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        close = depositor,
+        seeds = [b"nonce_reservation", depositor.key().as_ref()],
+        bump = reservation.bump
+    )]
+    pub reservation: Account<'info, NonceReservation>,
+    
+    #[account(
+        init,
+        payer = depositor,
+        space = DEPOSIT_SPACE,
+        seeds = [b"deposit", reservation.nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub deposit: Account<'info, Deposit>,
+    
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseNonce<'info> {
+    #[account(
+        mut,
+        close = depositor,
+        seeds = [b"nonce_reservation", depositor.key().as_ref()],
+        bump = reservation.bump
+    )]
+    pub reservation: Account<'info, NonceReservation>,
+    
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CloseDeposit<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        close = depositor,
+        seeds = [b"deposit", nonce.to_le_bytes().as_ref()],
+        bump = deposit.bump
+    )]
+    pub deposit: Account<'info, Deposit>,
+    
+    /// CHECK: Receives the reclaimed rent
+    #[account(
+        mut,
+        address = deposit.depositor @ BridgeError::DepositorMismatch
+    )]
+    pub depositor: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, commitment: [u8; 32])]
+pub struct DepositToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + 32 + 32 + 32 + 8 + 16 + 8 + 8 + 1 + 1 + 1,
+        seeds = [b"token_deposit", bridge_state.deposit_nonce.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub token_deposit: Account<'info, TokenDeposit>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = TOKEN_CONFIG_SPACE,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        constraint = depositor_token_account.owner == depositor.key()
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        seeds = [b"vault_token", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = bridge_state,
+        token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(withdrawal_hash: [u8; 32])]
+pub struct ProcessWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 32 + 8 + 1 + 8 + 1,
+        seeds = [b"withdrawal", withdrawal_hash.as_ref()],
+        bump
+    )]
+    pub withdrawal: Account<'info, Withdrawal>,
+    
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    /// CHECK: Withdrawal recipient
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    
+    /// CHECK: Receives the withdrawal fee
+    #[account(
+        mut,
+        address = bridge_state.fee_collector @ BridgeError::FeeCollectorMismatch
+    )]
+    pub fee_collector: UncheckedAccount<'info>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    /// Committed hub block holding the source event, when required
+    pub hub_block: Option<Account<'info, HubBlock>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct Guardian {
-    /// Guardian public key
-    pub pubkey: Pubkey,
-    /// Whether guardian is active
-    pub is_active: bool,
-    /// When guardian was added
-    pub added_at: i64,
+#[derive(Accounts)]
+#[instruction(withdrawal_hash: [u8; 32])]
+pub struct ProcessTokenWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 32 + 8 + 1 + 8 + 1,
+        seeds = [b"withdrawal", withdrawal_hash.as_ref()],
+        bump
+    )]
+    pub withdrawal: Account<'info, Withdrawal>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_token", mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = bridge_state,
+        token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    /// Committed hub block holding the source event, when required
+    pub hub_block: Option<Account<'info, HubBlock>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct Deposit {
-    /// Depositor's public key
-    pub depositor: Pubkey,
-    /// Poseidon commitment hash
-    pub commitment: [u8; 32],
-    /// Deposit amount in lamports
-    pub amount: u64,
-    /// Unique deposit nonce
-    pub nonce: u64,
-    /// Unix timestamp
-    pub timestamp: i64,
-    /// Whether deposit was relayed to hub
-    pub processed: bool,
-    /// PDA bump
-    pub bump: u8,
+#[derive(Accounts)]
+#[instruction(root: [u8; 32])]
+pub struct CommitWithdrawalBatch<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 4 + 8 + 1 + 8 + 8,
+        seeds = [b"withdrawal_batch", root.as_ref()],
+        bump
+    )]
+    pub withdrawal_batch: Account<'info, WithdrawalBatch>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct TokenDeposit {
-    /// Depositor's public key
-    pub depositor: Pubkey,
-    /// Token mint address
-    pub mint: Pubkey,
-    /// Poseidon commitment hash
-    pub commitment: [u8; 32],
-    /// Deposit amount
-    pub amount: u64,
-    /// Unique deposit nonce
-    pub nonce: u64,
-    /// Unix timestamp
-    pub timestamp: i64,
-    /// Whether deposit was relayed to hub
-    pub processed: bool,
-    /// PDA bump
-    pub bump: u8,
+#[derive(Accounts)]
+pub struct ApproveBloomOverride<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
 }
 
-#[account]
-pub struct Withdrawal {
-    /// Hash of the withdrawal request from hub chain
-    pub withdrawal_hash: [u8; 32],
-    /// Recipient's public key
-    pub recipient: Pubkey,
-    /// Withdrawal amount
-    pub amount: u64,
-    /// Whether withdrawal was processed
-    pub processed: bool,
-    /// Unix timestamp
-    pub timestamp: i64,
-    /// PDA bump
-    pub bump: u8,
+#[derive(Accounts)]
+pub struct AckDeposit<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    /// SOL deposit record; the signed message binds its nonce
+    #[account(mut)]
+    pub deposit: Account<'info, Deposit>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AckTokenDeposit<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    /// Token deposit record; the signed message binds its nonce
+    #[account(mut)]
+    pub token_deposit: Account<'info, TokenDeposit>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(block_hash: [u8; 32])]
+pub struct CommitHubBlock<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"hub_block", block_hash.as_ref()],
+        bump
+    )]
+    pub hub_block: Account<'info, HubBlock>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(root: [u8; 32], withdrawal_hash: [u8; 32])]
+pub struct ClaimFromBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        seeds = [b"withdrawal_batch", root.as_ref()],
+        bump = withdrawal_batch.bump
+    )]
+    pub withdrawal_batch: Account<'info, WithdrawalBatch>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 32 + 8 + 1 + 8 + 1,
+        seeds = [b"withdrawal", withdrawal_hash.as_ref()],
+        bump
+    )]
+    pub withdrawal: Account<'info, Withdrawal>,
+    
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    /// CHECK: Withdrawal recipient, bound into the batch leaf
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+    
+    /// CHECK: Receives the withdrawal fee
+    #[account(
+        mut,
+        address = bridge_state.fee_collector @ BridgeError::FeeCollectorMismatch
+    )]
+    pub fee_collector: UncheckedAccount<'info>,
+    
+    /// Committed hub block holding the source event, when required
+    pub hub_block: Option<Account<'info, HubBlock>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundVault<'info> {
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
-// ============ Contexts ============
-
 #[derive(Accounts)]
-pub struct Initialize<'info> {
+#[instruction(withdrawal_hash: [u8; 32])]
+pub struct SubmitSignature<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 8 + 1 + 8 + 8 + 8 + 8 + 1 + 1,
         seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + 8 + 4 + 8 + 8 + 8 + 32,
+        seeds = [b"signatures", withdrawal_hash.as_ref()],
+        bump
+    )]
+    pub signature_collection: Account<'info, SignatureCollection>,
+    
+    #[account(
+        seeds = [b"guardian", guardian.pubkey.as_ref()],
         bump
     )]
+    pub guardian: Account<'info, Guardian>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verification
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(withdrawal_hash: [u8; 32])]
+pub struct FinalizeWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
     pub bridge_state: Account<'info, BridgeState>,
     
-    /// CHECK: This is the SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"signatures", withdrawal_hash.as_ref()],
+        bump = signature_collection.bump
+    )]
+    pub signature_collection: Account<'info, SignatureCollection>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 32 + 8 + 1 + 8 + 1,
+        seeds = [b"withdrawal", withdrawal_hash.as_ref()],
+        bump
+    )]
+    pub withdrawal: Account<'info, Withdrawal>,
+    
+    /// CHECK: SOL vault PDA
     #[account(
         mut,
         seeds = [b"vault"],
@@ -389,40 +3022,67 @@ pub struct Initialize<'info> {
     )]
     pub vault: UncheckedAccount<'info>,
     
+    /// CHECK: Must match the recipient the guardians signed for
+    #[account(
+        mut,
+        address = signature_collection.recipient @ BridgeError::WithdrawalMismatch
+    )]
+    pub recipient: UncheckedAccount<'info>,
+    
+    /// CHECK: Receives the withdrawal fee
+    #[account(
+        mut,
+        address = bridge_state.fee_collector @ BridgeError::FeeCollectorMismatch
+    )]
+    pub fee_collector: UncheckedAccount<'info>,
+    
+    /// Committed hub block holding the source event, when required
+    pub hub_block: Option<Account<'info, HubBlock>>,
+    
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ManageGuardian<'info> {
+#[instruction(withdrawal_hash: [u8; 32])]
+pub struct QueueWithdrawal<'info> {
     #[account(
         mut,
         seeds = [b"bridge_state"],
-        bump = bridge_state.bump,
-        has_one = authority
+        bump = bridge_state.bump
     )]
     pub bridge_state: Account<'info, BridgeState>,
     
     #[account(
-        init_if_needed,
-        payer = authority,
-        space = 8 + 32 + 1 + 8,
-        seeds = [b"guardian", guardian.key().as_ref()],
+        mut,
+        seeds = [b"signatures", withdrawal_hash.as_ref()],
+        bump = signature_collection.bump
+    )]
+    pub signature_collection: Account<'info, SignatureCollection>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 32 + 8 + 32 + 8 + 1,
+        seeds = [b"queued_withdrawal", withdrawal_hash.as_ref()],
         bump
     )]
-    pub guardian: Account<'info, Guardian>,
+    pub queued_withdrawal: Account<'info, QueuedWithdrawal>,
+    
+    /// Committed hub block holding the source event, when required
+    pub hub_block: Option<Account<'info, HubBlock>>,
     
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(amount: u64, commitment: [u8; 32])]
-pub struct DepositSol<'info> {
+#[instruction(withdrawal_hash: [u8; 32])]
+pub struct ExecuteWithdrawal<'info> {
     #[account(
         mut,
         seeds = [b"bridge_state"],
@@ -430,14 +3090,23 @@ pub struct DepositSol<'info> {
     )]
     pub bridge_state: Account<'info, BridgeState>,
     
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"queued_withdrawal", withdrawal_hash.as_ref()],
+        bump = queued_withdrawal.bump,
+        has_one = payer @ BridgeError::PayerMismatch
+    )]
+    pub queued_withdrawal: Account<'info, QueuedWithdrawal>,
+    
     #[account(
         init,
-        payer = depositor,
-        space = 8 + 32 + 32 + 8 + 8 + 8 + 1 + 1,
-        seeds = [b"deposit", bridge_state.deposit_nonce.to_le_bytes().as_ref()],
+        payer = executor,
+        space = 8 + 32 + 32 + 8 + 1 + 8 + 1,
+        seeds = [b"withdrawal", withdrawal_hash.as_ref()],
         bump
     )]
-    pub deposit: Account<'info, Deposit>,
+    pub withdrawal: Account<'info, Withdrawal>,
     
     /// CHECK: SOL vault PDA
     #[account(
@@ -447,15 +3116,60 @@ pub struct DepositSol<'info> {
     )]
     pub vault: UncheckedAccount<'info>,
     
+    /// CHECK: Must match the recipient the guardians signed for
+    #[account(
+        mut,
+        address = queued_withdrawal.recipient @ BridgeError::WithdrawalMismatch
+    )]
+    pub recipient: UncheckedAccount<'info>,
+    
+    /// CHECK: Receives the withdrawal fee
+    #[account(
+        mut,
+        address = bridge_state.fee_collector @ BridgeError::FeeCollectorMismatch
+    )]
+    pub fee_collector: UncheckedAccount<'info>,
+    
+    /// CHECK: Refunded the queue entry's rent
     #[account(mut)]
-    pub depositor: Signer<'info>,
+    pub payer: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub executor: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(amount: u64, commitment: [u8; 32])]
-pub struct DepositToken<'info> {
+#[instruction(withdrawal_hash: [u8; 32])]
+pub struct CancelWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"queued_withdrawal", withdrawal_hash.as_ref()],
+        bump = queued_withdrawal.bump,
+        has_one = payer @ BridgeError::PayerMismatch
+    )]
+    pub queued_withdrawal: Account<'info, QueuedWithdrawal>,
+    
+    /// CHECK: Refunded the queue entry's rent
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(withdrawal_hash: [u8; 32])]
+pub struct ReapWithdrawal<'info> {
     #[account(
         mut,
         seeds = [b"bridge_state"],
@@ -464,71 +3178,251 @@ pub struct DepositToken<'info> {
     pub bridge_state: Account<'info, BridgeState>,
     
     #[account(
-        init,
-        payer = depositor,
-        space = 8 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1,
-        seeds = [b"token_deposit", bridge_state.deposit_nonce.to_le_bytes().as_ref()],
-        bump
+        mut,
+        close = payer,
+        seeds = [b"queued_withdrawal", withdrawal_hash.as_ref()],
+        bump = queued_withdrawal.bump,
+        has_one = payer @ BridgeError::PayerMismatch
     )]
-    pub token_deposit: Account<'info, TokenDeposit>,
+    pub queued_withdrawal: Account<'info, QueuedWithdrawal>,
     
-    /// CHECK: Token mint
-    pub mint: UncheckedAccount<'info>,
+    /// CHECK: Refunded the queue entry's rent
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyDrain<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority,
+        has_one = emergency_recipient @ BridgeError::EmergencyRecipientMismatch
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
     
+    /// CHECK: SOL vault PDA
     #[account(
         mut,
-        constraint = depositor_token_account.owner == depositor.key()
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+    
+    /// CHECK: Must be the configured cold wallet
+    #[account(mut)]
+    pub emergency_recipient: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureToken<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = TOKEN_CONFIG_SPACE,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
     )]
-    pub depositor_token_account: Account<'info, TokenAccount>,
+    pub token_config: Account<'info, TokenConfig>,
     
     #[account(
         mut,
         seeds = [b"vault_token", mint.key().as_ref()],
-        bump
+        bump,
+        token::mint = mint,
+        token::authority = bridge_state,
+        token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = bridge_state.fee_collector,
+        token::token_program = token_program
+    )]
+    pub collector_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    /// CHECK: Read by hand, since an old layout is shorter than BridgeState
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump,
+        owner = crate::ID
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub bridge_state: UncheckedAccount<'info>,
     
     #[account(mut)]
-    pub depositor: Signer<'info>,
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RotateGuardianSet<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(withdrawal_hash: [u8; 32])]
-pub struct ProcessWithdrawal<'info> {
+pub struct DumpGuardians<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+}
+
+#[derive(Accounts)]
+pub struct BenchmarkVerification<'info> {
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GuardianPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+    
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestReserves<'info> {
+    #[account(
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+
+    /// CHECK: SOL vault PDA, read for its balance
+    #[account(
+        seeds = [b"vault"],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 verifications
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(guardian_pubkey: Pubkey)]
+pub struct SetGuardianWeight<'info> {
     #[account(
         mut,
         seeds = [b"bridge_state"],
-        bump = bridge_state.bump
+        bump = bridge_state.bump,
+        has_one = authority
     )]
     pub bridge_state: Account<'info, BridgeState>,
     
     #[account(
-        init,
-        payer = payer,
-        space = 8 + 32 + 32 + 8 + 1 + 8 + 1,
-        seeds = [b"withdrawal", withdrawal_hash.as_ref()],
+        mut,
+        seeds = [b"guardian", guardian_pubkey.as_ref()],
         bump
     )]
-    pub withdrawal: Account<'info, Withdrawal>,
+    pub guardian: Account<'info, Guardian>,
     
-    /// CHECK: SOL vault PDA
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CompactWithdrawals<'info> {
     #[account(
         mut,
-        seeds = [b"vault"],
-        bump
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
     )]
-    pub vault: UncheckedAccount<'info>,
+    pub bridge_state: Account<'info, BridgeState>,
     
-    /// CHECK: Withdrawal recipient
     #[account(mut)]
-    pub recipient: UncheckedAccount<'info>,
-    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump = bridge_state.bump,
+        has_one = authority
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+
+    /// CHECK: Read by hand, since an old layout is shorter than the current one
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+
     #[account(mut)]
-    pub payer: Signer<'info>,
-    
+    pub authority: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -545,6 +3439,543 @@ pub struct AdminAction<'info> {
     pub authority: Signer<'info>,
 }
 
+// ============ Amounts ============
+
+/// Scale a token amount to CANONICAL_DECIMALS
+/// Mints with more decimals than canonical are only accepted when the
+/// conversion is exact, so no value is silently truncated.
+pub fn normalize_amount(amount: u64, decimals: u8) -> Result<u128> {
+    if decimals <= CANONICAL_DECIMALS {
+        let scale = 10u128.pow((CANONICAL_DECIMALS - decimals) as u32);
+        return Ok(amount as u128 * scale);
+    }
+    let scale = 10u128
+        .checked_pow((decimals - CANONICAL_DECIMALS) as u32)
+        .ok_or(BridgeError::PrecisionLoss)?;
+    let normalized = amount as u128 / scale;
+    require!(normalized * scale == amount as u128, BridgeError::PrecisionLoss);
+    Ok(normalized)
+}
+
+/// Protocol fee on a withdrawal of `amount` at `fee_bps`, rounded down
+pub fn withdrawal_fee(amount: u64, fee_bps: u16) -> u64 {
+    (amount as u128 * fee_bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+// ============ Withdrawal Bloom ============
+
+/// Bit positions a withdrawal hash sets in the withdrawal bloom filter
+fn withdrawal_bloom_bits(withdrawal_hash: &[u8; 32]) -> [usize; WITHDRAWAL_BLOOM_HASHES] {
+    let digest = hashv(&[b"cashio-withdrawal-bloom".as_ref(), withdrawal_hash]).to_bytes();
+    let mut bits = [0usize; WITHDRAWAL_BLOOM_HASHES];
+    for (i, bit) in bits.iter_mut().enumerate() {
+        let word = u32::from_le_bytes(digest[i * 4..i * 4 + 4].try_into().unwrap());
+        *bit = word as usize % WITHDRAWAL_BLOOM_BITS;
+    }
+    bits
+}
+
+/// Whether `withdrawal_hash` may have been compacted
+/// False positives are possible; false negatives are not.
+pub fn withdrawal_bloom_contains(bloom: &[u128; 32], withdrawal_hash: &[u8; 32]) -> bool {
+    withdrawal_bloom_bits(withdrawal_hash)
+        .iter()
+        .all(|bit| bloom[bit / 128] & (1u128 << (bit % 128)) != 0)
+}
+
+/// Reject a withdrawal whose hash is in the bloom filter, unless the
+/// guardians approved it as a false positive
+fn check_not_compacted(bridge: &BridgeState, withdrawal_hash: &[u8; 32]) -> Result<()> {
+    require!(
+        !withdrawal_bloom_contains(&bridge.withdrawal_bloom, withdrawal_hash)
+            || (*withdrawal_hash != [0; 32] && bridge.bloom_overrides.contains(withdrawal_hash)),
+        BridgeError::WithdrawalAlreadyProcessed
+    );
+    Ok(())
+}
+
+/// Clear a used bloom override; the new withdrawal marker guards against
+/// replays from here on
+fn consume_bloom_override(bridge: &mut BridgeState, withdrawal_hash: &[u8; 32]) {
+    if let Some(slot) = bridge
+        .bloom_overrides
+        .iter_mut()
+        .find(|slot| *slot == withdrawal_hash)
+    {
+        *slot = [0; 32];
+    }
+}
+
+fn withdrawal_bloom_insert(bloom: &mut [u128; 32], withdrawal_hash: &[u8; 32]) {
+    for bit in withdrawal_bloom_bits(withdrawal_hash) {
+        bloom[bit / 128] |= 1u128 << (bit % 128);
+    }
+}
+
+// ============ Vault ============
+
+/// Require a withdrawal of `amount` to leave `min_liquidity_buffer` in the
+/// vault above its rent-exempt minimum
+fn check_liquidity_buffer(bridge: &BridgeState, vault: &UncheckedAccount, amount: u64) -> Result<()> {
+    let remaining = vault
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(BridgeError::InsufficientVaultBalance)?;
+    require!(
+        remaining.saturating_sub(Rent::get()?.minimum_balance(0)) >= bridge.min_liquidity_buffer,
+        BridgeError::LiquidityBufferBreached
+    );
+    Ok(())
+}
+
+/// Pay a guardian-authorized withdrawal out of the vault, splitting off the
+/// protocol fee, and return the fee taken. Every SOL withdrawal path goes through here so the
+/// liquidity buffer holds regardless of how the withdrawal was authorized.
+fn pay_out_withdrawal<'info>(
+    bridge: &BridgeState,
+    vault: &UncheckedAccount<'info>,
+    recipient: &UncheckedAccount<'info>,
+    fee_collector: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    vault_bump: u8,
+    amount: u64,
+) -> Result<u64> {
+    check_liquidity_buffer(bridge, vault, amount)?;
+    let fee = withdrawal_fee(amount, bridge.withdrawal_fee_bps);
+    transfer_from_vault(vault, recipient, system_program, vault_bump, amount - fee)?;
+    if fee > 0 {
+        transfer_from_vault(vault, fee_collector, system_program, vault_bump, fee)?;
+    }
+    Ok(fee)
+}
+
+/// Pay out lamports from the system-owned vault PDA
+/// The vault is never drawn below its rent-exempt minimum.
+fn transfer_from_vault<'info>(
+    vault: &UncheckedAccount<'info>,
+    recipient: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    vault_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let remaining = vault
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(BridgeError::InsufficientVaultBalance)?;
+    require!(
+        remaining >= Rent::get()?.minimum_balance(0),
+        BridgeError::InsufficientVaultBalance
+    );
+
+    let seeds: &[&[u8]] = &[b"vault", &[vault_bump]];
+    let signer_seeds = &[seeds];
+    let cpi_context = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        anchor_lang::system_program::Transfer {
+            from: vault.to_account_info(),
+            to: recipient.to_account_info(),
+        },
+        signer_seeds,
+    );
+    anchor_lang::system_program::transfer(cpi_context, amount)
+}
+
+/// Resize a program account to `space`, topping up its rent from `payer`.
+/// Returns false, leaving the account untouched, if it is already that size.
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+) -> Result<bool> {
+    if info.data_len() >= space {
+        return Ok(false);
+    }
+    let rent_exempt = Rent::get()?.minimum_balance(space);
+    let shortfall = rent_exempt.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        let cpi_context = CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: info.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, shortfall)?;
+    }
+    info.resize(space)?;
+    Ok(true)
+}
+
+// ============ Signature Verification ============
+
+/// Digest guardians sign to authorize a withdrawal
+pub fn withdrawal_message(
+    bridge: &BridgeState,
+    withdrawal_hash: &[u8; 32],
+    recipient: &Pubkey,
+    amount: u64,
+) -> [u8; 32] {
+    hashv(&[
+        b"cashio-withdrawal".as_ref(),
+        &bridge.domain_tag,
+        &bridge.hub_chain_id.to_le_bytes(),
+        withdrawal_hash,
+        recipient.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Digest guardians sign to authorize a token withdrawal, binding the mint
+/// and the exact token account credited
+pub fn token_withdrawal_message(
+    bridge: &BridgeState,
+    withdrawal_hash: &[u8; 32],
+    mint: &Pubkey,
+    recipient_token_account: &Pubkey,
+    amount: u64,
+) -> [u8; 32] {
+    hashv(&[
+        b"cashio-token-withdrawal".as_ref(),
+        &bridge.domain_tag,
+        &bridge.hub_chain_id.to_le_bytes(),
+        withdrawal_hash,
+        mint.as_ref(),
+        recipient_token_account.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Digest guardians sign to confirm the hub received deposit `nonce`
+pub fn deposit_ack_message(bridge: &BridgeState, nonce: u64, commitment: &[u8; 32]) -> [u8; 32] {
+    hashv(&[
+        b"cashio-deposit-ack".as_ref(),
+        &bridge.domain_tag,
+        &bridge.hub_chain_id.to_le_bytes(),
+        &bridge.guardian_set_epoch.to_le_bytes(),
+        &nonce.to_le_bytes(),
+        commitment,
+    ])
+    .to_bytes()
+}
+
+/// Digest guardians sign to commit a hub block hash at `height`
+pub fn hub_block_message(
+    bridge: &BridgeState,
+    block_hash: &[u8; 32],
+    height: u64,
+    valid_until: i64,
+) -> [u8; 32] {
+    hashv(&[
+        b"cashio-hub-block".as_ref(),
+        &bridge.domain_tag,
+        &bridge.hub_chain_id.to_le_bytes(),
+        &bridge.guardian_set_epoch.to_le_bytes(),
+        block_hash,
+        &height.to_le_bytes(),
+        &valid_until.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Digest guardians sign once to approve every withdrawal under `root`
+pub fn batch_message(bridge: &BridgeState, root: &[u8; 32], valid_until: i64) -> [u8; 32] {
+    hashv(&[
+        b"cashio-withdrawal-batch".as_ref(),
+        &bridge.domain_tag,
+        &bridge.hub_chain_id.to_le_bytes(),
+        &bridge.guardian_set_epoch.to_le_bytes(),
+        root,
+        &valid_until.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Digest guardians sign to let `withdrawal_hash` past a bloom filter
+/// false positive
+pub fn bloom_override_message(
+    bridge: &BridgeState,
+    withdrawal_hash: &[u8; 32],
+    valid_until: i64,
+) -> [u8; 32] {
+    hashv(&[
+        b"cashio-bloom-override".as_ref(),
+        &bridge.domain_tag,
+        &bridge.hub_chain_id.to_le_bytes(),
+        &bridge.guardian_set_epoch.to_le_bytes(),
+        withdrawal_hash,
+        &valid_until.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Batch tree leaf for a single withdrawal, bound to the hub heights and
+/// source block it was approved at
+pub fn batch_leaf(
+    withdrawal_hash: &[u8; 32],
+    recipient: &Pubkey,
+    amount: u64,
+    source_height: u64,
+    source_block_hash: &[u8; 32],
+    current_height: u64,
+) -> [u8; 32] {
+    hashv(&[
+        b"cashio-batch-leaf".as_ref(),
+        withdrawal_hash,
+        recipient.as_ref(),
+        &amount.to_le_bytes(),
+        &source_height.to_le_bytes(),
+        source_block_hash,
+        &current_height.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Hash two batch tree nodes in sorted order, so proofs need no direction bits
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[left, right]).to_bytes()
+}
+
+/// Require, when the bridge is configured for it, that the source event sits
+/// in a hub block guardians committed, matching both its signed height and
+/// signed hash
+fn check_hub_block(
+    bridge: &BridgeState,
+    hub_block: Option<&HubBlock>,
+    source_height: u64,
+    source_block_hash: &[u8; 32],
+) -> Result<()> {
+    if bridge.require_hub_block {
+        let hub_block = hub_block.ok_or(BridgeError::UnknownHubBlock)?;
+        require!(
+            hub_block.height == source_height && hub_block.block_hash == *source_block_hash,
+            BridgeError::HubBlockMismatch
+        );
+    }
+    Ok(())
+}
+
+/// Require the hub heights a withdrawal was signed at to be at least
+/// `min_confirmations` apart
+fn check_confirmations(bridge: &BridgeState, source_height: u64, current_height: u64) -> Result<()> {
+    let confirmations = current_height
+        .checked_sub(source_height)
+        .ok_or(BridgeError::InsufficientConfirmations)?;
+    require!(
+        confirmations >= bridge.min_confirmations,
+        BridgeError::InsufficientConfirmations
+    );
+    Ok(())
+}
+
+/// Withdrawal digest bound to the hub heights guardians observed, the hub
+/// block holding the source event and the time the signatures expire
+pub fn confirmed_withdrawal_message(
+    message: &[u8; 32],
+    source_height: u64,
+    source_block_hash: &[u8; 32],
+    current_height: u64,
+    valid_until: i64,
+) -> [u8; 32] {
+    hashv(&[
+        message.as_ref(),
+        &source_height.to_le_bytes(),
+        source_block_hash,
+        &current_height.to_le_bytes(),
+        &valid_until.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Digest guardians sign to pause the bridge without the authority
+/// Bound to the guardian set epoch so a rotated-out set can't reuse it.
+pub fn pause_message(bridge: &BridgeState, valid_until: i64) -> [u8; 32] {
+    hashv(&[
+        b"cashio-pause".as_ref(),
+        &bridge.domain_tag,
+        &bridge.hub_chain_id.to_le_bytes(),
+        &bridge.guardian_set_epoch.to_le_bytes(),
+        &valid_until.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Digest guardians sign to attest the bridge's reserves at `timestamp`
+pub fn reserves_message(
+    bridge: &BridgeState,
+    vault_balance: u64,
+    total_deposited: u64,
+    total_withdrawn: u64,
+    timestamp: i64,
+) -> [u8; 32] {
+    hashv(&[
+        b"cashio-reserves".as_ref(),
+        &bridge.domain_tag,
+        &bridge.hub_chain_id.to_le_bytes(),
+        &bridge.guardian_set_epoch.to_le_bytes(),
+        &vault_balance.to_le_bytes(),
+        &total_deposited.to_le_bytes(),
+        &total_withdrawn.to_le_bytes(),
+        &timestamp.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Require threshold guardian signatures confirming the hub received
+/// deposit `nonce` with `commitment`, returning the number of signers
+fn verify_deposit_ack<'info>(
+    bridge: &BridgeState,
+    guardian_accounts: &'info [AccountInfo<'info>],
+    instructions: &AccountInfo,
+    nonce: u64,
+    commitment: &[u8; 32],
+    signatures: &[[u8; 64]],
+) -> Result<u32> {
+    require!(
+        guardian_accounts.len() == signatures.len(),
+        BridgeError::GuardianAccountsMismatch
+    );
+    let (signers, weight) = count_guardian_signers(
+        guardian_accounts,
+        instructions,
+        &deposit_ack_message(bridge, nonce, commitment),
+        signatures,
+    )?;
+    require!(
+        signers >= 1 && quorum_reached(bridge, signers, weight),
+        BridgeError::InsufficientSignatures
+    );
+    Ok(signers)
+}
+
+/// Count distinct active guardians among `guardian_accounts` whose paired
+/// signature over `message` was verified by an Ed25519 instruction, and
+/// their summed weight.
+/// Any provided signature that fails verification aborts with
+/// InvalidSignature; inactive or repeated guardians simply don't count.
+fn count_guardian_signers<'info>(
+    guardian_accounts: &'info [AccountInfo<'info>],
+    instructions: &AccountInfo,
+    message: &[u8; 32],
+    signatures: &[[u8; 64]],
+) -> Result<(u32, u32)> {
+    let mut signer_bitmap = 0u64;
+    let mut weight = 0u32;
+    for (info, signature) in guardian_accounts.iter().zip(signatures) {
+        let guardian = Account::<Guardian>::try_from(info)?;
+        verify_ed25519_signature(instructions, &guardian.pubkey, message, signature)?;
+        let bit = 1u64 << guardian.index;
+        if guardian.is_active && signer_bitmap & bit == 0 {
+            signer_bitmap |= bit;
+            weight += guardian_weight(&guardian);
+        }
+    }
+    Ok((signer_bitmap.count_ones(), weight))
+}
+
+/// Reject a guardian change within `min_guardian_change_interval` of the
+/// previous one, logging the interval being enforced
+fn check_guardian_change_interval(bridge: &BridgeState, now: i64) -> Result<()> {
+    let interval = bridge.min_guardian_change_interval;
+    if now < bridge.last_guardian_change.saturating_add(interval) {
+        msg!("Guardian changes must be at least {} seconds apart", interval);
+        return err!(BridgeError::GuardianChangeTooSoon);
+    }
+    Ok(())
+}
+
+/// Voting weight of a guardian; accounts from before weights count as 1
+fn guardian_weight(guardian: &Guardian) -> u32 {
+    guardian.weight.max(1) as u32
+}
+
+/// Whether `signers` guardians of summed `weight` authorize an action:
+/// by weight once a weight threshold is set, else by count
+pub fn quorum_reached(bridge: &BridgeState, signers: u32, weight: u32) -> bool {
+    if bridge.weight_threshold > 0 {
+        weight >= bridge.weight_threshold
+    } else {
+        signers >= bridge.guardian_threshold as u32
+    }
+}
+
+const ED25519_HEADER_SIZE: usize = 2;
+const ED25519_OFFSETS_SIZE: usize = 14;
+/// Order of the Ed25519 base point, little-endian
+const ED25519_ORDER: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+];
+
+/// Check that an Ed25519 program instruction earlier in this transaction
+/// verified `signature` by `signer` over `message`. The native program
+/// fails the whole transaction on a bad signature, so finding a matching
+/// entry is proof of validity. Non-canonical encodings are rejected so a
+/// guardian's signature has exactly one accepted form.
+fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+    signature: &[u8; 64],
+) -> Result<()> {
+    require!(is_canonical_signature(signature), BridgeError::NonCanonicalSignature);
+    let current = ix_sysvar::load_current_index_checked(instructions)?;
+    for index in 0..current {
+        let ix = ix_sysvar::load_instruction_at_checked(index as usize, instructions)?;
+        if ix.program_id != ed25519_program::ID {
+            continue;
+        }
+        if ed25519_instruction_contains(&ix.data, signer, message, signature) {
+            return Ok(());
+        }
+    }
+    err!(BridgeError::InvalidSignature)
+}
+
+/// Whether the signature's S scalar is fully reduced below the group
+/// order; S + L verifies identically under lenient verifiers
+fn is_canonical_signature(signature: &[u8; 64]) -> bool {
+    let scalar = &signature[32..];
+    for i in (0..32).rev() {
+        if scalar[i] != ED25519_ORDER[i] {
+            return scalar[i] < ED25519_ORDER[i];
+        }
+    }
+    false
+}
+
+fn ed25519_instruction_contains(
+    data: &[u8],
+    signer: &Pubkey,
+    message: &[u8],
+    signature: &[u8; 64],
+) -> bool {
+    let read_u16 = |at: usize| -> Option<usize> {
+        data.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let slice = |offset: usize, len: usize| data.get(offset..offset + len);
+
+    let count = match data.first() {
+        Some(count) => *count as usize,
+        None => return false,
+    };
+    (0..count).any(|i| {
+        let start = ED25519_HEADER_SIZE + i * ED25519_OFFSETS_SIZE;
+        let fields: Option<Vec<usize>> = (0..7).map(|f| read_u16(start + f * 2)).collect();
+        let Some(fields) = fields else { return false };
+        // Only accept entries whose data lives inside the Ed25519 instruction itself
+        if fields[1] != u16::MAX as usize || fields[3] != u16::MAX as usize || fields[6] != u16::MAX as usize {
+            return false;
+        }
+        slice(fields[0], 64) == Some(&signature[..])
+            && slice(fields[2], 32) == Some(signer.as_ref())
+            && slice(fields[4], fields[5]) == Some(message)
+    })
+}
+
 // ============ Events ============
 
 #[event]
@@ -553,6 +3984,15 @@ pub struct DepositEvent {
     pub commitment: [u8; 32],
     pub amount: u64,
     pub nonce: u64,
+    pub pool_id: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositClosed {
+    pub nonce: u64,
+    pub depositor: Pubkey,
+    pub rent_returned: u64,
     pub timestamp: i64,
 }
 
@@ -562,7 +4002,32 @@ pub struct TokenDepositEvent {
     pub mint: Pubkey,
     pub commitment: [u8; 32],
     pub amount: u64,
+    pub normalized_amount: u128,
+    pub nonce: u64,
+    pub pool_id: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DepositAcknowledged {
     pub nonce: u64,
+    pub commitment: [u8; 32],
+    pub signers: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HubBlockCommitted {
+    pub block_hash: [u8; 32],
+    pub height: u64,
+    pub signers: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalBatchCommitted {
+    pub root: [u8; 32],
+    pub signers: u32,
     pub timestamp: i64,
 }
 
@@ -571,6 +4036,7 @@ pub struct WithdrawalEvent {
     pub withdrawal_hash: [u8; 32],
     pub recipient: Pubkey,
     pub amount: u64,
+    pub fee: u64,
     pub timestamp: i64,
 }
 
@@ -588,6 +4054,161 @@ pub struct GuardianRemoved {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct GuardianSetRotated {
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub epoch: u64,
+    pub rotated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GuardianWeightUpdated {
+    pub guardian: Pubkey,
+    pub weight: u16,
+    pub total_weight: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GuardianSetDump {
+    pub guardians: Vec<Pubkey>,
+    pub threshold: u8,
+    pub epoch: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GuardianMetadataUpdated {
+    pub guardian: Pubkey,
+    pub name: [u8; 32],
+    pub endpoint_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VaultFunded {
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SignatureSubmitted {
+    pub withdrawal_hash: [u8; 32],
+    pub guardian: Pubkey,
+    pub signature_count: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalFeeUpdated {
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+    pub fee_collector: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyDrained {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalQueued {
+    pub withdrawal_hash: [u8; 32],
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub ready_at: i64,
+}
+
+#[event]
+pub struct WithdrawalCancelled {
+    pub withdrawal_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NonceReserved {
+    pub depositor: Pubkey,
+    pub nonce: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct GuardianPaused {
+    pub signers: u32,
+    pub submitted_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReserveAttestation {
+    pub vault_balance: u64,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    pub signers: u32,
+    pub epoch: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WithdrawalsCompacted {
+    pub count: u64,
+    pub total_compacted: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BloomOverrideApproved {
+    pub withdrawal_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenPauseUpdated {
+    pub mint: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenWithdrawalEvent {
+    pub withdrawal_hash: [u8; 32],
+    pub mint: Pubkey,
+    pub recipient_token_account: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenConfigUpdated {
+    pub mint: Pubkey,
+    pub min: u64,
+    pub max: u64,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DustSwept {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub collector_token_account: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenPoolUpdated {
+    pub mint: Pubkey,
+    pub pool_id: u8,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct ThresholdUpdated {
     pub old_threshold: u8,
@@ -617,4 +4238,102 @@ pub enum BridgeError {
     ThresholdTooHigh,
     #[msg("Invalid signature")]
     InvalidSignature,
+    #[msg("Guardian is already active")]
+    GuardianAlreadyActive,
+    #[msg("Maximum number of guardians reached")]
+    TooManyGuardians,
+    #[msg("Withdrawal parameters do not match the signed request")]
+    WithdrawalMismatch,
+    #[msg("Grace period cannot be negative")]
+    InvalidGracePeriod,
+    #[msg("Vault balance would fall below the rent-exempt minimum")]
+    InsufficientVaultBalance,
+    #[msg("Amount cannot be normalized without losing precision")]
+    PrecisionLoss,
+    #[msg("Account does not match the deposit's depositor")]
+    DepositorMismatch,
+    #[msg("Withdrawal fee exceeds the maximum")]
+    FeeTooHigh,
+    #[msg("Account does not match the configured fee collector")]
+    FeeCollectorMismatch,
+    #[msg("Expected one guardian account per signature")]
+    GuardianAccountsMismatch,
+    #[msg("Source event does not have enough hub chain confirmations")]
+    InsufficientConfirmations,
+    #[msg("Withdrawal amount is below the minimum")]
+    WithdrawalTooSmall,
+    #[msg("Bridge must be paused")]
+    BridgeNotPaused,
+    #[msg("Account does not match the configured emergency recipient")]
+    EmergencyRecipientMismatch,
+    #[msg("No pending change to apply")]
+    NoPendingChange,
+    #[msg("Timelock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Withdrawal delay cannot be negative")]
+    InvalidWithdrawalDelay,
+    #[msg("Too many withdrawals are already queued")]
+    WithdrawalQueueFull,
+    #[msg("Queued withdrawal has not expired")]
+    WithdrawalNotExpired,
+    #[msg("Account does not match the queue entry's payer")]
+    PayerMismatch,
+    #[msg("Guardian signatures have expired")]
+    SignatureExpired,
+    #[msg("Signer is not the bridge authority")]
+    Unauthorized,
+    #[msg("Vault received no tokens")]
+    NothingReceived,
+    #[msg("Deposits and withdrawals of this token are paused")]
+    TokenPaused,
+    #[msg("Nonce reservation has expired")]
+    ReservationExpired,
+    #[msg("Threshold must be between 1 and the guardian count")]
+    InvalidThreshold,
+    #[msg("Signatures were collected under a previous guardian set")]
+    StaleGuardianSet,
+    #[msg("Pool id is reserved for native SOL")]
+    InvalidPoolId,
+    #[msg("Withdrawal marker is still within the retention period")]
+    WithdrawalRetained,
+    #[msg("Withdrawal retention cannot be negative")]
+    InvalidRetention,
+    #[msg("Withdrawal bloom filter has reached its compaction cap")]
+    WithdrawalBloomFull,
+    #[msg("Withdrawal hash is not blocked by the bloom filter")]
+    BloomOverrideNotNeeded,
+    #[msg("Too many pending bloom overrides")]
+    BloomOverridesFull,
+    #[msg("Token program is not allowed for deposits")]
+    TokenProgramNotAllowed,
+    #[msg("Guardian weight must be at least 1")]
+    InvalidWeight,
+    #[msg("Signature is not canonically encoded")]
+    NonCanonicalSignature,
+    #[msg("Attested reserves do not match the bridge")]
+    ReserveMismatch,
+    #[msg("Minimum deposit must be below the maximum")]
+    InvalidDepositLimits,
+    #[msg("Invalid batch inclusion proof")]
+    InvalidBatchProof,
+    #[msg("Withdrawal references an uncommitted hub block")]
+    UnknownHubBlock,
+    #[msg("Hub block does not match the signed source block")]
+    HubBlockMismatch,
+    #[msg("Vault holds nothing above its deposit liability")]
+    NothingToSweep,
+    #[msg("Deposit already acknowledged")]
+    DepositAlreadyAcknowledged,
+    #[msg("Deposit has not been acknowledged")]
+    DepositNotAcknowledged,
+    #[msg("Guardian set changed too recently")]
+    GuardianChangeTooSoon,
+    #[msg("Guardian change interval cannot be negative")]
+    InvalidGuardianChangeInterval,
+    #[msg("Withdrawal would draw the vault into its liquidity buffer")]
+    LiquidityBufferBreached,
+    #[msg("Mint's deposit liability predates tracking and must be backfilled")]
+    LiabilityNotTracked,
+    #[msg("Mint's deposit liability is already tracked")]
+    LiabilityAlreadyTracked,
 }
@@ -1,7 +1,29 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
-import { PublicKey, Keypair, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+  Ed25519Program,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Transaction,
+} from "@solana/web3.js";
 import { expect } from "chai";
+import { createHash } from "crypto";
+import nacl from "tweetnacl";
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  getMintLen,
+  createMint,
+  createAccount,
+  getAccount,
+  mintTo,
+} from "@solana/spl-token";
 import { CashioBridge } from "../target/types/cashio_bridge";
 
 describe("cashio-bridge", () => {
@@ -18,6 +40,185 @@ describe("cashio-bridge", () => {
   
   const HUB_CHAIN_ID = new anchor.BN(999888777);
   const GUARDIAN_THRESHOLD = 1;
+  const coldWallet = Keypair.generate().publicKey;
+  const DOMAIN_TAG = Buffer.alloc(16);
+  DOMAIN_TAG.write("cashio-localnet");
+  // Expiry of incrementally collected signatures; shared so every
+  // submission to one collection signs the same digest
+  const COLLECTION_VALID_UNTIL = new anchor.BN(Math.floor(Date.now() / 1000) + 24 * 60 * 60);
+  // Hub block hash signed with withdrawals that don't reference a
+  // committed hub block
+  const SOURCE_BLOCK_HASH = Buffer.alloc(32);
+
+  // Digest guardians sign to authorize a withdrawal
+  const withdrawalMessage = (
+    withdrawalHash: Buffer,
+    recipient: PublicKey,
+    amount: anchor.BN,
+    domainTag: Buffer = DOMAIN_TAG
+  ): Buffer =>
+    createHash("sha256")
+      .update(Buffer.from("cashio-withdrawal"))
+      .update(domainTag)
+      .update(HUB_CHAIN_ID.toArrayLike(Buffer, "le", 8))
+      .update(withdrawalHash)
+      .update(recipient.toBuffer())
+      .update(amount.toArrayLike(Buffer, "le", 8))
+      .digest();
+
+  // Withdrawal digest bound to the observed hub heights, source block and
+  // expiry
+  const confirmedWithdrawalMessage = (
+    withdrawalHash: Buffer,
+    recipient: PublicKey,
+    amount: anchor.BN,
+    sourceHeight: anchor.BN,
+    currentHeight: anchor.BN,
+    validUntil: anchor.BN,
+    domainTag: Buffer = DOMAIN_TAG,
+    sourceBlockHash: Buffer = SOURCE_BLOCK_HASH
+  ): Buffer =>
+    createHash("sha256")
+      .update(withdrawalMessage(withdrawalHash, recipient, amount, domainTag))
+      .update(sourceHeight.toArrayLike(Buffer, "le", 8))
+      .update(sourceBlockHash)
+      .update(currentHeight.toArrayLike(Buffer, "le", 8))
+      .update(validUntil.toTwos(64).toArrayLike(Buffer, "le", 8))
+      .digest();
+
+  const addGuardian = async (guardian: Keypair): Promise<PublicKey> => {
+    const [guardianPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("guardian"), guardian.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .addGuardian(guardian.publicKey)
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        guardian: guardianPDA,
+        authority: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    return guardianPDA;
+  };
+
+  const removeGuardian = async (guardian: Keypair) => {
+    const [guardianPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("guardian"), guardian.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .removeGuardian(guardian.publicKey)
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        guardian: guardianPDA,
+        authority: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  };
+
+  const guardianAddress = (guardian: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("guardian"), guardian.toBuffer()],
+      program.programId
+    )[0];
+
+  // Replace every active guardian with `guardians`
+  // Guardian accounts, skipping legacy layouts that can't be decoded until
+  // they are migrated
+  const currentLayoutGuardians = () =>
+    program.account.guardian.all([{ dataSize: program.account.guardian.size }]);
+
+  const rotateGuardianSet = async (guardians: Keypair[], threshold: number) => {
+    const current = (await currentLayoutGuardians()).filter((g) => g.account.isActive);
+    const incoming = guardians.map((guardian) => guardianAddress(guardian.publicKey));
+    await program.methods
+      .rotateGuardianSet(
+        guardians.map((guardian) => guardian.publicKey),
+        threshold
+      )
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        authority: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        [...current.map((g) => g.publicKey), ...incoming].map((pubkey) => ({
+          pubkey,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .rpc();
+  };
+
+  // Submit a guardian signature, verified through the Ed25519 program
+  const submitSignature = (
+    guardian: Keypair,
+    withdrawalHash: Buffer,
+    recipient: PublicKey,
+    amount: anchor.BN,
+    withEd25519 = true,
+    signatureOverride?: Uint8Array,
+    sourceHeight = new anchor.BN(100),
+    currentHeight = new anchor.BN(200),
+    validUntil = COLLECTION_VALID_UNTIL,
+    sourceBlockHash: Buffer = SOURCE_BLOCK_HASH
+  ) => {
+    const message = confirmedWithdrawalMessage(
+      withdrawalHash,
+      recipient,
+      amount,
+      sourceHeight,
+      currentHeight,
+      validUntil,
+      DOMAIN_TAG,
+      sourceBlockHash
+    );
+    const signature = signatureOverride ?? nacl.sign.detached(message, guardian.secretKey);
+    const [guardianPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("guardian"), guardian.publicKey.toBuffer()],
+      program.programId
+    );
+    const [collectionPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("signatures"), withdrawalHash],
+      program.programId
+    );
+    return program.methods
+      .submitSignature(
+        Array.from(withdrawalHash),
+        {
+          recipient,
+          amount,
+          sourceHeight,
+          sourceBlockHash: Array.from(sourceBlockHash),
+          currentHeight,
+          validUntil,
+        },
+        Array.from(signature)
+      )
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        signatureCollection: collectionPDA,
+        guardian: guardianPDA,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions(
+        withEd25519
+          ? [
+              Ed25519Program.createInstructionWithPublicKey({
+                publicKey: guardian.publicKey.toBytes(),
+                message,
+                signature,
+              }),
+            ]
+          : []
+      );
+  };
 
   before(async () => {
     // Derive PDAs
@@ -34,7 +235,7 @@ describe("cashio-bridge", () => {
 
   it("Initializes the bridge", async () => {
     const tx = await program.methods
-      .initialize(HUB_CHAIN_ID, GUARDIAN_THRESHOLD)
+      .initialize(HUB_CHAIN_ID, GUARDIAN_THRESHOLD, coldWallet, Array.from(DOMAIN_TAG))
       .accounts({
         bridgeState: bridgeStatePDA,
         vault: vaultPDA,
@@ -52,8 +253,263 @@ describe("cashio-bridge", () => {
     expect(bridgeState.guardianThreshold).to.equal(GUARDIAN_THRESHOLD);
     expect(bridgeState.isPaused).to.be.false;
     expect(bridgeState.depositNonce.toString()).to.equal("0");
+    expect(bridgeState.emergencyRecipient.toString()).to.equal(coldWallet.toString());
+    expect(Buffer.from(bridgeState.domainTag).equals(DOMAIN_TAG)).to.be.true;
   });
 
+  const finalizeWithdrawal = (
+    withdrawalHash: Buffer,
+    recipient: PublicKey,
+    feeCollector: PublicKey = authority,
+    hubBlock: PublicKey | null = null
+  ) => {
+    const [collectionPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("signatures"), withdrawalHash],
+      program.programId
+    );
+    const [withdrawalPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("withdrawal"), withdrawalHash],
+      program.programId
+    );
+    return program.methods
+      .finalizeWithdrawal(Array.from(withdrawalHash))
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        signatureCollection: collectionPDA,
+        withdrawal: withdrawalPDA,
+        vault: vaultPDA,
+        recipient,
+        feeCollector,
+        hubBlock,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      });
+  };
+
+  // Withdraw in one transaction, verifying every guardian signature
+  // through the Ed25519 program unless `withEd25519` is false
+  const processWithdrawal = (
+    guardians: Keypair[],
+    withdrawalHash: Buffer,
+    recipient: PublicKey,
+    amount: anchor.BN,
+    withEd25519 = true,
+    sourceHeight = new anchor.BN(100),
+    currentHeight = new anchor.BN(200),
+    validUntil = new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+    domainTag: Buffer = DOMAIN_TAG,
+    hubBlock: PublicKey | null = null,
+    sourceBlockHash: Buffer = SOURCE_BLOCK_HASH
+  ) => {
+    const message = confirmedWithdrawalMessage(
+      withdrawalHash,
+      recipient,
+      amount,
+      sourceHeight,
+      currentHeight,
+      validUntil,
+      domainTag,
+      sourceBlockHash
+    );
+    const signatures = guardians.map((guardian) =>
+      nacl.sign.detached(message, guardian.secretKey)
+    );
+    const [withdrawalPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("withdrawal"), withdrawalHash],
+      program.programId
+    );
+    return program.methods
+      .processWithdrawal(
+        Array.from(withdrawalHash),
+        amount,
+        { height: sourceHeight, hash: Array.from(sourceBlockHash) },
+        currentHeight,
+        validUntil,
+        signatures.map((signature) => Array.from(signature))
+      )
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        withdrawal: withdrawalPDA,
+        vault: vaultPDA,
+        recipient,
+        feeCollector: authority,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        hubBlock,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts(
+        guardians.map((guardian) => ({
+          pubkey: PublicKey.findProgramAddressSync(
+            [Buffer.from("guardian"), guardian.publicKey.toBuffer()],
+            program.programId
+          )[0],
+          isSigner: false,
+          isWritable: false,
+        }))
+      )
+      .preInstructions(
+        withEd25519
+          ? guardians.map((guardian, i) =>
+              Ed25519Program.createInstructionWithPublicKey({
+                publicKey: guardian.publicKey.toBytes(),
+                message,
+                signature: signatures[i],
+              })
+            )
+          : []
+      );
+  };
+
+  // Create a mint with a funded depositor token account
+  const setupMint = async (decimals: number, tokenProgram: PublicKey = TOKEN_PROGRAM_ID) => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const mint = await createMint(
+      provider.connection,
+      payer,
+      authority,
+      null,
+      decimals,
+      undefined,
+      undefined,
+      tokenProgram
+    );
+    const depositorTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      mint,
+      authority,
+      undefined,
+      undefined,
+      tokenProgram
+    );
+    await mintTo(
+      provider.connection,
+      payer,
+      mint,
+      depositorTokenAccount,
+      payer,
+      1_000_000_000_000,
+      [],
+      undefined,
+      tokenProgram
+    );
+    return { mint, depositorTokenAccount };
+  };
+
+  const depositToken = async (
+    mint: PublicKey,
+    depositorTokenAccount: PublicKey,
+    amount: anchor.BN,
+    commitmentFill: number,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+  ): Promise<PublicKey> => {
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const [tokenDepositPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_deposit"), bridgeState.depositNonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    const [vaultTokenPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_token"), mint.toBuffer()],
+      program.programId
+    );
+    const [tokenConfigPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_config"), mint.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .depositToken(amount, Array.from(Buffer.alloc(32, commitmentFill)))
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        tokenDeposit: tokenDepositPDA,
+        mint,
+        tokenConfig: tokenConfigPDA,
+        depositorTokenAccount,
+        vaultTokenAccount: vaultTokenPDA,
+        depositor: authority,
+        tokenProgram,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    return tokenDepositPDA;
+  };
+
+  // Withdraw `amount` of `mint` to `recipientTokenAccount` on one guardian's
+  // signature, which covers `signedTokenAccount`
+  const processTokenWithdrawal = (
+    guardian: Keypair,
+    mint: PublicKey,
+    withdrawalHash: Buffer,
+    amount: anchor.BN,
+    recipientTokenAccount: PublicKey,
+    signedTokenAccount: PublicKey = recipientTokenAccount,
+    tokenProgram: PublicKey = TOKEN_PROGRAM_ID
+  ) => {
+    const sourceHeight = new anchor.BN(100);
+    const currentHeight = new anchor.BN(200);
+    const validUntil = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+    const message = createHash("sha256")
+      .update(
+        createHash("sha256")
+          .update(Buffer.from("cashio-token-withdrawal"))
+          .update(DOMAIN_TAG)
+          .update(HUB_CHAIN_ID.toArrayLike(Buffer, "le", 8))
+          .update(withdrawalHash)
+          .update(mint.toBuffer())
+          .update(signedTokenAccount.toBuffer())
+          .update(amount.toArrayLike(Buffer, "le", 8))
+          .digest()
+      )
+      .update(sourceHeight.toArrayLike(Buffer, "le", 8))
+      .update(SOURCE_BLOCK_HASH)
+      .update(currentHeight.toArrayLike(Buffer, "le", 8))
+      .update(validUntil.toArrayLike(Buffer, "le", 8))
+      .digest();
+    const signature = nacl.sign.detached(message, guardian.secretKey);
+
+    return program.methods
+      .processTokenWithdrawal(
+        Array.from(withdrawalHash),
+        amount,
+        { height: sourceHeight, hash: Array.from(SOURCE_BLOCK_HASH) },
+        currentHeight,
+        validUntil,
+        [Array.from(signature)]
+      )
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        withdrawal: PublicKey.findProgramAddressSync(
+          [Buffer.from("withdrawal"), withdrawalHash],
+          program.programId
+        )[0],
+        mint,
+        tokenConfig: PublicKey.findProgramAddressSync(
+          [Buffer.from("token_config"), mint.toBuffer()],
+          program.programId
+        )[0],
+        vaultTokenAccount: PublicKey.findProgramAddressSync(
+          [Buffer.from("vault_token"), mint.toBuffer()],
+          program.programId
+        )[0],
+        recipientTokenAccount,
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        hubBlock: null,
+        payer: authority,
+        tokenProgram,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: guardianAddress(guardian.publicKey), isSigner: false, isWritable: false },
+      ])
+      .preInstructions([
+        Ed25519Program.createInstructionWithPublicKey({
+          publicKey: guardian.publicKey.toBytes(),
+          message,
+          signature,
+        }),
+      ]);
+  };
+
   it("Adds a guardian", async () => {
     const guardianKeypair = Keypair.generate();
     
@@ -212,4 +668,2262 @@ describe("cashio-bridge", () => {
     const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
     expect(bridgeState.guardianThreshold).to.equal(newThreshold);
   });
+
+  it("Collects guardian signatures across transactions and finalizes", async () => {
+    const guardian = Keypair.generate();
+    await addGuardian(guardian);
+
+    const recipient = Keypair.generate().publicKey;
+    const withdrawalHash = Buffer.alloc(32, 3);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    const [collectionPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("signatures"), withdrawalHash],
+      program.programId
+    );
+    const [withdrawalPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("withdrawal"), withdrawalHash],
+      program.programId
+    );
+
+    // First submission is verified through the Ed25519 program
+    await submitSignature(guardian, withdrawalHash, recipient, amount).rpc();
+
+    // A retry without the Ed25519 instruction is a cached no-op
+    await submitSignature(guardian, withdrawalHash, recipient, amount, false).rpc();
+
+    const collection = await program.account.signatureCollection.fetch(collectionPDA);
+    expect(collection.signerBitmap.toString()).to.not.equal("0");
+    expect(collection.finalized).to.be.false;
+
+    await finalizeWithdrawal(withdrawalHash, recipient).rpc();
+
+    const withdrawal = await program.account.withdrawal.fetch(withdrawalPDA);
+    expect(withdrawal.processed).to.be.true;
+    expect(withdrawal.amount.toString()).to.equal(amount.toString());
+    expect(await provider.connection.getBalance(recipient)).to.equal(amount.toNumber());
+  });
+
+  it("Accepts removed guardians' signatures only within the grace period", async () => {
+    const active = Keypair.generate();
+    const withinGrace = Keypair.generate();
+    const outsideGrace = Keypair.generate();
+    await addGuardian(active);
+    await addGuardian(withinGrace);
+    await addGuardian(outsideGrace);
+
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const queuedHash = Buffer.alloc(32, 5);
+    const expiredHash = Buffer.alloc(32, 6);
+
+    // Both withdrawals are queued before either guardian is removed
+    await submitSignature(active, queuedHash, recipient, amount).rpc();
+    await submitSignature(active, expiredHash, recipient, amount).rpc();
+
+    await program.methods
+      .setSignatureGracePeriod(new anchor.BN(3600))
+      .accounts({ bridgeState: bridgeStatePDA, authority })
+      .rpc();
+    await removeGuardian(withinGrace);
+    await submitSignature(withinGrace, queuedHash, recipient, amount).rpc();
+
+    const [collectionPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("signatures"), queuedHash],
+      program.programId
+    );
+    const collection = await program.account.signatureCollection.fetch(collectionPDA);
+    expect(collection.signerBitmap.toString(2).split("1").length - 1).to.equal(2);
+
+    await program.methods
+      .setSignatureGracePeriod(new anchor.BN(0))
+      .accounts({ bridgeState: bridgeStatePDA, authority })
+      .rpc();
+    await removeGuardian(outsideGrace);
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+
+    try {
+      await submitSignature(outsideGrace, expiredHash, recipient, amount).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("GuardianNotActive");
+    }
+  });
+
+  it("Funds the vault and keeps withdrawals above the rent-exempt floor", async () => {
+    const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const before = await provider.connection.getBalance(vaultPDA);
+    expect(before).to.be.gte(rentExempt);
+
+    const topUp = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    await program.methods
+      .fundVault(topUp)
+      .accounts({
+        vault: vaultPDA,
+        funder: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const balance = await provider.connection.getBalance(vaultPDA);
+    expect(balance).to.equal(before + topUp.toNumber());
+
+    const guardian = Keypair.generate();
+    await addGuardian(guardian);
+    const recipient = Keypair.generate().publicKey;
+
+    // One lamport past the floor is rejected
+    const overHash = Buffer.alloc(32, 7);
+    const overAmount = new anchor.BN(balance - rentExempt + 1);
+    await submitSignature(guardian, overHash, recipient, overAmount).rpc();
+    try {
+      await finalizeWithdrawal(overHash, recipient).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InsufficientVaultBalance");
+    }
+
+    // Drawing exactly down to the floor succeeds
+    const floorHash = Buffer.alloc(32, 8);
+    const floorAmount = new anchor.BN(balance - rentExempt);
+    await submitSignature(guardian, floorHash, recipient, floorAmount).rpc();
+    await finalizeWithdrawal(floorHash, recipient).rpc();
+    expect(await provider.connection.getBalance(vaultPDA)).to.equal(rentExempt);
+
+    // Refill for later tests
+    await program.methods
+      .fundVault(new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({
+        vault: vaultPDA,
+        funder: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it("Normalizes token deposits to 18 decimals", async () => {
+    const amount = new anchor.BN(20_000_000);
+
+    const sixDecimals = await setupMint(6);
+    const sixPDA = await depositToken(
+      sixDecimals.mint,
+      sixDecimals.depositorTokenAccount,
+      amount,
+      20
+    );
+    const sixDeposit = await program.account.tokenDeposit.fetch(sixPDA);
+    expect(sixDeposit.amount.toString()).to.equal("20000000");
+    expect(sixDeposit.normalizedAmount.toString()).to.equal("20000000000000000000");
+
+    const nineDecimals = await setupMint(9);
+    const ninePDA = await depositToken(
+      nineDecimals.mint,
+      nineDecimals.depositorTokenAccount,
+      amount,
+      21
+    );
+    const nineDeposit = await program.account.tokenDeposit.fetch(ninePDA);
+    expect(nineDeposit.normalizedAmount.toString()).to.equal("20000000000000000");
+  });
+
+  it("Refuses to close a deposit guardians have not acknowledged", async () => {
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const nonce = bridgeState.depositNonce;
+    const [depositPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+
+    await program.methods
+      .depositSol(new anchor.BN(0.1 * LAMPORTS_PER_SOL), Array.from(Buffer.alloc(32, 22)))
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        deposit: depositPDA,
+        vault: vaultPDA,
+        depositor: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .closeDeposit(nonce)
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          deposit: depositPDA,
+          depositor: authority,
+          authority: authority,
+        })
+        .rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("DepositNotAcknowledged");
+    }
+    expect(await provider.connection.getAccountInfo(depositPDA)).to.not.be.null;
+  });
+
+  it("Takes a withdrawal fee for the fee collector", async () => {
+    const collector = Keypair.generate().publicKey;
+    const updateFee = (bps: number, feeCollector: PublicKey) =>
+      program.methods
+        .updateWithdrawalFee(bps, feeCollector)
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+
+    try {
+      await updateFee(101, collector);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("FeeTooHigh");
+    }
+
+    await updateFee(50, collector);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(bridgeState.withdrawalFeeBps).to.equal(50);
+    expect(bridgeState.feeCollector.toString()).to.equal(collector.toString());
+
+    // Keep the collector rent-exempt so small fees can land
+    const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(0);
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({ fromPubkey: authority, toPubkey: collector, lamports: rentExempt })
+      )
+    );
+
+    const guardian = Keypair.generate();
+    await addGuardian(guardian);
+    const recipient = Keypair.generate().publicKey;
+    const withdrawalHash = Buffer.alloc(32, 9);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    await submitSignature(guardian, withdrawalHash, recipient, amount).rpc();
+    await finalizeWithdrawal(withdrawalHash, recipient, collector).rpc();
+
+    // 0.5% of 10,000,000 lamports
+    const fee = 50_000;
+    expect(await provider.connection.getBalance(recipient)).to.equal(amount.toNumber() - fee);
+    expect(await provider.connection.getBalance(collector)).to.equal(rentExempt + fee);
+
+    await updateFee(0, authority);
+  });
+
+  it("Distinguishes invalid signatures from insufficient signatures", async () => {
+    const active = Keypair.generate();
+    const removed = Keypair.generate();
+    await addGuardian(active);
+    await addGuardian(removed);
+    await removeGuardian(removed);
+
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    // A signature without a matching Ed25519 verification is invalid
+    try {
+      await processWithdrawal([active], Buffer.alloc(32, 10), recipient, amount, false).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InvalidSignature");
+    }
+
+    // A valid signature from a removed guardian doesn't count
+    try {
+      await processWithdrawal([removed], Buffer.alloc(32, 11), recipient, amount).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InsufficientSignatures");
+    }
+
+    await processWithdrawal([active], Buffer.alloc(32, 12), recipient, amount).rpc();
+    expect(await provider.connection.getBalance(recipient)).to.equal(amount.toNumber());
+  });
+
+  it("Rejects withdrawals without enough hub confirmations", async () => {
+    const setMinConfirmations = (confirmations: number) =>
+      program.methods
+        .setMinConfirmations(new anchor.BN(confirmations))
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+    await setMinConfirmations(10);
+
+    const guardian = Keypair.generate();
+    await addGuardian(guardian);
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const source = new anchor.BN(1_000);
+
+    for (const current of [new anchor.BN(1_009), new anchor.BN(999)]) {
+      try {
+        await processWithdrawal(
+          [guardian], Buffer.alloc(32, 13), recipient, amount, true, source, current
+        ).rpc();
+        expect.fail("Should have thrown");
+      } catch (err: any) {
+        expect(err.toString()).to.include("InsufficientConfirmations");
+      }
+    }
+
+    await processWithdrawal(
+      [guardian], Buffer.alloc(32, 13), recipient, amount, true, source, new anchor.BN(1_010)
+    ).rpc();
+    expect(await provider.connection.getBalance(recipient)).to.equal(amount.toNumber());
+
+    // Incrementally collected signatures are held to the same depth
+    const collectedHash = Buffer.alloc(32, 49);
+    await submitSignature(
+      guardian, collectedHash, recipient, amount, true, undefined, source, new anchor.BN(1_009)
+    ).rpc();
+    try {
+      await finalizeWithdrawal(collectedHash, recipient).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InsufficientConfirmations");
+    }
+
+    await setMinConfirmations(0);
+  });
+
+  it("Enforces the minimum withdrawal at the boundary", async () => {
+    const minimum = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const setMinWithdrawal = (amount: anchor.BN) =>
+      program.methods
+        .setMinWithdrawal(amount)
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+    await setMinWithdrawal(minimum);
+
+    const guardian = Keypair.generate();
+    await addGuardian(guardian);
+    const recipient = Keypair.generate().publicKey;
+
+    try {
+      await processWithdrawal([guardian], Buffer.alloc(32, 14), recipient, minimum.subn(1)).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("WithdrawalTooSmall");
+    }
+
+    await processWithdrawal([guardian], Buffer.alloc(32, 15), recipient, minimum).rpc();
+    expect(await provider.connection.getBalance(recipient)).to.equal(minimum.toNumber());
+
+    await setMinWithdrawal(new anchor.BN(0));
+  });
+
+  it("Updates and reads back guardian metadata", async () => {
+    const guardian = Keypair.generate();
+    const guardianPDA = await addGuardian(guardian);
+
+    const name = Buffer.alloc(32);
+    name.write("guardian-eu-1");
+    const endpointHash = createHash("sha256").update("https://eu-1.guardians.cash.io").digest();
+
+    await program.methods
+      .updateGuardianMetadata(guardian.publicKey, Array.from(name), Array.from(endpointHash))
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        guardian: guardianPDA,
+        authority,
+      })
+      .rpc();
+
+    const account = await program.account.guardian.fetch(guardianPDA);
+    expect(Buffer.from(account.name).toString().replace(/\0+$/, "")).to.equal("guardian-eu-1");
+    expect(Buffer.from(account.endpointHash).equals(endpointHash)).to.be.true;
+    expect(account.isActive).to.be.true;
+  });
+
+  it("Drains only to the configured emergency recipient while paused", async () => {
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const drain = (recipient: PublicKey) =>
+      program.methods
+        .emergencyDrain(amount)
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          vault: vaultPDA,
+          emergencyRecipient: recipient,
+          authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    const setPaused = (paused: boolean) =>
+      (paused ? program.methods.pause() : program.methods.unpause())
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+
+    try {
+      await drain(coldWallet);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("BridgeNotPaused");
+    }
+
+    await setPaused(true);
+    try {
+      await drain(Keypair.generate().publicKey);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("EmergencyRecipientMismatch");
+    }
+
+    await drain(coldWallet);
+    expect(await provider.connection.getBalance(coldWallet)).to.equal(amount.toNumber());
+    await setPaused(false);
+
+    // Changing the recipient waits out the timelock
+    await program.methods
+      .proposeEmergencyRecipient(Keypair.generate().publicKey)
+      .accounts({ bridgeState: bridgeStatePDA, authority })
+      .rpc();
+    try {
+      await program.methods
+        .applyEmergencyRecipient()
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("TimelockNotElapsed");
+    }
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(bridgeState.emergencyRecipient.toString()).to.equal(coldWallet.toString());
+  });
+
+  it("Caps the number of queued withdrawals", async () => {
+    const setWithdrawalQueue = (maxPending: number) =>
+      program.methods
+        .setWithdrawalQueue(new anchor.BN(0), new anchor.BN(maxPending))
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+    const queueWithdrawal = (withdrawalHash: Buffer) => {
+      const [collectionPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("signatures"), withdrawalHash],
+        program.programId
+      );
+      const [queuedPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("queued_withdrawal"), withdrawalHash],
+        program.programId
+      );
+      return program.methods
+        .queueWithdrawal(Array.from(withdrawalHash))
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          signatureCollection: collectionPDA,
+          queuedWithdrawal: queuedPDA,
+          hubBlock: null,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    };
+
+    const guardian = Keypair.generate();
+    await addGuardian(guardian);
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const hashes = [16, 17, 18].map((fill) => Buffer.alloc(32, fill));
+    for (const withdrawalHash of hashes) {
+      await submitSignature(guardian, withdrawalHash, recipient, amount).rpc();
+    }
+
+    const before = await program.account.bridgeState.fetch(bridgeStatePDA);
+    await setWithdrawalQueue(before.pendingWithdrawalCount.toNumber() + 2);
+    await queueWithdrawal(hashes[0]);
+    await queueWithdrawal(hashes[1]);
+    try {
+      await queueWithdrawal(hashes[2]);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("WithdrawalQueueFull");
+    }
+
+    // Executing an entry frees a slot
+    const [queuedPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("queued_withdrawal"), hashes[0]],
+      program.programId
+    );
+    const [withdrawalPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("withdrawal"), hashes[0]],
+      program.programId
+    );
+    await program.methods
+      .executeWithdrawal(Array.from(hashes[0]))
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        queuedWithdrawal: queuedPDA,
+        withdrawal: withdrawalPDA,
+        vault: vaultPDA,
+        recipient,
+        feeCollector: authority,
+        payer: authority,
+        executor: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await queueWithdrawal(hashes[2]);
+
+    const after = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(after.pendingWithdrawalCount.toNumber()).to.equal(
+      before.pendingWithdrawalCount.toNumber() + 2
+    );
+    await setWithdrawalQueue(256);
+  });
+
+  it("Rejects expired guardian signatures", async () => {
+    const guardian = Keypair.generate();
+    await addGuardian(guardian);
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const now = Math.floor(Date.now() / 1000);
+
+    try {
+      await processWithdrawal(
+        [guardian], Buffer.alloc(32, 19), recipient, amount, true,
+        undefined, undefined, new anchor.BN(now - 60)
+      ).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("SignatureExpired");
+    }
+
+    await processWithdrawal(
+      [guardian], Buffer.alloc(32, 19), recipient, amount, true,
+      undefined, undefined, new anchor.BN(now + 600)
+    ).rpc();
+    expect(await provider.connection.getBalance(recipient)).to.equal(amount.toNumber());
+  });
+
+  it("Expires incrementally collected guardian signatures", async () => {
+    const guardian = Keypair.generate();
+    await rotateGuardianSet([guardian], 1);
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const withdrawalHash = Buffer.alloc(32, 50);
+    const validUntil = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+
+    await submitSignature(
+      guardian, withdrawalHash, recipient, amount, true, undefined,
+      new anchor.BN(100), new anchor.BN(200), validUntil
+    ).rpc();
+    await new Promise((resolve) => setTimeout(resolve, 4000));
+    try {
+      await finalizeWithdrawal(withdrawalHash, recipient).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("SignatureExpired");
+    }
+  });
+  it("Migrates bridge state only for the authority and leaves current layouts intact", async () => {
+    const migrate = (signer?: Keypair) => {
+      const builder = program.methods.migrateState().accounts({
+        bridgeState: bridgeStatePDA,
+        authority: signer ? signer.publicKey : authority,
+        systemProgram: SystemProgram.programId,
+      });
+      return signer ? builder.signers([signer]).rpc() : builder.rpc();
+    };
+
+    const outsider = Keypair.generate();
+    try {
+      await migrate(outsider);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("Unauthorized");
+    }
+
+    const before = await program.account.bridgeState.fetch(bridgeStatePDA);
+    await migrate();
+    const info = await provider.connection.getAccountInfo(bridgeStatePDA);
+    expect(info!.data.length).to.equal(program.account.bridgeState.size);
+    const after = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(after.totalDeposited.toString()).to.equal(before.totalDeposited.toString());
+    expect(after.maxPendingWithdrawals.toString()).to.equal(
+      before.maxPendingWithdrawals.toString()
+    );
+  });
+
+  it("Migrates legacy guardian, token config and deposit accounts", async () => {
+    // Loaded at genesis from tests/fixtures in their original layouts
+    const legacyGuardianKey = new PublicKey(Buffer.alloc(32, 0x47));
+    const legacyMint = new PublicKey(Buffer.alloc(32, 0x4d));
+    const legacyGuardian = guardianAddress(legacyGuardianKey);
+    const legacyTokenConfig = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_config"), legacyMint.toBuffer()],
+      program.programId
+    )[0];
+    const legacyDeposit = PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), new anchor.BN(1_000_000).toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+    const migrate = (account: PublicKey, signer?: Keypair) => {
+      const builder = program.methods.migrateAccount().accounts({
+        bridgeState: bridgeStatePDA,
+        account,
+        authority: signer ? signer.publicKey : authority,
+        systemProgram: SystemProgram.programId,
+      });
+      return signer ? builder.signers([signer]).rpc() : builder.rpc();
+    };
+    const size = async (account: PublicKey) =>
+      (await provider.connection.getAccountInfo(account))!.data.length;
+
+    expect(await size(legacyGuardian)).to.equal(8 + 32 + 1 + 8);
+    expect(await size(legacyTokenConfig)).to.equal(8 + 32 + 1 + 1);
+    expect(await size(legacyDeposit)).to.equal(8 + 32 + 32 + 8 + 8 + 8 + 1 + 1);
+
+    try {
+      await migrate(legacyGuardian, Keypair.generate());
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("ConstraintHasOne");
+    }
+    // Only guardian, token config and deposit layouts are migrated here
+    try {
+      await migrate(bridgeStatePDA);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("AccountDiscriminatorMismatch");
+    }
+
+    const { nextGuardianIndex } = await program.account.bridgeState.fetch(bridgeStatePDA);
+    await migrate(legacyGuardian);
+    expect(await size(legacyGuardian)).to.equal(program.account.guardian.size);
+    const guardian = await program.account.guardian.fetch(legacyGuardian);
+    expect(guardian.pubkey.toString()).to.equal(legacyGuardianKey.toString());
+    expect(guardian.isActive).to.be.false;
+    expect(guardian.addedAt.toNumber()).to.equal(1_700_000_000);
+    expect(guardian.index).to.equal(nextGuardianIndex);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(bridgeState.nextGuardianIndex).to.equal(nextGuardianIndex + 1);
+
+    await migrate(legacyTokenConfig);
+    expect(await size(legacyTokenConfig)).to.equal(program.account.tokenConfig.size);
+    const tokenConfig = await program.account.tokenConfig.fetch(legacyTokenConfig);
+    expect(tokenConfig.mint.toString()).to.equal(legacyMint.toString());
+    expect(tokenConfig.isPaused).to.be.true;
+    expect(tokenConfig.bump).to.equal(255);
+    expect(tokenConfig.poolId).to.equal(1);
+    expect(tokenConfig.liabilityTracked).to.be.false;
+
+    await migrate(legacyDeposit);
+    expect(await size(legacyDeposit)).to.equal(program.account.deposit.size);
+    const deposit = await program.account.deposit.fetch(legacyDeposit);
+    expect(deposit.amount.toNumber()).to.equal(50_000_000);
+    expect(deposit.nonce.toNumber()).to.equal(1_000_000);
+    expect(Buffer.from(deposit.commitment).equals(Buffer.alloc(32, 57))).to.be.true;
+    expect(deposit.poolId).to.equal(0);
+
+    // A second run leaves current layouts untouched
+    await migrate(legacyGuardian);
+    expect((await program.account.guardian.fetch(legacyGuardian)).index).to.equal(
+      nextGuardianIndex
+    );
+  });
+
+  it("Binds withdrawal signatures to the deployment's domain tag", async () => {
+    const testnetTag = Buffer.alloc(16);
+    testnetTag.write("cashio-testnet");
+    const withdrawalHash = Buffer.alloc(32, 20);
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    expect(
+      withdrawalMessage(withdrawalHash, recipient, amount).equals(
+        withdrawalMessage(withdrawalHash, recipient, amount, testnetTag)
+      )
+    ).to.be.false;
+
+    const guardian = Keypair.generate();
+    await addGuardian(guardian);
+    try {
+      await processWithdrawal(
+        [guardian], withdrawalHash, recipient, amount, true,
+        undefined, undefined, undefined, testnetTag
+      ).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InvalidSignature");
+    }
+
+    await processWithdrawal([guardian], withdrawalHash, recipient, amount).rpc();
+    expect(await provider.connection.getBalance(recipient)).to.equal(amount.toNumber());
+  });
+
+  it("Records the token amount the vault actually received", async () => {
+    // Token-2022 mint withholding a 1% fee on every transfer
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const mint = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: authority,
+          newAccountPubkey: mint.publicKey,
+          space: mintLen,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          mint.publicKey,
+          authority,
+          authority,
+          100,
+          BigInt(1_000_000_000),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(mint.publicKey, 6, authority, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [mint]
+    );
+    const depositorTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      mint.publicKey,
+      authority,
+      undefined,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      payer,
+      mint.publicKey,
+      depositorTokenAccount,
+      payer,
+      1_000_000_000,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const setAllowedTokenProgram = (tokenProgram: PublicKey) =>
+      program.methods
+        .setAllowedTokenProgram(tokenProgram)
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+
+    await setAllowedTokenProgram(TOKEN_2022_PROGRAM_ID);
+    const depositPDA = await depositToken(
+      mint.publicKey,
+      depositorTokenAccount,
+      new anchor.BN(20_000_000),
+      23,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await setAllowedTokenProgram(TOKEN_PROGRAM_ID);
+
+    const [vaultTokenPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_token"), mint.publicKey.toBuffer()],
+      program.programId
+    );
+    const vault = await getAccount(provider.connection, vaultTokenPDA, undefined, TOKEN_2022_PROGRAM_ID);
+    expect(Number(vault.amount)).to.equal(19_800_000);
+    const deposit = await program.account.tokenDeposit.fetch(depositPDA);
+    expect(deposit.amount.toNumber()).to.equal(19_800_000);
+    expect(deposit.normalizedAmount.toString()).to.equal("19800000000000000000");
+    const config = await program.account.tokenConfig.fetch(
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("token_config"), mint.publicKey.toBuffer()],
+        program.programId
+      )[0]
+    );
+    expect(config.liability.toNumber()).to.equal(19_800_000);
+  });
+
+  it("Pauses deposits of one mint while others stay active", async () => {
+    const risky = await setupMint(6);
+    const healthy = await setupMint(6);
+    const amount = new anchor.BN(20_000_000);
+    const setTokenPaused = (mint: PublicKey, paused: boolean) =>
+      program.methods
+        .setTokenPaused(paused)
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          mint,
+          tokenConfig: PublicKey.findProgramAddressSync(
+            [Buffer.from("token_config"), mint.toBuffer()],
+            program.programId
+          )[0],
+          authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    await setTokenPaused(risky.mint, true);
+    try {
+      await depositToken(risky.mint, risky.depositorTokenAccount, amount, 25);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("TokenPaused");
+    }
+
+    const depositPDA = await depositToken(healthy.mint, healthy.depositorTokenAccount, amount, 26);
+    const deposit = await program.account.tokenDeposit.fetch(depositPDA);
+    expect(deposit.mint.toString()).to.equal(healthy.mint.toString());
+
+    await setTokenPaused(risky.mint, false);
+    await depositToken(risky.mint, risky.depositorTokenAccount, amount, 27);
+  });
+
+  it("Reserves distinct deposit nonces for concurrent depositors", async () => {
+    const reservationPDA = (depositor: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("nonce_reservation"), depositor.toBuffer()],
+        program.programId
+      )[0];
+    const depositPDA = (nonce: anchor.BN) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("deposit"), nonce.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const [alice, bob] = [Keypair.generate(), Keypair.generate()];
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        ...[alice, bob].map((depositor) =>
+          SystemProgram.transfer({
+            fromPubkey: authority,
+            toPubkey: depositor.publicKey,
+            lamports: LAMPORTS_PER_SOL,
+          })
+        )
+      )
+    );
+
+    // Both reserve in parallel and receive different nonces
+    await Promise.all(
+      [alice, bob].map((depositor) =>
+        program.methods
+          .reserveNonce()
+          .accounts({
+            bridgeState: bridgeStatePDA,
+            reservation: reservationPDA(depositor.publicKey),
+            depositor: depositor.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([depositor])
+          .rpc()
+      )
+    );
+    const aliceReservation = await program.account.nonceReservation.fetch(
+      reservationPDA(alice.publicKey)
+    );
+    const bobReservation = await program.account.nonceReservation.fetch(
+      reservationPDA(bob.publicKey)
+    );
+    expect(aliceReservation.nonce.toString()).to.not.equal(bobReservation.nonce.toString());
+
+    // Deposits land in either order under their reserved nonces
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const depositReserved = (depositor: Keypair, nonce: anchor.BN, fill: number) =>
+      program.methods
+        .depositSolReserved(amount, Array.from(Buffer.alloc(32, fill)))
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          reservation: reservationPDA(depositor.publicKey),
+          deposit: depositPDA(nonce),
+          vault: vaultPDA,
+          depositor: depositor.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([depositor])
+        .rpc();
+    await depositReserved(bob, bobReservation.nonce, 28);
+    await depositReserved(alice, aliceReservation.nonce, 29);
+
+    const aliceDeposit = await program.account.deposit.fetch(depositPDA(aliceReservation.nonce));
+    expect(aliceDeposit.depositor.toString()).to.equal(alice.publicKey.toString());
+    const bobDeposit = await program.account.deposit.fetch(depositPDA(bobReservation.nonce));
+    expect(bobDeposit.depositor.toString()).to.equal(bob.publicKey.toString());
+
+    // The reservation is consumed by the deposit
+    expect(
+      await provider.connection.getAccountInfo(reservationPDA(alice.publicKey))
+    ).to.be.null;
+  });
+
+  it("Rotates the whole guardian set and bumps the epoch", async () => {
+    const retiring = Keypair.generate();
+    await addGuardian(retiring);
+
+    // A collection started under the old set can't be completed after rotation
+    const recipient = Keypair.generate().publicKey;
+    const withdrawalHash = Buffer.alloc(32, 30);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    await submitSignature(retiring, withdrawalHash, recipient, amount).rpc();
+
+    const before = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const current = (await currentLayoutGuardians()).filter((g) => g.account.isActive);
+    expect(current.length).to.equal(before.guardianCount.toNumber());
+
+    const incoming = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    await rotateGuardianSet(incoming, 2);
+
+    const after = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(after.guardianSetEpoch.toNumber()).to.equal(before.guardianSetEpoch.toNumber() + 1);
+    expect(after.guardianCount.toString()).to.equal("3");
+    expect(after.guardianThreshold).to.equal(2);
+
+    for (const g of current) {
+      const guardian = await program.account.guardian.fetch(g.publicKey);
+      expect(guardian.isActive).to.be.false;
+    }
+    for (const keypair of incoming) {
+      const guardian = await program.account.guardian.fetch(guardianAddress(keypair.publicKey));
+      expect(guardian.isActive).to.be.true;
+      expect(guardian.pubkey.toString()).to.equal(keypair.publicKey.toString());
+    }
+
+    try {
+      await submitSignature(incoming[0], withdrawalHash, recipient, amount).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("StaleGuardianSet");
+    }
+  });
+
+  it("Tags SOL and token deposits with distinct pool ids", async () => {
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const [solDepositPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), bridgeState.depositNonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .depositSol(new anchor.BN(0.01 * LAMPORTS_PER_SOL), Array.from(Buffer.alloc(32, 31)))
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        deposit: solDepositPDA,
+        vault: vaultPDA,
+        depositor: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const solDeposit = await program.account.deposit.fetch(solDepositPDA);
+
+    const token = await setupMint(6);
+    const amount = new anchor.BN(20_000_000);
+    const defaultPDA = await depositToken(token.mint, token.depositorTokenAccount, amount, 32);
+    const defaultDeposit = await program.account.tokenDeposit.fetch(defaultPDA);
+    expect(solDeposit.poolId).to.equal(0);
+    expect(defaultDeposit.poolId).to.not.equal(solDeposit.poolId);
+
+    const setTokenPool = (poolId: number) =>
+      program.methods
+        .setTokenPool(poolId)
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          mint: token.mint,
+          tokenConfig: PublicKey.findProgramAddressSync(
+            [Buffer.from("token_config"), token.mint.toBuffer()],
+            program.programId
+          )[0],
+          authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    await setTokenPool(7);
+    const routedPDA = await depositToken(token.mint, token.depositorTokenAccount, amount, 33);
+    expect((await program.account.tokenDeposit.fetch(routedPDA)).poolId).to.equal(7);
+
+    // Native SOL's pool can't be assigned to a mint
+    try {
+      await setTokenPool(0);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InvalidPoolId");
+    }
+  });
+
+  it("Lets a guardian quorum pause the bridge without the authority", async () => {
+    const guardians = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    await rotateGuardianSet(guardians, 2);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const validUntil = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+    const message = createHash("sha256")
+      .update(Buffer.from("cashio-pause"))
+      .update(DOMAIN_TAG)
+      .update(HUB_CHAIN_ID.toArrayLike(Buffer, "le", 8))
+      .update(bridgeState.guardianSetEpoch.toArrayLike(Buffer, "le", 8))
+      .update(validUntil.toArrayLike(Buffer, "le", 8))
+      .digest();
+
+    // Any fee payer may submit the guardians' signatures
+    const relayer = Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(relayer.publicKey, LAMPORTS_PER_SOL)
+    );
+    const guardianPause = (signers: Keypair[]) => {
+      const signatures = signers.map((guardian) =>
+        nacl.sign.detached(message, guardian.secretKey)
+      );
+      return program.methods
+        .guardianPause(
+          validUntil,
+          signatures.map((signature) => Array.from(signature))
+        )
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          payer: relayer.publicKey,
+        })
+        .remainingAccounts(
+          signers.map((guardian) => ({
+            pubkey: guardianAddress(guardian.publicKey),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .preInstructions(
+          signers.map((guardian, i) =>
+            Ed25519Program.createInstructionWithPublicKey({
+              publicKey: guardian.publicKey.toBytes(),
+              message,
+              signature: signatures[i],
+            })
+          )
+        )
+        .signers([relayer])
+        .rpc();
+    };
+
+    try {
+      await guardianPause(guardians.slice(0, 1));
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InsufficientSignatures");
+    }
+    expect((await program.account.bridgeState.fetch(bridgeStatePDA)).isPaused).to.be.false;
+
+    await guardianPause(guardians.slice(0, 2));
+    expect((await program.account.bridgeState.fetch(bridgeStatePDA)).isPaused).to.be.true;
+
+    await program.methods
+      .unpause()
+      .accounts({ bridgeState: bridgeStatePDA, authority })
+      .rpc();
+  });
+
+  it("Compacts old withdrawal markers without allowing replays", async () => {
+    const guardian = Keypair.generate();
+    await rotateGuardianSet([guardian], 1);
+    const recipient = Keypair.generate().publicKey;
+    const withdrawalHash = Buffer.alloc(32, 32);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const [withdrawalPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("withdrawal"), withdrawalHash],
+      program.programId
+    );
+    await processWithdrawal([guardian], withdrawalHash, recipient, amount).rpc();
+
+    const setRetention = (seconds: number) =>
+      program.methods
+        .setWithdrawalRetention(new anchor.BN(seconds))
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+    const compact = () =>
+      program.methods
+        .compactWithdrawals()
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .remainingAccounts([{ pubkey: withdrawalPDA, isWritable: true, isSigner: false }])
+        .rpc();
+
+    // Markers inside the default retention period stay put
+    try {
+      await compact();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("WithdrawalRetained");
+    }
+
+    await setRetention(0);
+    await compact();
+    expect(await provider.connection.getAccountInfo(withdrawalPDA)).to.be.null;
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(bridgeState.compactedWithdrawals.toNumber()).to.equal(1);
+
+    // The marker is gone, but the bloom filter still rejects the hash
+    try {
+      await processWithdrawal([guardian], withdrawalHash, recipient, amount).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("WithdrawalAlreadyProcessed");
+    }
+    expect(await provider.connection.getBalance(recipient)).to.equal(amount.toNumber());
+
+    // Guardians can let a bloom false positive through; the compacted hash
+    // stands in for one here
+    const overrideValidUntil = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+    const approveOverride = async (hash: Buffer, signers: Keypair[]) => {
+      const epoch = (await program.account.bridgeState.fetch(bridgeStatePDA)).guardianSetEpoch;
+      const message = createHash("sha256")
+        .update(Buffer.from("cashio-bloom-override"))
+        .update(DOMAIN_TAG)
+        .update(HUB_CHAIN_ID.toArrayLike(Buffer, "le", 8))
+        .update(epoch.toArrayLike(Buffer, "le", 8))
+        .update(hash)
+        .update(overrideValidUntil.toArrayLike(Buffer, "le", 8))
+        .digest();
+      const signatures = signers.map((signer) => nacl.sign.detached(message, signer.secretKey));
+      return program.methods
+        .approveBloomOverride(
+          Array.from(hash),
+          overrideValidUntil,
+          signatures.map((signature) => Array.from(signature))
+        )
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authority,
+        })
+        .remainingAccounts(
+          signers.map((signer) => ({
+            pubkey: guardianAddress(signer.publicKey),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .preInstructions(
+          signers.map((signer, i) =>
+            Ed25519Program.createInstructionWithPublicKey({
+              publicKey: signer.publicKey.toBytes(),
+              message,
+              signature: signatures[i],
+            })
+          )
+        )
+        .rpc();
+    };
+    // The authority alone can't approve an override
+    try {
+      await approveOverride(withdrawalHash, []);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InsufficientSignatures");
+    }
+    try {
+      await approveOverride(Buffer.alloc(32, 33), [guardian]);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("BloomOverrideNotNeeded");
+    }
+    await approveOverride(withdrawalHash, [guardian]);
+    await processWithdrawal([guardian], withdrawalHash, recipient, amount).rpc();
+    expect(await provider.connection.getBalance(recipient)).to.equal(2 * amount.toNumber());
+    const overridden = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(
+      overridden.bloomOverrides.every((hash: number[]) => hash.every((byte) => byte === 0))
+    ).to.be.true;
+
+    await setRetention(30 * 24 * 60 * 60);
+  });
+
+  it("Routes token transfers only through the allowed token program", async () => {
+    const { mint, depositorTokenAccount } = await setupMint(6);
+    const token2022 = await setupMint(6, TOKEN_2022_PROGRAM_ID);
+    const amount = new anchor.BN(20_000_000);
+    const setAllowedTokenProgram = (tokenProgram: PublicKey) =>
+      program.methods
+        .setAllowedTokenProgram(tokenProgram)
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(bridgeState.allowedTokenProgram.toString()).to.equal(TOKEN_PROGRAM_ID.toString());
+
+    // A Token-2022 mint is refused until Token-2022 is the allowed program
+    try {
+      await depositToken(token2022.mint, token2022.depositorTokenAccount, amount, 33, TOKEN_2022_PROGRAM_ID);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("TokenProgramNotAllowed");
+    }
+
+    await setAllowedTokenProgram(TOKEN_2022_PROGRAM_ID);
+    try {
+      await depositToken(mint, depositorTokenAccount, amount, 33);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("TokenProgramNotAllowed");
+    }
+    const depositPDA = await depositToken(
+      token2022.mint,
+      token2022.depositorTokenAccount,
+      amount,
+      33,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const deposit = await program.account.tokenDeposit.fetch(depositPDA);
+    expect(deposit.amount.toNumber()).to.equal(amount.toNumber());
+    const [vaultTokenPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_token"), token2022.mint.toBuffer()],
+      program.programId
+    );
+    const vault = await getAccount(provider.connection, vaultTokenPDA, undefined, TOKEN_2022_PROGRAM_ID);
+    expect(Number(vault.amount)).to.equal(amount.toNumber());
+
+    await setAllowedTokenProgram(TOKEN_PROGRAM_ID);
+    await depositToken(mint, depositorTokenAccount, amount, 33);
+  });
+
+  it("Sums guardian weights against the weight threshold", async () => {
+    const [heavy, light1, light2] = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    await rotateGuardianSet([heavy, light1, light2], 2);
+    await program.methods
+      .setGuardianWeight(heavy.publicKey, 3)
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        guardian: guardianAddress(heavy.publicKey),
+        authority,
+      })
+      .rpc();
+    const setWeightThreshold = (weight: number) =>
+      program.methods
+        .setWeightThreshold(weight)
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+
+    try {
+      await setWeightThreshold(6);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("ThresholdTooHigh");
+    }
+    await setWeightThreshold(3);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(bridgeState.totalWeight).to.equal(5);
+    expect(bridgeState.weightThreshold).to.equal(3);
+
+    // Two light guardians meet the count threshold but not the weight
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    try {
+      await processWithdrawal([light1, light2], Buffer.alloc(32, 34), recipient, amount).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InsufficientSignatures");
+    }
+
+    await processWithdrawal([heavy], Buffer.alloc(32, 35), recipient, amount).rpc();
+    expect(await provider.connection.getBalance(recipient)).to.equal(amount.toNumber());
+
+    await setWeightThreshold(0);
+  });
+
+  it("Dumps the active guardian set in one event", async () => {
+    const guardians = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+    await rotateGuardianSet(guardians, 2);
+    const dumpGuardians = (members: Keypair[]) =>
+      program.methods
+        .dumpGuardians()
+        .accounts({ bridgeState: bridgeStatePDA })
+        .remainingAccounts(
+          members.map((guardian) => ({
+            pubkey: guardianAddress(guardian.publicKey),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .rpc({ commitment: "confirmed" });
+
+    // A partial or repeated set is rejected
+    for (const members of [guardians.slice(0, 2), [guardians[0], guardians[0], guardians[1]]]) {
+      try {
+        await dumpGuardians(members);
+        expect.fail("Should have thrown");
+      } catch (err: any) {
+        expect(err.toString()).to.include("GuardianAccountsMismatch");
+      }
+    }
+
+    const tx = await dumpGuardians(guardians);
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const dump = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+      (e) => e.name === "guardianSetDump"
+    );
+    expect(dump).to.not.be.undefined;
+    expect(dump.data.guardians.map((g: PublicKey) => g.toString())).to.deep.equal(
+      guardians.map((g) => g.publicKey.toString())
+    );
+    expect(dump.data.threshold).to.equal(2);
+  });
+
+  it("Rejects malleated guardian signatures", async () => {
+    const guardian = Keypair.generate();
+    await rotateGuardianSet([guardian], 1);
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const withdrawalHash = Buffer.alloc(32, 36);
+
+    // S + L verifies under lenient checks but is a second encoding
+    const message = confirmedWithdrawalMessage(
+      withdrawalHash,
+      recipient,
+      amount,
+      new anchor.BN(100),
+      new anchor.BN(200),
+      COLLECTION_VALID_UNTIL
+    );
+    const signature = nacl.sign.detached(message, guardian.secretKey);
+    const order = new anchor.BN(
+      "1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed",
+      16
+    );
+    const scalar = new anchor.BN(signature.slice(32), "le");
+    const malleated = new Uint8Array(signature);
+    malleated.set(scalar.add(order).toArrayLike(Buffer, "le", 32), 32);
+
+    try {
+      await submitSignature(guardian, withdrawalHash, recipient, amount, false, malleated).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("NonCanonicalSignature");
+    }
+
+    // Once the canonical form is in, the variant does not count again
+    await submitSignature(guardian, withdrawalHash, recipient, amount).rpc();
+    await submitSignature(guardian, withdrawalHash, recipient, amount, false, malleated).rpc();
+    const [collectionPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("signatures"), withdrawalHash],
+      program.programId
+    );
+    const collection = await program.account.signatureCollection.fetch(collectionPDA);
+    expect(collection.signerBitmap.toString(2).split("1").length - 1).to.equal(1);
+  });
+
+  it("Attests reserves only when they match the vault", async () => {
+    const guardians = [Keypair.generate(), Keypair.generate()];
+    await rotateGuardianSet(guardians, 2);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const vaultBalance = new anchor.BN(await provider.connection.getBalance(vaultPDA));
+    const timestamp = new anchor.BN(
+      await provider.connection.getBlockTime(await provider.connection.getSlot())
+    );
+
+    const attestReserves = (balance: anchor.BN) => {
+      const message = createHash("sha256")
+        .update(Buffer.from("cashio-reserves"))
+        .update(DOMAIN_TAG)
+        .update(HUB_CHAIN_ID.toArrayLike(Buffer, "le", 8))
+        .update(bridgeState.guardianSetEpoch.toArrayLike(Buffer, "le", 8))
+        .update(balance.toArrayLike(Buffer, "le", 8))
+        .update(bridgeState.totalDeposited.toArrayLike(Buffer, "le", 8))
+        .update(bridgeState.totalWithdrawn.toArrayLike(Buffer, "le", 8))
+        .update(timestamp.toArrayLike(Buffer, "le", 8))
+        .digest();
+      const signatures = guardians.map((guardian) =>
+        nacl.sign.detached(message, guardian.secretKey)
+      );
+      return program.methods
+        .attestReserves(
+          balance,
+          bridgeState.totalDeposited,
+          bridgeState.totalWithdrawn,
+          timestamp,
+          signatures.map((signature) => Array.from(signature))
+        )
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          payer: authority,
+        })
+        .remainingAccounts(
+          guardians.map((guardian) => ({
+            pubkey: guardianAddress(guardian.publicKey),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .preInstructions(
+          guardians.map((guardian, i) =>
+            Ed25519Program.createInstructionWithPublicKey({
+              publicKey: guardian.publicKey.toBytes(),
+              message,
+              signature: signatures[i],
+            })
+          )
+        )
+        .rpc({ commitment: "confirmed" });
+    };
+
+    // Guardians signing an inflated balance are refused
+    try {
+      await attestReserves(vaultBalance.addn(1));
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("ReserveMismatch");
+    }
+
+    const tx = await attestReserves(vaultBalance);
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const attestation = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+      (e) => e.name === "reserveAttestation"
+    );
+    expect(attestation).to.not.be.undefined;
+    expect(attestation.data.vaultBalance.toString()).to.equal(vaultBalance.toString());
+    expect(attestation.data.signers).to.equal(2);
+  });
+
+  it("Updates per-mint deposit limits", async () => {
+    const { mint, depositorTokenAccount } = await setupMint(6);
+    const [tokenConfigPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_config"), mint.toBuffer()],
+      program.programId
+    );
+    const updateTokenConfig = (min: number, max: number, paused: boolean) =>
+      program.methods
+        .updateTokenConfig(new anchor.BN(min), new anchor.BN(max), paused)
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          mint,
+          tokenConfig: tokenConfigPDA,
+          authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    try {
+      await updateTokenConfig(50_000_000, 50_000_000, false);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InvalidDepositLimits");
+    }
+
+    const tx = await updateTokenConfig(20_000_000, 50_000_000, false);
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const updated = [...parser.parseLogs(txInfo.meta.logMessages)].find(
+      (e) => e.name === "tokenConfigUpdated"
+    );
+    expect(updated).to.not.be.undefined;
+    expect(updated.data.mint.toString()).to.equal(mint.toString());
+    expect(updated.data.min.toNumber()).to.equal(20_000_000);
+    expect(updated.data.max.toNumber()).to.equal(50_000_000);
+    expect(updated.data.paused).to.be.false;
+
+    const config = await program.account.tokenConfig.fetch(tokenConfigPDA);
+    expect(config.minDeposit.toNumber()).to.equal(20_000_000);
+    expect(config.maxDeposit.toNumber()).to.equal(50_000_000);
+
+    for (const [amount, error] of [
+      [10_000_000, "AmountTooSmall"],
+      [60_000_000, "AmountTooLarge"],
+    ] as const) {
+      try {
+        await depositToken(mint, depositorTokenAccount, new anchor.BN(amount), 37);
+        expect.fail("Should have thrown");
+      } catch (err: any) {
+        expect(err.toString()).to.include(error);
+      }
+    }
+    await depositToken(mint, depositorTokenAccount, new anchor.BN(30_000_000), 37);
+  });
+
+  it("Binds token withdrawals to the signed token account", async () => {
+    const guardian = Keypair.generate();
+    await rotateGuardianSet([guardian], 1);
+    const { mint, depositorTokenAccount } = await setupMint(6);
+    await depositToken(mint, depositorTokenAccount, new anchor.BN(50_000_000), 38);
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const intended = await createAccount(provider.connection, payer, mint, Keypair.generate().publicKey);
+    const attacker = await createAccount(provider.connection, payer, mint, Keypair.generate().publicKey);
+
+    const withdrawalHash = Buffer.alloc(32, 39);
+    const amount = new anchor.BN(20_000_000);
+
+    // The same signature pointed at another token account doesn't verify
+    try {
+      await processTokenWithdrawal(guardian, mint, withdrawalHash, amount, attacker, intended).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InvalidSignature");
+    }
+    expect(Number((await getAccount(provider.connection, attacker)).amount)).to.equal(0);
+
+    await processTokenWithdrawal(guardian, mint, withdrawalHash, amount, intended).rpc();
+    expect(Number((await getAccount(provider.connection, intended)).amount)).to.equal(
+      amount.toNumber()
+    );
+  });
+
+  it("Authorizes a whole withdrawal batch with one signature set", async () => {
+    const guardians = [Keypair.generate(), Keypair.generate()];
+    await rotateGuardianSet(guardians, 2);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const sourceHeight = new anchor.BN(100);
+    const currentHeight = new anchor.BN(200);
+    const items = [Buffer.alloc(32, 40), Buffer.alloc(32, 41)].map((withdrawalHash) => ({
+      withdrawalHash,
+      recipient: Keypair.generate().publicKey,
+    }));
+    const leaves = items.map(({ withdrawalHash, recipient }) =>
+      createHash("sha256")
+        .update(Buffer.from("cashio-batch-leaf"))
+        .update(withdrawalHash)
+        .update(recipient.toBuffer())
+        .update(amount.toArrayLike(Buffer, "le", 8))
+        .update(sourceHeight.toArrayLike(Buffer, "le", 8))
+        .update(SOURCE_BLOCK_HASH)
+        .update(currentHeight.toArrayLike(Buffer, "le", 8))
+        .digest()
+    );
+    const root = createHash("sha256")
+      .update(Buffer.concat([...leaves].sort(Buffer.compare)))
+      .digest();
+    const batchPDA = PublicKey.findProgramAddressSync(
+      [Buffer.from("withdrawal_batch"), root],
+      program.programId
+    )[0];
+
+    const validUntil = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+    const message = createHash("sha256")
+      .update(Buffer.from("cashio-withdrawal-batch"))
+      .update(DOMAIN_TAG)
+      .update(HUB_CHAIN_ID.toArrayLike(Buffer, "le", 8))
+      .update(bridgeState.guardianSetEpoch.toArrayLike(Buffer, "le", 8))
+      .update(root)
+      .update(validUntil.toArrayLike(Buffer, "le", 8))
+      .digest();
+    const signatures = guardians.map((guardian) =>
+      nacl.sign.detached(message, guardian.secretKey)
+    );
+    const commitBatch = () =>
+      program.methods
+        .commitWithdrawalBatch(
+          Array.from(root),
+          validUntil,
+          signatures.map((signature) => Array.from(signature))
+        )
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          withdrawalBatch: batchPDA,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          guardians.map((guardian) => ({
+            pubkey: guardianAddress(guardian.publicKey),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .preInstructions(
+          guardians.map((guardian, i) =>
+            Ed25519Program.createInstructionWithPublicKey({
+              publicKey: guardian.publicKey.toBytes(),
+              message,
+              signature: signatures[i],
+            })
+          )
+        )
+        .rpc();
+    const claim = (index: number, proof: Buffer[]) =>
+      program.methods
+        .claimFromBatch(
+          Array.from(root),
+          Array.from(items[index].withdrawalHash),
+          amount,
+          { height: sourceHeight, hash: Array.from(SOURCE_BLOCK_HASH) },
+          currentHeight,
+          proof.map((sibling) => Array.from(sibling))
+        )
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          withdrawalBatch: batchPDA,
+          withdrawal: PublicKey.findProgramAddressSync(
+            [Buffer.from("withdrawal"), items[index].withdrawalHash],
+            program.programId
+          )[0],
+          vault: vaultPDA,
+          recipient: items[index].recipient,
+          feeCollector: authority,
+          hubBlock: null,
+          payer: authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    await commitBatch();
+    const batch = await program.account.withdrawalBatch.fetch(batchPDA);
+    expect(batch.signers).to.equal(2);
+    expect(batch.guardianSetEpoch.toString()).to.equal(bridgeState.guardianSetEpoch.toString());
+    expect(batch.validUntil.toString()).to.equal(validUntil.toString());
+
+    // The committed signature set can't be replayed for the same root
+    try {
+      await commitBatch();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("already in use");
+    }
+
+    try {
+      await claim(0, [leaves[0]]);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InvalidBatchProof");
+    }
+
+    // Each item is paid once under the single approval
+    await claim(0, [leaves[1]]);
+    expect(await provider.connection.getBalance(items[0].recipient)).to.equal(amount.toNumber());
+    try {
+      await claim(0, [leaves[1]]);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("already in use");
+    }
+
+    // Once the approving guardian set is rotated out, the batch stops paying
+    await rotateGuardianSet([Keypair.generate(), Keypair.generate()], 2);
+    try {
+      await claim(1, [leaves[0]]);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("StaleGuardianSet");
+    }
+  });
+
+  it("Requires withdrawals to reference a committed hub block", async () => {
+    const guardian = Keypair.generate();
+    await rotateGuardianSet([guardian], 1);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const hubBlockAddress = (blockHash: Buffer) =>
+      PublicKey.findProgramAddressSync([Buffer.from("hub_block"), blockHash], program.programId)[0];
+    const knownHash = Buffer.alloc(32, 42);
+    const height = new anchor.BN(100);
+    const validUntil = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+    const message = createHash("sha256")
+      .update(Buffer.from("cashio-hub-block"))
+      .update(DOMAIN_TAG)
+      .update(HUB_CHAIN_ID.toArrayLike(Buffer, "le", 8))
+      .update(bridgeState.guardianSetEpoch.toArrayLike(Buffer, "le", 8))
+      .update(knownHash)
+      .update(height.toArrayLike(Buffer, "le", 8))
+      .update(validUntil.toArrayLike(Buffer, "le", 8))
+      .digest();
+    const signature = nacl.sign.detached(message, guardian.secretKey);
+    await program.methods
+      .commitHubBlock(Array.from(knownHash), height, validUntil, [Array.from(signature)])
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        hubBlock: hubBlockAddress(knownHash),
+        instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        payer: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .remainingAccounts([
+        { pubkey: guardianAddress(guardian.publicKey), isSigner: false, isWritable: false },
+      ])
+      .preInstructions([
+        Ed25519Program.createInstructionWithPublicKey({
+          publicKey: guardian.publicKey.toBytes(),
+          message,
+          signature,
+        }),
+      ])
+      .rpc();
+    const hubBlock = await program.account.hubBlock.fetch(hubBlockAddress(knownHash));
+    expect(hubBlock.height.toNumber()).to.equal(100);
+
+    const setRequireHubBlock = (required: boolean) =>
+      program.methods
+        .setRequireHubBlock(required)
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+    await setRequireHubBlock(true);
+
+    const recipient = Keypair.generate().publicKey;
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const withdraw = (
+      withdrawalHash: Buffer,
+      sourceHeight: anchor.BN,
+      hub: PublicKey | null,
+      sourceBlockHash: Buffer = knownHash
+    ) =>
+      processWithdrawal(
+        [guardian],
+        withdrawalHash,
+        recipient,
+        amount,
+        true,
+        sourceHeight,
+        new anchor.BN(200),
+        validUntil,
+        DOMAIN_TAG,
+        hub,
+        sourceBlockHash
+      ).rpc();
+
+    try {
+      await withdraw(Buffer.alloc(32, 43), height, null);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("UnknownHubBlock");
+    }
+    // A block hash guardians never committed has no account
+    try {
+      await withdraw(Buffer.alloc(32, 43), height, hubBlockAddress(Buffer.alloc(32, 44)));
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("AccountNotInitialized");
+    }
+    try {
+      await withdraw(Buffer.alloc(32, 43), new anchor.BN(101), hubBlockAddress(knownHash));
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("HubBlockMismatch");
+    }
+    // Guardians signed a different block at the committed height
+    try {
+      await withdraw(Buffer.alloc(32, 43), height, hubBlockAddress(knownHash), Buffer.alloc(32, 44));
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("HubBlockMismatch");
+    }
+
+    await withdraw(Buffer.alloc(32, 43), height, hubBlockAddress(knownHash));
+    expect(await provider.connection.getBalance(recipient)).to.equal(amount.toNumber());
+
+    // Incrementally collected signatures need the hub block at finalize too
+    const collectedHash = Buffer.alloc(32, 51);
+    await submitSignature(
+      guardian, collectedHash, recipient, amount, true, undefined,
+      height, new anchor.BN(200), COLLECTION_VALID_UNTIL, knownHash
+    ).rpc();
+    try {
+      await finalizeWithdrawal(collectedHash, recipient).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("UnknownHubBlock");
+    }
+    await finalizeWithdrawal(collectedHash, recipient, authority, hubBlockAddress(knownHash)).rpc();
+    expect(await provider.connection.getBalance(recipient)).to.equal(2 * amount.toNumber());
+
+    await setRequireHubBlock(false);
+  });
+
+  it("Benchmarks guardian signature verification", async () => {
+    const message = Buffer.alloc(32, 45);
+    const benchmark = async (guardians: Keypair[]) => {
+      await rotateGuardianSet(guardians, 1);
+      const signatures = guardians.map((guardian) =>
+        nacl.sign.detached(message, guardian.secretKey)
+      );
+      const tx = await program.methods
+        .benchmarkVerification(
+          Array.from(message),
+          signatures.map((signature) => Array.from(signature))
+        )
+        .accounts({ instructions: SYSVAR_INSTRUCTIONS_PUBKEY })
+        .remainingAccounts(
+          guardians.map((guardian) => ({
+            pubkey: guardianAddress(guardian.publicKey),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .preInstructions(
+          guardians.map((guardian, i) =>
+            Ed25519Program.createInstructionWithPublicKey({
+              publicKey: guardian.publicKey.toBytes(),
+              message,
+              signature: signatures[i],
+            })
+          )
+        )
+        .rpc({ commitment: "confirmed" });
+      const txInfo = await provider.connection.getTransaction(tx, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const report = txInfo.meta.logMessages
+        .map((log) => log.match(/Verified (\d+) signatures \((\d+) active guardians\) in (\d+) compute units/))
+        .find((match) => match !== null);
+      expect(report).to.not.be.undefined;
+      expect(Number(report[1])).to.equal(guardians.length);
+      expect(Number(report[2])).to.equal(guardians.length);
+      return Number(report[3]);
+    };
+
+    const one = await benchmark([Keypair.generate()]);
+    const three = await benchmark([Keypair.generate(), Keypair.generate(), Keypair.generate()]);
+    expect(one).to.be.greaterThan(0);
+    expect(three).to.be.greaterThan(one);
+    expect(three).to.be.lessThan(200_000);
+  });
+
+  it("Sweeps only vault tokens above the deposit liability", async () => {
+    const { mint, depositorTokenAccount } = await setupMint(6);
+    const deposited = new anchor.BN(30_000_000);
+    await depositToken(mint, depositorTokenAccount, deposited, 46);
+    const [vaultTokenPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_token"), mint.toBuffer()],
+      program.programId
+    );
+    const [tokenConfigPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_config"), mint.toBuffer()],
+      program.programId
+    );
+    expect((await program.account.tokenConfig.fetch(tokenConfigPDA)).liability.toNumber()).to.equal(
+      deposited.toNumber()
+    );
+
+    // Tokens sent straight to the vault back no deposit
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    await mintTo(provider.connection, payer, mint, vaultTokenPDA, payer, 1_234);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const collector = await createAccount(
+      provider.connection,
+      payer,
+      mint,
+      bridgeState.feeCollector,
+      Keypair.generate()
+    );
+    const sweep = () =>
+      program.methods
+        .sweepDust()
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          mint,
+          tokenConfig: tokenConfigPDA,
+          vaultTokenAccount: vaultTokenPDA,
+          collectorTokenAccount: collector,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+    await sweep();
+    expect(Number((await getAccount(provider.connection, collector)).amount)).to.equal(1_234);
+    expect(Number((await getAccount(provider.connection, vaultTokenPDA)).amount)).to.equal(
+      deposited.toNumber()
+    );
+
+    try {
+      await sweep();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("NothingToSweep");
+    }
+  });
+
+  it("Refuses to sweep a mint until its pre-tracking liability is backfilled", async () => {
+    // Migrated legacy config whose genesis vault holds 1_000_000 tokens of
+    // deposits made before liabilities were tracked
+    const mint = new PublicKey(Buffer.alloc(32, 0x4d));
+    const mintAuthority = Keypair.fromSeed(Buffer.alloc(32, 0x4d));
+    const [vaultTokenPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_token"), mint.toBuffer()],
+      program.programId
+    );
+    const [tokenConfigPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_config"), mint.toBuffer()],
+      program.programId
+    );
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const collector = await createAccount(
+      provider.connection,
+      payer,
+      mint,
+      bridgeState.feeCollector,
+      Keypair.generate()
+    );
+    const sweep = () =>
+      program.methods
+        .sweepDust()
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          mint,
+          tokenConfig: tokenConfigPDA,
+          vaultTokenAccount: vaultTokenPDA,
+          collectorTokenAccount: collector,
+          authority,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    const backfill = (liability: number) =>
+      program.methods
+        .backfillTokenLiability(new anchor.BN(liability))
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          mint,
+          tokenConfig: tokenConfigPDA,
+          authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    const config = await program.account.tokenConfig.fetch(tokenConfigPDA);
+    expect(config.liabilityTracked).to.be.false;
+    expect(config.liability.toNumber()).to.equal(0);
+    try {
+      await sweep();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("LiabilityNotTracked");
+    }
+
+    await backfill(1_000_000);
+    try {
+      await backfill(0);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("LiabilityAlreadyTracked");
+    }
+    try {
+      await sweep();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("NothingToSweep");
+    }
+
+    await mintTo(provider.connection, payer, mint, vaultTokenPDA, mintAuthority, 25);
+    await sweep();
+    expect(Number((await getAccount(provider.connection, collector)).amount)).to.equal(25);
+    expect(Number((await getAccount(provider.connection, vaultTokenPDA)).amount)).to.equal(
+      1_000_000
+    );
+  });
+
+  it("Acknowledges a deposit on threshold guardian signatures", async () => {
+    const guardians = [Keypair.generate(), Keypair.generate()];
+    await rotateGuardianSet(guardians, 2);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const nonce = bridgeState.depositNonce;
+    const commitment = Buffer.alloc(32, 47);
+    const [depositPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("deposit"), nonce.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .depositSol(new anchor.BN(0.01 * LAMPORTS_PER_SOL), Array.from(commitment))
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        deposit: depositPDA,
+        vault: vaultPDA,
+        depositor: authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const message = createHash("sha256")
+      .update(Buffer.from("cashio-deposit-ack"))
+      .update(DOMAIN_TAG)
+      .update(HUB_CHAIN_ID.toArrayLike(Buffer, "le", 8))
+      .update(bridgeState.guardianSetEpoch.toArrayLike(Buffer, "le", 8))
+      .update(nonce.toArrayLike(Buffer, "le", 8))
+      .update(commitment)
+      .digest();
+    const ackDeposit = (signers: Keypair[]) => {
+      const signatures = signers.map((guardian) =>
+        nacl.sign.detached(message, guardian.secretKey)
+      );
+      return program.methods
+        .ackDeposit(signatures.map((signature) => Array.from(signature)))
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          deposit: depositPDA,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          payer: authority,
+        })
+        .remainingAccounts(
+          signers.map((guardian) => ({
+            pubkey: guardianAddress(guardian.publicKey),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .preInstructions(
+          signers.map((guardian, i) =>
+            Ed25519Program.createInstructionWithPublicKey({
+              publicKey: guardian.publicKey.toBytes(),
+              message,
+              signature: signatures[i],
+            })
+          )
+        )
+        .rpc();
+    };
+
+    try {
+      await ackDeposit(guardians.slice(0, 1));
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InsufficientSignatures");
+    }
+    expect((await program.account.deposit.fetch(depositPDA)).processed).to.be.false;
+
+    await ackDeposit(guardians);
+    expect((await program.account.deposit.fetch(depositPDA)).processed).to.be.true;
+
+    try {
+      await ackDeposit(guardians);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("DepositAlreadyAcknowledged");
+    }
+
+    // Acknowledged deposits can be closed for their rent
+    const rent = await provider.connection.getBalance(depositPDA);
+    const tx = await program.methods
+      .closeDeposit(nonce)
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        deposit: depositPDA,
+        depositor: authority,
+        authority: authority,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    const events = [...parser.parseLogs(txInfo.meta.logMessages)];
+    const closed = events.find((e) => e.name === "depositClosed");
+    expect(closed).to.not.be.undefined;
+    expect(closed.data.nonce.toString()).to.equal(nonce.toString());
+    expect(closed.data.rentReturned.toString()).to.equal(rent.toString());
+    expect(await provider.connection.getAccountInfo(depositPDA)).to.be.null;
+  });
+
+  it("Acknowledges a token deposit on threshold guardian signatures", async () => {
+    const guardians = [Keypair.generate(), Keypair.generate()];
+    await rotateGuardianSet(guardians, 2);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    const { mint, depositorTokenAccount } = await setupMint(6);
+    const tokenDepositPDA = await depositToken(
+      mint,
+      depositorTokenAccount,
+      new anchor.BN(20_000_000),
+      57
+    );
+
+    const message = createHash("sha256")
+      .update(Buffer.from("cashio-deposit-ack"))
+      .update(DOMAIN_TAG)
+      .update(HUB_CHAIN_ID.toArrayLike(Buffer, "le", 8))
+      .update(bridgeState.guardianSetEpoch.toArrayLike(Buffer, "le", 8))
+      .update(bridgeState.depositNonce.toArrayLike(Buffer, "le", 8))
+      .update(Buffer.alloc(32, 57))
+      .digest();
+    const ackTokenDeposit = (signers: Keypair[]) => {
+      const signatures = signers.map((guardian) =>
+        nacl.sign.detached(message, guardian.secretKey)
+      );
+      return program.methods
+        .ackTokenDeposit(signatures.map((signature) => Array.from(signature)))
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          tokenDeposit: tokenDepositPDA,
+          instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          payer: authority,
+        })
+        .remainingAccounts(
+          signers.map((guardian) => ({
+            pubkey: guardianAddress(guardian.publicKey),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .preInstructions(
+          signers.map((guardian, i) =>
+            Ed25519Program.createInstructionWithPublicKey({
+              publicKey: guardian.publicKey.toBytes(),
+              message,
+              signature: signatures[i],
+            })
+          )
+        )
+        .rpc();
+    };
+
+    try {
+      await ackTokenDeposit(guardians.slice(0, 1));
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("InsufficientSignatures");
+    }
+    expect((await program.account.tokenDeposit.fetch(tokenDepositPDA)).processed).to.be.false;
+
+    await ackTokenDeposit(guardians);
+    expect((await program.account.tokenDeposit.fetch(tokenDepositPDA)).processed).to.be.true;
+
+    try {
+      await ackTokenDeposit(guardians);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("DepositAlreadyAcknowledged");
+    }
+  });
+
+  it("Rate-limits back-to-back guardian changes", async () => {
+    const setChangeInterval = (seconds: number) =>
+      program.methods
+        .setMinGuardianChangeInterval(new anchor.BN(seconds))
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+    const guardian = Keypair.generate();
+    await addGuardian(guardian);
+
+    await setChangeInterval(3600);
+    const bridgeState = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(bridgeState.minGuardianChangeInterval.toNumber()).to.equal(3600);
+    expect(bridgeState.lastGuardianChange.toNumber()).to.be.greaterThan(0);
+    try {
+      await removeGuardian(guardian);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("GuardianChangeTooSoon");
+      expect(err.logs.join("\n")).to.include("at least 3600 seconds apart");
+    }
+    try {
+      await addGuardian(Keypair.generate());
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("GuardianChangeTooSoon");
+    }
+    try {
+      await rotateGuardianSet([Keypair.generate()], 1);
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("GuardianChangeTooSoon");
+    }
+
+    await setChangeInterval(0);
+    await removeGuardian(guardian);
+    await rotateGuardianSet([guardian], 1);
+    const rotated = await program.account.bridgeState.fetch(bridgeStatePDA);
+    expect(rotated.lastGuardianChange.toNumber()).to.be.at.least(
+      bridgeState.lastGuardianChange.toNumber()
+    );
+  });
+
+  it("Keeps withdrawals out of the vault liquidity buffer", async () => {
+    const guardian = Keypair.generate();
+    await rotateGuardianSet([guardian], 1);
+    const setBuffer = (buffer: anchor.BN) =>
+      program.methods
+        .setMinLiquidityBuffer(buffer)
+        .accounts({ bridgeState: bridgeStatePDA, authority })
+        .rpc();
+
+    const amount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+    const rentExempt = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const vaultBalance = await provider.connection.getBalance(vaultPDA);
+    const boundary = new anchor.BN(vaultBalance - rentExempt).sub(amount);
+    const recipient = Keypair.generate().publicKey;
+
+    await setBuffer(boundary.addn(1));
+    try {
+      await processWithdrawal([guardian], Buffer.alloc(32, 48), recipient, amount).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("LiquidityBufferBreached");
+    }
+
+    await setBuffer(boundary);
+    await processWithdrawal([guardian], Buffer.alloc(32, 48), recipient, amount).rpc();
+    expect(await provider.connection.getBalance(vaultPDA)).to.equal(
+      rentExempt + boundary.toNumber()
+    );
+
+    // Incrementally collected signatures pay out through the same check
+    const collectedHash = Buffer.alloc(32, 52);
+    await setBuffer(boundary.sub(amount).addn(1));
+    await submitSignature(guardian, collectedHash, recipient, amount).rpc();
+    try {
+      await finalizeWithdrawal(collectedHash, recipient).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("LiquidityBufferBreached");
+    }
+
+    await setBuffer(new anchor.BN(0));
+  });
+
+  it("Pauses withdrawals of a paused mint", async () => {
+    const guardian = Keypair.generate();
+    await rotateGuardianSet([guardian], 1);
+    const { mint, depositorTokenAccount } = await setupMint(6);
+    await depositToken(mint, depositorTokenAccount, new anchor.BN(50_000_000), 53);
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const recipient = await createAccount(provider.connection, payer, mint, Keypair.generate().publicKey);
+    const setTokenPaused = (paused: boolean) =>
+      program.methods
+        .setTokenPaused(paused)
+        .accounts({
+          bridgeState: bridgeStatePDA,
+          mint,
+          tokenConfig: PublicKey.findProgramAddressSync(
+            [Buffer.from("token_config"), mint.toBuffer()],
+            program.programId
+          )[0],
+          authority,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    const withdrawalHash = Buffer.alloc(32, 54);
+    const amount = new anchor.BN(20_000_000);
+    await setTokenPaused(true);
+    try {
+      await processTokenWithdrawal(guardian, mint, withdrawalHash, amount, recipient).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("TokenPaused");
+    }
+    expect(Number((await getAccount(provider.connection, recipient)).amount)).to.equal(0);
+
+    await setTokenPaused(false);
+    await processTokenWithdrawal(guardian, mint, withdrawalHash, amount, recipient).rpc();
+    expect(Number((await getAccount(provider.connection, recipient)).amount)).to.equal(
+      amount.toNumber()
+    );
+  });
+
+  it("Enforces a mint's minimum token withdrawal", async () => {
+    const guardian = Keypair.generate();
+    await rotateGuardianSet([guardian], 1);
+    const { mint, depositorTokenAccount } = await setupMint(6);
+    await depositToken(mint, depositorTokenAccount, new anchor.BN(50_000_000), 55);
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const recipient = await createAccount(provider.connection, payer, mint, Keypair.generate().publicKey);
+    const tokenConfigPDA = PublicKey.findProgramAddressSync(
+      [Buffer.from("token_config"), mint.toBuffer()],
+      program.programId
+    )[0];
+    await program.methods
+      .setTokenMinWithdrawal(new anchor.BN(10_000_000))
+      .accounts({
+        bridgeState: bridgeStatePDA,
+        mint,
+        tokenConfig: tokenConfigPDA,
+        authority,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    const config = await program.account.tokenConfig.fetch(tokenConfigPDA);
+    expect(config.minWithdrawal.toNumber()).to.equal(10_000_000);
+
+    const withdrawalHash = Buffer.alloc(32, 56);
+    try {
+      await processTokenWithdrawal(guardian, mint, withdrawalHash, new anchor.BN(9_999_999), recipient).rpc();
+      expect.fail("Should have thrown");
+    } catch (err: any) {
+      expect(err.toString()).to.include("WithdrawalTooSmall");
+    }
+
+    await processTokenWithdrawal(guardian, mint, withdrawalHash, new anchor.BN(10_000_000), recipient).rpc();
+    expect(Number((await getAccount(provider.connection, recipient)).amount)).to.equal(10_000_000);
+  });
 });